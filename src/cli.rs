@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use crate::query::PropertyFilter;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 const LONG_ABOUT: &str = r#"
@@ -30,11 +31,12 @@ CLI STRUCTURE:
   Groups:
     init     - Database initialization
     index    - Vault indexing (scan, status)
-    search   - Search and retrieval (notes, backlinks, links, tags, unresolved)
+    search   - Search and retrieval (notes, grep, backlinks, links, tags, unresolved, mentions, blocks, callouts, tasks, properties)
+    query    - Dataview-style queries (dql)
     graph    - Graph operations (neighbors, paths, centrality, components)
     analyze  - Content analysis (bloat, related, similar, quality)
     diagnose - Diagnostics (orphans, broken-links, conflicts)
-    view     - Display commands (stats, describe, health)
+    view     - Display commands (stats, describe, outline, health)
 
 EXAMPLES:
   # Initialize and index your vault
@@ -44,12 +46,30 @@ EXAMPLES:
   # Search for notes containing 'rust'
   obsidian-cli-inspector search notes rust --limit 10
 
+  # Find TODO(owner) markers with a line of context
+  obsidian-cli-inspector search grep 'TODO\(\w+\)' -C 1
+
   # Find all notes linking to 'Project Ideas'
   obsidian-cli-inspector search backlinks "Project Ideas"
 
+  # Find plain-text mentions of 'Deep Work' that are not linked yet
+  obsidian-cli-inspector search mentions "Deep Work"
+
   # List all notes tagged with 'work'
   obsidian-cli-inspector search tags work
 
+  # List open tasks that are past their due date
+  obsidian-cli-inspector search tasks --overdue
+
+  # Find decision callouts that mention SQLite
+  obsidian-cli-inspector search callouts --type decision sqlite
+
+  # Find notes by frontmatter or inline field values
+  obsidian-cli-inspector search properties "rating>=4" status=done
+
+  # Run a Dataview-style query
+  obsidian-cli-inspector query dql 'TABLE rating FROM #books WHERE rating >= 4 SORT rating DESC'
+
   # Find large notes that might need splitting
   obsidian-cli-inspector analyze bloat --threshold 100000
 
@@ -98,6 +118,10 @@ pub enum Commands {
     #[command(subcommand)]
     Search(SearchCommands),
 
+    /// Dataview-style query commands
+    #[command(subcommand)]
+    Query(QueryCommands),
+
     // /// Graph operations commands
     // #[command(subcommand)]
     // Graph(GraphCommands),
@@ -113,6 +137,36 @@ pub enum Commands {
     #[command(subcommand)]
     View(ViewCommands),
 
+    /// Answer a question from your notes with the `[llm]` chat model, citing its sources
+    Ask {
+        /// Question to answer
+        question: String,
+
+        /// Chunks given to the model as context
+        #[arg(short, long, default_value_t = crate::commands::ask::DEFAULT_CONTEXT_CHUNKS)]
+        limit: usize,
+
+        /// Print the retrieved chunks before the answer
+        #[arg(long)]
+        show_context: bool,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
+    },
+
     /// Launch interactive TUI
     Tui,
 }
@@ -149,6 +203,17 @@ pub enum IndexCommands {
         #[arg(short, long)]
         verbose: bool,
     },
+
+    /// Embed indexed chunks with the [llm] embedding endpoint for semantic search
+    Embed {
+        /// Embed every chunk again, not only new and changed ones
+        #[arg(short, long)]
+        force: bool,
+
+        /// Chunks sent per request
+        #[arg(long, default_value_t = crate::embeddings::DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+    },
     // /// Show indexing status
     // Status,
 
@@ -169,6 +234,140 @@ pub enum SearchCommands {
         /// Maximum number of results
         #[arg(short, long, default_value = "20")]
         limit: usize,
+
+        /// Tokens in each result snippet (1-64, default from config)
+        #[arg(long)]
+        snippet_tokens: Option<usize>,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
+
+        /// Only notes modified on or after this date
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+        modified_after: Option<String>,
+
+        /// Only notes modified before this date
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+        modified_before: Option<String>,
+
+        /// Property filter such as `status=done` or `rating>=4` (repeatable, all must match)
+        #[arg(long = "prop", value_name = "FILTER")]
+        props: Vec<PropertyFilter>,
+
+        /// List matching chunks, or one result per note ranked with the `[search]` boosts
+        #[arg(long, value_enum, default_value_t = SearchGroupBy::Chunk)]
+        group_by: SearchGroupBy,
+    },
+
+    /// Search note files line by line with a regular expression
+    Grep {
+        /// Regular expression (Rust `regex` syntax)
+        pattern: String,
+
+        /// Maximum number of matching lines
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+
+        /// Lines of context before and after each match
+        #[arg(short = 'C', long, default_value = "0")]
+        context: usize,
+
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
+    },
+
+    /// Search chunks by meaning with the embeddings from `index embed`
+    Semantic {
+        /// Search query
+        query: String,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
+    },
+
+    /// Search with full-text and embedding rankings fused by reciprocal rank
+    Hybrid {
+        /// Search query, in `search notes` syntax for the full-text ranking
+        query: String,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Tokens in each result snippet (1-64, default from config)
+        #[arg(long)]
+        snippet_tokens: Option<usize>,
+
+        /// Weight of the full-text ranking (default from config; 0 leaves it out)
+        #[arg(long)]
+        keyword_weight: Option<f64>,
+
+        /// Weight of the embedding ranking (default from config; 0 leaves it out)
+        #[arg(long)]
+        semantic_weight: Option<f64>,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
     },
 
     /// List backlinks to a note
@@ -186,6 +385,82 @@ pub enum SearchCommands {
     /// List all unresolved links in the vault
     Unresolved,
 
+    /// Find unlinked mentions of a note's title or aliases
+    Mentions {
+        /// Note path or title
+        note: String,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Look up the paragraph marked by a `^block-id` anchor
+    Blocks {
+        /// Block id (with or without the leading ^)
+        id: String,
+
+        /// Only look in this note
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+
+    /// List callouts such as `> [!warning]` or `> [!decision]`, optionally filtered by text
+    Callouts {
+        /// Text to look for in callout titles and bodies
+        query: Option<String>,
+
+        /// Callout type; built-in types include their aliases (warning, todo, question, ...)
+        #[arg(short = 't', long = "type", value_name = "TYPE")]
+        kind: Option<String>,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+
+    /// Find notes by frontmatter properties and inline fields, or list property keys
+    Properties {
+        /// Filters such as `status=done`, `rating>=4`, `due<2024-06-01` or `author~newport`
+        #[arg(value_name = "FILTER")]
+        filters: Vec<PropertyFilter>,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+
+    /// List checkbox tasks across the vault
+    Tasks {
+        /// Only open tasks (not done or cancelled)
+        #[arg(long)]
+        open: bool,
+
+        /// Only open tasks whose due date has passed
+        #[arg(long)]
+        overdue: bool,
+
+        /// Only tasks due before this date
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+        due_before: Option<String>,
+
+        /// Only tasks carrying this tag or a tag nested under it
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Only tasks in notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only tasks in notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+
     /// List notes by tag
     Tags {
         /// Tag name (without #)
@@ -194,6 +469,26 @@ pub enum SearchCommands {
         /// List all tags if no tag specified
         #[arg(short, long)]
         list: bool,
+
+        /// Match the tag only, not its nested tags (`project` without `project/alpha`)
+        #[arg(long)]
+        exact: bool,
+
+        /// Show the tag hierarchy with note counts, under the tag if one is given
+        #[arg(long, conflicts_with_all = ["list", "exact"])]
+        tree: bool,
+    },
+}
+
+// ============================================================================
+// QUERY Commands
+// ============================================================================
+#[derive(Subcommand)]
+pub enum QueryCommands {
+    /// Run a read-only Dataview query (LIST or TABLE, FROM, WHERE, SORT, LIMIT)
+    Dql {
+        /// Query text, e.g. 'TABLE rating FROM #books WHERE rating >= 4'
+        query: String,
     },
 }
 
@@ -248,6 +543,69 @@ pub enum AnalyzeCommands {
         #[arg(short, long, default_value = "10")]
         limit: usize,
     },
+
+    /// Analyze tag co-occurrence and suggest tags for untagged or lightly tagged notes
+    Tags {
+        /// Minimum number of notes a pair of tags must share
+        #[arg(long, default_value = "2")]
+        min_count: usize,
+
+        /// Suggest tags for notes with at most this many tags
+        #[arg(long, default_value = "1")]
+        max_tags: usize,
+
+        /// Maximum number of suggested tags per note
+        #[arg(long, default_value = "3")]
+        suggestions: usize,
+
+        /// Maximum number of entries per section
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Summarize a note, or every note matching the filters, with the `[llm]` chat model
+    Summarize {
+        /// Note path, title or alias; omit to summarize the notes matching the filters
+        note: Option<String>,
+
+        /// Only notes whose path matches the glob (repeatable; `*` also matches `/`)
+        #[arg(long = "path", value_name = "GLOB")]
+        paths: Vec<String>,
+
+        /// Only notes in the folder or its subfolders (repeatable)
+        #[arg(long = "folder", value_name = "FOLDER")]
+        folders: Vec<String>,
+
+        /// Only notes with the tag or a tag nested under it (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Require every --tag instead of any of them
+        #[arg(long)]
+        all_tags: bool,
+
+        /// Only notes modified on or after this date
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+        modified_after: Option<String>,
+
+        /// Only notes modified before this date
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_date)]
+        modified_before: Option<String>,
+
+        /// Ask the model again instead of using cached summaries
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Suggest a title and tags for a note with the `[llm]` chat model
+    SuggestTags {
+        /// Note path, title or alias
+        note: String,
+
+        /// Ask the model again instead of using the cached suggestions
+        #[arg(long)]
+        refresh: bool,
+    },
     // /// Find similar notes based on content
     // Similar {
     //     /// Note path or title
@@ -281,6 +639,12 @@ pub enum DiagnoseCommands {
 
     /// Diagnose broken links (unresolved and ambiguous)
     BrokenLinks,
+
+    /// Diagnose footnotes (missing definitions, unused definitions, duplicate labels)
+    Footnotes,
+
+    /// Diagnose likely duplicate tags (case, plural and spelling variants)
+    Tags,
     // /// Diagnose note conflicts
     // Conflicts,
 }
@@ -298,6 +662,37 @@ pub enum ViewCommands {
         /// File path or title to describe
         filename: String,
     },
+
+    /// Show the heading tree of a note
+    Outline {
+        /// Note path, title or alias
+        note: String,
+    },
     // /// Show health metrics
     // Health,
 }
+
+/// How `search notes` reports matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchGroupBy {
+    /// Every matching chunk
+    Chunk,
+    /// One result per note
+    Note,
+}
+
+impl SearchGroupBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchGroupBy::Chunk => "chunk",
+            SearchGroupBy::Note => "note",
+        }
+    }
+}
+
+/// Validate a `YYYY-MM-DD` date argument
+fn parse_date(value: &str) -> Result<String, String> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .map_err(|_| format!("invalid date '{value}', expected YYYY-MM-DD"))
+}
@@ -1,39 +1,73 @@
+pub mod ask;
 pub mod index;
+pub mod index_embed;
 pub mod init;
 
 pub mod query_backlinks;
+pub mod query_blocks;
+pub mod query_callouts;
+pub mod query_dql;
+pub mod query_grep;
+pub mod query_hybrid;
 pub mod query_links;
+pub mod query_mentions;
+pub mod query_properties;
 pub mod query_search;
+pub mod query_semantic;
 pub mod query_tags;
+pub mod query_tasks;
 pub mod query_unresolved;
 
 pub mod analyze_bloat;
 pub mod analyze_related;
+pub mod analyze_suggest_tags;
+pub mod analyze_summarize;
+pub mod analyze_tags;
 
 pub mod diagnose_broken_links;
+pub mod diagnose_footnotes;
 pub mod diagnose_orphans;
+pub mod diagnose_tags;
 
 pub mod stats;
 pub mod view_describe;
+pub mod view_outline;
 
 pub mod tui;
 
+pub use ask::ask_vault;
 pub use index::index_vault;
+pub use index_embed::embed_vault;
 pub use init::initialize_database;
 
 pub use query_backlinks::get_backlinks;
+pub use query_blocks::find_blocks;
+pub use query_callouts::list_callouts;
+pub use query_dql::run_dql_query;
+pub use query_grep::grep_vault;
+pub use query_hybrid::hybrid_search_vault;
 pub use query_links::get_forward_links;
-pub use query_search::search_vault;
-pub use query_tags::list_notes_by_tag;
+pub use query_mentions::list_unlinked_mentions;
+pub use query_properties::search_properties;
+pub use query_search::{search_vault, search_vault_by_note};
+pub use query_semantic::semantic_search_vault;
+pub use query_tags::{list_notes_by_tag, show_tag_tree};
+pub use query_tasks::list_tasks;
 pub use query_unresolved::list_unresolved_links;
 
 pub use analyze_bloat::show_bloat;
 pub use analyze_related::analyze_related;
+pub use analyze_suggest_tags::suggest_tags_vault;
+pub use analyze_summarize::summarize_vault;
+pub use analyze_tags::analyze_tags_cmd;
 
 pub use diagnose_broken_links::diagnose_broken_links_cmd;
+pub use diagnose_footnotes::diagnose_footnotes_cmd;
 pub use diagnose_orphans::diagnose_orphans;
+pub use diagnose_tags::diagnose_tags_cmd;
 
 pub use stats::show_stats;
 pub use view_describe::get_note_describe;
+pub use view_outline::show_outline;
 
 pub use tui::show_tui;
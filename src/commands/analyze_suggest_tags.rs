@@ -0,0 +1,234 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::llm::LlmClient;
+use crate::logger::Logger;
+use crate::parser::tag_key;
+use crate::query::NoteCandidate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::analyze_summarize::{cached_chat, chat_client, note_text, open_database, resolve_note};
+
+/// Most used vault tags offered to the model for reuse
+const VAULT_TAGS: usize = 100;
+
+const SUGGEST_PROMPT: &str = "You organize notes in a personal knowledge base. Suggest a concise \
+title and three to seven tags for the note. Prefer the existing vault tags when they fit. Tags \
+are lowercase, use hyphens instead of spaces and may be nested with '/'. Reply with JSON only, \
+in the form {\"title\": \"...\", \"tags\": [\"...\"]}.";
+
+/// A suggested tag, `existing` when some note in the vault already has it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSuggestion {
+    pub tag: String,
+    pub existing: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NoteSuggestions {
+    pub note_path: String,
+    pub note_title: String,
+    /// Suggested title
+    pub title: String,
+    /// Suggested tags the note does not have yet
+    pub tags: Vec<TagSuggestion>,
+    pub cached: bool,
+}
+
+/// Title and tags from a JSON reply, which may be wrapped in a code fence or prose
+fn parse_suggestions(reply: &str) -> Result<(String, Vec<String>)> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => anyhow::bail!(
+            "Expected a JSON object from the model, got: {}",
+            reply.trim()
+        ),
+    };
+    let value: Value = serde_json::from_str(json).with_context(|| {
+        format!(
+            "Expected a JSON object from the model, got: {}",
+            reply.trim()
+        )
+    })?;
+
+    let title = value
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let tags = value
+        .get("tags")
+        .and_then(Value::as_array)
+        .context("The model's reply has no `tags` array")?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|tag| {
+            tag.trim()
+                .trim_start_matches('#')
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    Ok((title, tags))
+}
+
+/// Tags of the vault by number of notes, most used first
+fn vault_tags(db: &Database) -> Result<Vec<String>> {
+    db.conn()
+        .execute_query(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT MIN(display) FROM tags GROUP BY tag
+                 ORDER BY COUNT(DISTINCT note_id) DESC, tag LIMIT ?1",
+            )?;
+            let tags = stmt
+                .query_map([VAULT_TAGS as i64], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(tags)
+        })
+        .context("Failed to list vault tags")
+}
+
+/// Keys of the tags `note_id` has, and of every tag in the vault
+fn tag_keys(db: &Database, note_id: i64) -> Result<(HashSet<String>, HashSet<String>)> {
+    db.conn()
+        .execute_query(|conn| {
+            let mut stmt = conn.prepare("SELECT DISTINCT note_id = ?1, tag FROM tags")?;
+            let mut note = HashSet::new();
+            let mut vault = HashSet::new();
+            let mut rows = stmt.query([note_id])?;
+            while let Some(row) = rows.next()? {
+                let tag: String = row.get(1)?;
+                if row.get::<_, bool>(0)? {
+                    note.insert(tag.clone());
+                }
+                vault.insert(tag);
+            }
+            Ok((note, vault))
+        })
+        .context("Failed to read tags")
+}
+
+/// Suggest a title and tags for one note, from the cache when its content is
+/// unchanged. Tags the note already has are left out.
+pub fn suggest_for_note(
+    db: &Database,
+    client: &LlmClient,
+    note: NoteCandidate,
+    refresh: bool,
+) -> Result<NoteSuggestions> {
+    let note_id = note.id;
+    let note = note_text(db, note)?;
+    let (note_tags, vault) = tag_keys(db, note_id)?;
+
+    let ((title, tags), cached) = if note.text.is_empty() {
+        ((String::new(), Vec::new()), false)
+    } else {
+        let prompt = format!(
+            "Existing vault tags: {}\n\n{}",
+            vault_tags(db)?.join(", "),
+            note.prompt()
+        );
+        cached_chat(
+            db,
+            client,
+            "tags",
+            &note,
+            refresh,
+            |client| client.chat(SUGGEST_PROMPT, &prompt),
+            parse_suggestions,
+        )
+        .with_context(|| format!("Failed to suggest tags for {}", note.note.path))?
+    };
+
+    let mut seen = HashSet::new();
+    let tags = tags
+        .into_iter()
+        .filter(|tag| {
+            let key = tag_key(tag);
+            !note_tags.contains(&key) && seen.insert(key)
+        })
+        .map(|tag| TagSuggestion {
+            existing: vault.contains(&tag_key(&tag)),
+            tag,
+        })
+        .collect();
+
+    Ok(NoteSuggestions {
+        note_path: note.note.path,
+        note_title: note.note.title,
+        title,
+        tags,
+        cached,
+    })
+}
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("analyze.suggest-tags", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+/// Print a title and tags suggested for `note`. Suggestions are cached in
+/// the database; nothing is written to the vault.
+pub fn suggest_tags_vault(
+    config: &Config,
+    note: &str,
+    refresh: bool,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let client = chat_client(config)?;
+    let db = open_database(config)?;
+
+    let Some(resolved) = resolve_note(&db, note)? else {
+        emit(logger, &format!("Note not found: {note}"));
+        return Ok(());
+    };
+
+    let suggestions = suggest_for_note(&db, &client, resolved, refresh)?;
+    let cached = if suggestions.cached { " [cached]" } else { "" };
+    let mut lines = vec![format!(
+        "Suggestions for '{}' ({}){cached}:",
+        suggestions.note_title, suggestions.note_path
+    )];
+    if !suggestions.title.is_empty() {
+        lines.push(format!("  Title: {}", suggestions.title));
+    }
+    if suggestions.tags.is_empty() {
+        lines.push("  Tags: (none)".to_string());
+    } else {
+        let tags: Vec<String> = suggestions
+            .tags
+            .iter()
+            .map(|t| {
+                let new = if t.existing { "" } else { " (new)" };
+                format!("#{}{new}", t.tag)
+            })
+            .collect();
+        lines.push(format!("  Tags: {}", tags.join(", ")));
+    }
+    emit(logger, &lines.join("\n"));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let reply = "```json\n{\"title\": \" Weekly Standup \", \"tags\": [\"#Meetings\", \"team sync\", \"\", 3]}\n```";
+        let (title, tags) = parse_suggestions(reply).unwrap();
+        assert_eq!(title, "Weekly Standup");
+        assert_eq!(tags, vec!["Meetings", "team-sync"]);
+
+        assert!(parse_suggestions("No idea").is_err());
+        assert!(parse_suggestions("{\"title\": \"x\"}").is_err());
+    }
+}
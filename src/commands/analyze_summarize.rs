@@ -0,0 +1,297 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::embeddings::content_hash;
+use crate::llm::{LlmClient, LlmError};
+use crate::logger::Logger;
+use crate::query::{self, NoteCandidate, SearchOptions};
+use anyhow::{Context, Result};
+
+/// Characters of note text sent to the model
+const NOTE_CHARS: usize = 24_000;
+
+const SUMMARY_PROMPT: &str = "You summarize notes from a personal knowledge base. Reply with a \
+TL;DR of one to three sentences, followed by up to five bullet points with the key points, \
+decisions and action items. Write in the language of the note and reply with the summary only.";
+
+/// A note's text as sent to the model
+pub(crate) struct NoteText {
+    pub note: NoteCandidate,
+    pub text: String,
+    /// SHA-256 of the title and text, the cache key of replies about the note
+    pub hash: String,
+}
+
+impl NoteText {
+    /// Title and text, cut to `NOTE_CHARS`
+    pub fn prompt(&self) -> String {
+        let text = match self.text.char_indices().nth(NOTE_CHARS) {
+            Some((end, _)) => format!("{}\n[...]", &self.text[..end]),
+            None => self.text.clone(),
+        };
+        format!("Title: {}\n\n{}", self.note.title, text)
+    }
+}
+
+/// A note summary, `cached` when no request was needed
+#[derive(Debug, Clone)]
+pub struct NoteSummary {
+    pub note_path: String,
+    pub note_title: String,
+    /// Empty for notes without text, which are not sent
+    pub summary: String,
+    pub cached: bool,
+}
+
+/// Client for the `[llm]` chat model
+pub(crate) fn chat_client(config: &Config) -> Result<LlmClient> {
+    let llm = config.llm.as_ref().context(
+        "No [llm] section in the config; set api_url and model to use a chat completions endpoint",
+    )?;
+    Ok(LlmClient::new(llm)?)
+}
+
+/// Bring the schema up to date, as replies are cached in the database
+pub(crate) fn prepare_cache(db: &Database) -> Result<()> {
+    // Rebuilds the schema when the database was created by an older version
+    db.initialize(false)
+        .context("Failed to upgrade database schema")?;
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn open_database(config: &Config) -> Result<Database> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+    prepare_cache(&db)?;
+    Ok(db)
+}
+
+/// The note `note` resolves to, or none when nothing matches
+pub(crate) fn resolve_note(db: &Database, note: &str) -> Result<Option<NoteCandidate>> {
+    Ok(db
+        .conn()
+        .execute_query(|conn| query::resolve_note(conn, note))
+        .context("Failed to resolve note")?
+        .into_result(note)?)
+}
+
+/// The notes to summarize: `note` alone, or every note passing `filters`
+pub(crate) fn select_notes(
+    db: &Database,
+    note: Option<&str>,
+    filters: &SearchOptions,
+) -> Result<Vec<NoteCandidate>> {
+    if let Some(note) = note {
+        return Ok(resolve_note(db, note)?.into_iter().collect());
+    }
+
+    let mut params = Vec::new();
+    let conditions = filters.note_conditions("n", &mut params);
+    if conditions.is_empty() {
+        anyhow::bail!(
+            "Give a note, or select the notes to summarize with --path, --folder, --tag, \
+             --modified-after or --modified-before"
+        );
+    }
+    db.conn()
+        .execute_query(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT n.id, n.path, n.title FROM notes n WHERE {} ORDER BY n.path",
+                conditions.join(" AND ")
+            ))?;
+            let notes = stmt
+                .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                    Ok(NoteCandidate {
+                        id: row.get(0)?,
+                        path: row.get(1)?,
+                        title: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(notes)
+        })
+        .context("Failed to list notes")
+}
+
+/// The chunks of `note` in document order
+pub(crate) fn note_text(db: &Database, note: NoteCandidate) -> Result<NoteText> {
+    let chunks: Vec<String> = db
+        .conn()
+        .execute_query(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT text FROM chunks WHERE note_id = ?1 ORDER BY byte_offset, id")?;
+            let chunks = stmt
+                .query_map([note.id], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(chunks)
+        })
+        .context("Failed to read note chunks")?;
+    let text = chunks
+        .iter()
+        .map(|chunk| chunk.trim())
+        .filter(|chunk| !chunk.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let hash = content_hash(&format!("{}\n\n{}", note.title, text));
+
+    Ok(NoteText { note, text, hash })
+}
+
+/// The reply of `kind` from `client` cached for `note`, otherwise the reply
+/// of `ask`, cached once `parse` accepts it. With `refresh` the cache is not
+/// read. Returns the parsed reply and whether it came from the cache.
+pub(crate) fn cached_chat<T>(
+    db: &Database,
+    client: &LlmClient,
+    kind: &str,
+    note: &NoteText,
+    refresh: bool,
+    ask: impl FnOnce(&LlmClient) -> Result<String, LlmError>,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<(T, bool)> {
+    if !refresh {
+        let cached = db
+            .cached_llm_response(kind, client.model(), &note.hash)
+            .context("Failed to read cached reply")?;
+        if let Some(value) = cached.and_then(|reply| parse(&reply).ok()) {
+            return Ok((value, true));
+        }
+    }
+
+    let reply = ask(client)?;
+    let value = parse(&reply)?;
+    db.store_llm_response(kind, client.model(), &note.hash, &reply)
+        .context("Failed to cache reply")?;
+    Ok((value, false))
+}
+
+/// Summarize one note, from the cache when its content is unchanged
+pub fn summarize_note(
+    db: &Database,
+    client: &LlmClient,
+    note: NoteCandidate,
+    refresh: bool,
+) -> Result<NoteSummary> {
+    let note = note_text(db, note)?;
+    let (summary, cached) = if note.text.is_empty() {
+        (String::new(), false)
+    } else {
+        cached_chat(
+            db,
+            client,
+            "summary",
+            &note,
+            refresh,
+            |client| client.chat(SUMMARY_PROMPT, &note.prompt()),
+            |reply| Ok(reply.trim().to_string()),
+        )
+        .with_context(|| format!("Failed to summarize {}", note.note.path))?
+    };
+
+    Ok(NoteSummary {
+        note_path: note.note.path,
+        note_title: note.note.title,
+        summary,
+        cached,
+    })
+}
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("analyze.summarize", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+/// Print the summary of `note`, or of every note passing `filters`. Summaries
+/// are cached in the database; nothing is written to the vault.
+pub fn summarize_vault(
+    config: &Config,
+    note: Option<&str>,
+    filters: &SearchOptions,
+    refresh: bool,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let client = chat_client(config)?;
+    let db = open_database(config)?;
+
+    let notes = select_notes(&db, note, filters)?;
+    if notes.is_empty() {
+        match note {
+            Some(note) => emit(logger, &format!("Note not found: {note}")),
+            None => emit(logger, "No notes match the filters"),
+        }
+        return Ok(());
+    }
+
+    let total = notes.len();
+    let mut requested = 0;
+    for note in notes {
+        let summary = summarize_note(&db, &client, note, refresh)?;
+        if !summary.cached && !summary.summary.is_empty() {
+            requested += 1;
+        }
+        let cached = if summary.cached { " [cached]" } else { "" };
+        let body = if summary.summary.is_empty() {
+            "(empty note)"
+        } else {
+            summary.summary.as_str()
+        };
+        emit(
+            logger,
+            &format!(
+                "Summary of '{}' ({}){cached}:\n{body}\n",
+                summary.note_title, summary.note_path
+            ),
+        );
+    }
+    if total > 1 {
+        emit(
+            logger,
+            &format!(
+                "Summarized {total} notes ({requested} requested from {}, {} cached or empty)",
+                client.model(),
+                total - requested
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_text_prompt_is_cut() {
+        let note = NoteText {
+            note: NoteCandidate {
+                id: 1,
+                path: "Standup.md".to_string(),
+                title: "Standup".to_string(),
+            },
+            text: "é".repeat(NOTE_CHARS + 10),
+            hash: String::new(),
+        };
+        let prompt = note.prompt();
+        assert!(prompt.starts_with("Title: Standup\n\n"));
+        assert!(prompt.ends_with("é\n[...]"));
+        assert_eq!(prompt.chars().filter(|c| *c == 'é').count(), NOTE_CHARS);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, TagAnalysisOptions};
+use anyhow::{Context, Result};
+
+pub fn analyze_tags_cmd(
+    config: &Config,
+    options: &TagAnalysisOptions,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let analysis = db
+        .conn()
+        .execute_query(|conn| query::analyze_tags(conn, options))
+        .context("Failed to analyze tags")?;
+
+    println!("=== TAG ANALYSIS ===");
+    println!(
+        "{} tag(s) on {} of {} note(s)\n",
+        analysis.tag_count, analysis.tagged_notes, analysis.total_notes
+    );
+
+    println!("--- HIGH PMI PAIRS ({}) ---", analysis.pairs.len());
+    if analysis.pairs.is_empty() {
+        println!("No tag pairs share at least {} notes.", options.min_count);
+    }
+    for (idx, pair) in analysis.pairs.iter().enumerate() {
+        println!(
+            "{}. #{} + #{}: {} note(s) together (#{}: {}, #{}: {}), PMI {:.2}",
+            idx + 1,
+            pair.tag_a,
+            pair.tag_b,
+            pair.together,
+            pair.tag_a,
+            pair.count_a,
+            pair.tag_b,
+            pair.count_b,
+            pair.pmi
+        );
+    }
+    println!();
+
+    println!(
+        "--- ALMOST ALWAYS TOGETHER ({}) ---",
+        analysis.companions.len()
+    );
+    if analysis.companions.is_empty() {
+        println!("No tag is almost always used with another.");
+    }
+    for (idx, companion) in analysis.companions.iter().enumerate() {
+        println!(
+            "{}. #{} -> #{}: {} of {} note(s) ({:.0}%)",
+            idx + 1,
+            companion.tag,
+            companion.implies,
+            companion.together,
+            companion.tag_count,
+            companion.confidence * 100.0
+        );
+    }
+    println!();
+
+    println!("--- TAG SUGGESTIONS ({}) ---", analysis.suggestions.len());
+    if analysis.suggestions.is_empty() {
+        println!(
+            "No suggestions for notes with at most {} tag(s).",
+            options.max_note_tags
+        );
+    }
+    for (idx, note) in analysis.suggestions.iter().enumerate() {
+        let current = if note.current_tags.is_empty() {
+            "untagged".to_string()
+        } else {
+            note.current_tags
+                .iter()
+                .map(|t| format!("#{t}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        println!(
+            "{}. {} ({}) [{}]",
+            idx + 1,
+            note.note_title,
+            note.note_path,
+            current
+        );
+        for suggestion in &note.suggestions {
+            println!(
+                "   #{} {:.2} (links {:.2}, content {:.2})",
+                suggestion.tag, suggestion.score, suggestion.link_score, suggestion.content_score
+            );
+        }
+    }
+
+    if let Some(log) = logger {
+        let _ = log.print_and_log(
+            "analyze-tags",
+            &format!(
+                "Found {} tag pairs and suggestions for {} notes",
+                analysis.pairs.len(),
+                analysis.suggestions.len()
+            ),
+        );
+    }
+
+    Ok(())
+}
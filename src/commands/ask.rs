@@ -0,0 +1,406 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::embeddings::Embedder;
+use crate::llm::LlmClient;
+use crate::logger::Logger;
+use crate::query::{self, HybridWeights, SearchOptions};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::sync::OnceLock;
+
+use super::query_semantic::{excerpt, has_embeddings};
+
+/// Chunks given to the model by default
+pub const DEFAULT_CONTEXT_CHUNKS: usize = 6;
+
+/// Characters of each chunk sent to the model
+const CONTEXT_CHARS: usize = 2000;
+
+/// Citations such as `[1]` or `[1, 2]`, compiled on first use
+static CITATION: OnceLock<Regex> = OnceLock::new();
+
+/// Question words left out of the full-text query
+const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "are", "as", "at", "be", "by", "can", "could", "did", "do", "does",
+    "for", "from", "had", "has", "have", "how", "i", "in", "is", "it", "me", "my", "of", "on",
+    "or", "should", "so", "that", "the", "this", "to", "was", "we", "were", "what", "when",
+    "where", "which", "who", "why", "will", "with", "would", "you", "your",
+];
+
+const SYSTEM_PROMPT: &str = "You answer questions about the user's notes using only the \
+numbered excerpts you are given. Cite the excerpts you rely on by their number in square \
+brackets, such as [1] or [2][3]. If the excerpts do not answer the question, say so.";
+
+/// How the chunks given to the model were found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retrieval {
+    /// `search hybrid`, when chunks are embedded with the configured model
+    Hybrid,
+    /// `search notes` on the words of the question
+    FullText,
+}
+
+impl Retrieval {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Retrieval::Hybrid => "hybrid",
+            Retrieval::FullText => "fts",
+        }
+    }
+}
+
+/// A chunk given to the model, numbered for citation
+#[derive(Debug, Clone)]
+pub struct AskSource {
+    pub number: usize,
+    pub chunk_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub heading_path: Option<String>,
+    pub text: String,
+    /// Whether the answer cites it
+    pub cited: bool,
+}
+
+impl AskSource {
+    /// `note#heading` in Obsidian link form, such as `Projects/Deep Work#Rules`
+    pub fn link(&self) -> String {
+        let note = self
+            .note_path
+            .strip_suffix(".md")
+            .unwrap_or(&self.note_path);
+        let heading = self
+            .heading_path
+            .as_deref()
+            .and_then(|path| path.rsplit(" > ").next())
+            .map(|heading| heading.trim_start_matches('#').trim())
+            .filter(|heading| !heading.is_empty());
+        match heading {
+            Some(heading) => format!("{note}#{heading}"),
+            None => note.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AskAnswer {
+    /// Chat model that answered
+    pub model: String,
+    pub retrieval: Retrieval,
+    /// `None` when no chunk matched, in which case the model is not asked
+    pub answer: Option<String>,
+    pub sources: Vec<AskSource>,
+}
+
+/// `search notes` query matching any significant word of `question`
+pub(crate) fn keyword_query(question: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    for word in question
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+    {
+        if !word.is_empty() && !STOPWORDS.contains(&word.as_str()) && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words.join(" OR ")
+}
+
+/// The question preceded by the numbered excerpts, each headed by its
+/// `note#heading` link and note path
+pub(crate) fn user_prompt(question: &str, sources: &[AskSource]) -> String {
+    let mut prompt = String::from("Excerpts:\n");
+    for source in sources {
+        let text = source.text.trim();
+        let text = match text.char_indices().nth(CONTEXT_CHARS) {
+            Some((end, _)) => format!("{}...", &text[..end]),
+            None => text.to_string(),
+        };
+        prompt.push_str(&format!(
+            "\n[{}] {} ({})\n{}\n",
+            source.number,
+            source.link(),
+            source.note_path,
+            text
+        ));
+    }
+    prompt.push_str(&format!("\nQuestion: {question}"));
+    prompt
+}
+
+/// Numbers cited as `[1]` or `[1, 2]` in `answer`
+fn cited_numbers(answer: &str) -> Vec<usize> {
+    CITATION
+        .get_or_init(|| Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").expect("valid citation pattern"))
+        .captures_iter(answer)
+        .flat_map(|caps| {
+            caps[1]
+                .split(',')
+                .filter_map(|n| n.trim().parse().ok())
+                .collect::<Vec<usize>>()
+        })
+        .collect()
+}
+
+/// The chunks most relevant to `question`: hybrid search when the vault is
+/// embedded with the configured model, full-text search otherwise
+fn retrieve(
+    config: &Config,
+    db: &Database,
+    question: &str,
+    options: &SearchOptions,
+) -> Result<(Retrieval, Vec<AskSource>)> {
+    let keywords = keyword_query(question);
+    let source = |number: usize, chunk_id, note_path, note_title, heading_path, text| AskSource {
+        number,
+        chunk_id,
+        note_path,
+        note_title,
+        heading_path,
+        text,
+        cited: false,
+    };
+
+    let embedder = Embedder::from_config(config, db)?;
+    let model = embedder.model();
+    if has_embeddings(db, &model)? {
+        let query_vector = embedder
+            .embed(&[question.to_string()])?
+            .pop()
+            .context("Embedding endpoint returned no vector for the question")?;
+        let defaults = config.search.hybrid_weights();
+        defaults.validate().map_err(|e| anyhow::anyhow!(e))?;
+        let weights = HybridWeights {
+            keyword: if keywords.is_empty() {
+                0.0
+            } else {
+                defaults.keyword
+            },
+            ..defaults
+        };
+        let results = db
+            .conn()
+            .execute_query(|conn| {
+                Ok(query::hybrid_search(
+                    conn,
+                    &keywords,
+                    &model,
+                    &query_vector,
+                    options,
+                    &weights,
+                ))
+            })
+            .context("Failed to retrieve context")??;
+        let sources = results
+            .into_iter()
+            .enumerate()
+            .map(|(idx, r)| {
+                source(
+                    idx + 1,
+                    r.chunk_id,
+                    r.note_path,
+                    r.note_title,
+                    r.heading_path,
+                    r.chunk_text,
+                )
+            })
+            .collect();
+        return Ok((Retrieval::Hybrid, sources));
+    }
+
+    if keywords.is_empty() {
+        anyhow::bail!("The question has no words to search for");
+    }
+    let results = db
+        .conn()
+        .execute_query(|conn| Ok(query::search_chunks(conn, &keywords, options)))
+        .context("Failed to retrieve context")??;
+    let sources = results
+        .into_iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            source(
+                idx + 1,
+                r.chunk_id,
+                r.note_path,
+                r.note_title,
+                r.heading_path,
+                r.chunk_text,
+            )
+        })
+        .collect();
+    Ok((Retrieval::FullText, sources))
+}
+
+/// Retrieve the chunks relevant to `question` and ask the configured chat
+/// model to answer from them. Nothing is written to the database.
+pub fn answer_question(
+    config: &Config,
+    db: &Database,
+    question: &str,
+    options: &SearchOptions,
+) -> Result<AskAnswer> {
+    let llm = config.llm.as_ref().context(
+        "No [llm] section in the config; set api_url and model to use a chat completions endpoint",
+    )?;
+    let client = LlmClient::new(llm)?;
+    if question.trim().is_empty() {
+        anyhow::bail!("The question cannot be empty");
+    }
+
+    let (retrieval, mut sources) = retrieve(config, db, question, options)?;
+    let mut answer = AskAnswer {
+        model: client.model().to_string(),
+        retrieval,
+        answer: None,
+        sources: Vec::new(),
+    };
+    if sources.is_empty() {
+        return Ok(answer);
+    }
+
+    let reply = client.chat(SYSTEM_PROMPT, &user_prompt(question, &sources))?;
+    for number in cited_numbers(&reply) {
+        if let Some(source) = sources.get_mut(number.wrapping_sub(1)) {
+            source.cited = true;
+        }
+    }
+    answer.answer = Some(reply);
+    answer.sources = sources;
+    Ok(answer)
+}
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("ask", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+pub fn ask_vault(
+    config: &Config,
+    question: &str,
+    options: &SearchOptions,
+    show_context: bool,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let result = answer_question(config, &db, question, options)?;
+    let Some(answer) = &result.answer else {
+        emit(logger, &format!("No notes match: {question}"));
+        return Ok(());
+    };
+
+    if show_context {
+        emit(
+            logger,
+            &format!(
+                "Context ({} chunks, {} retrieval):",
+                result.sources.len(),
+                result.retrieval.as_str()
+            ),
+        );
+        for source in &result.sources {
+            emit(
+                logger,
+                &format!(
+                    "[{}] {} ({})\n   {}",
+                    source.number,
+                    source.link(),
+                    source.note_path,
+                    excerpt(&source.text)
+                ),
+            );
+        }
+        emit(logger, "");
+    }
+
+    emit(logger, answer);
+
+    // The cited sources, or every source when the answer cites none
+    let cited: Vec<&AskSource> = result.sources.iter().filter(|s| s.cited).collect();
+    let listed = if cited.is_empty() {
+        result.sources.iter().collect()
+    } else {
+        cited
+    };
+    emit(logger, "\nSources:");
+    for source in listed {
+        emit(logger, &format!("[{}] {}", source.number, source.link()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(number: usize, note_path: &str, heading_path: Option<&str>) -> AskSource {
+        AskSource {
+            number,
+            chunk_id: number as i64,
+            note_path: note_path.to_string(),
+            note_title: String::new(),
+            heading_path: heading_path.map(String::from),
+            text: "Work deeply.\n\n".to_string(),
+            cited: false,
+        }
+    }
+
+    #[test]
+    fn test_keyword_query_drops_stopwords_and_syntax() {
+        assert_eq!(
+            keyword_query("How do I stay focused at work? (title:x -y)"),
+            "stay OR focused OR work OR title OR x OR y"
+        );
+        assert_eq!(keyword_query("What is it?"), "");
+    }
+
+    #[test]
+    fn test_source_link() {
+        assert_eq!(
+            source(1, "Projects/Deep Work.md", Some("# Deep Work > ## Rules")).link(),
+            "Projects/Deep Work#Rules"
+        );
+        assert_eq!(source(1, "Inbox.md", None).link(), "Inbox");
+    }
+
+    #[test]
+    fn test_user_prompt_and_citations() {
+        let sources = vec![
+            source(1, "Deep Work.md", Some("# Deep Work > ## Rules")),
+            source(2, "Ideas.md", None),
+        ];
+        let prompt = user_prompt("How do I focus?", &sources);
+        assert_eq!(
+            prompt,
+            "Excerpts:\n\n[1] Deep Work#Rules (Deep Work.md)\nWork deeply.\n\n[2] Ideas (Ideas.md)\nWork deeply.\n\nQuestion: How do I focus?"
+        );
+
+        assert_eq!(
+            cited_numbers("Work deeply [1][2], or [1, 3]. See [x]."),
+            vec![1, 2, 1, 3]
+        );
+    }
+}
@@ -42,6 +42,14 @@ pub fn diagnose_broken_links_cmd(config: &Config, logger: Option<&Logger>) -> Re
             .iter()
             .filter(|l| l.status == "ambiguous")
             .collect();
+        let missing_headings: Vec<_> = broken_links
+            .iter()
+            .filter(|l| l.status == "missing_heading")
+            .collect();
+        let missing_blocks: Vec<_> = broken_links
+            .iter()
+            .filter(|l| l.status == "missing_block")
+            .collect();
 
         println!("=== BROKEN LINKS ===");
         println!("Found {} broken link(s):\n", broken_links.len());
@@ -66,6 +74,28 @@ pub fn diagnose_broken_links_cmd(config: &Config, logger: Option<&Logger>) -> Re
                 println!("{}. [[{}]] (in {})", idx + 1, link.target, link.src_path);
                 println!("   Candidates: {}", link.candidates.join(", "));
             }
+            println!();
+        }
+
+        if !missing_headings.is_empty() {
+            println!("--- MISSING HEADINGS ({}) ---", missing_headings.len());
+            for (idx, link) in missing_headings.iter().enumerate() {
+                println!("{}. [[{}]] (in {})", idx + 1, link.target, link.src_path);
+                if !link.candidates.is_empty() {
+                    println!("   Did you mean: {}", link.candidates.join(", "));
+                }
+            }
+            println!();
+        }
+
+        if !missing_blocks.is_empty() {
+            println!("--- MISSING BLOCKS ({}) ---", missing_blocks.len());
+            for (idx, link) in missing_blocks.iter().enumerate() {
+                println!("{}. [[{}]] (in {})", idx + 1, link.target, link.src_path);
+                if !link.candidates.is_empty() {
+                    println!("   Did you mean: {}", link.candidates.join(", "));
+                }
+            }
         }
     }
 
@@ -0,0 +1,83 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn diagnose_footnotes_cmd(config: &Config, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let issues = db
+        .conn()
+        .execute_query(query::diagnose_footnotes)
+        .context("Failed to diagnose footnotes")?;
+
+    println!("=== FOOTNOTES ===");
+    if issues.is_empty() {
+        println!("No footnote issues found! Every reference has a single definition.");
+    } else {
+        println!("Found {} footnote issue(s):\n", issues.len());
+
+        let sections = [
+            (
+                "missing_definition",
+                "MISSING DEFINITIONS",
+                "referenced on line",
+            ),
+            ("unused_definition", "UNUSED DEFINITIONS", "defined on line"),
+            ("duplicate_label", "DUPLICATE LABELS", "defined on lines"),
+        ];
+        for (issue, title, location) in sections {
+            let matching: Vec<_> = issues.iter().filter(|i| i.issue == issue).collect();
+            if matching.is_empty() {
+                continue;
+            }
+
+            println!("--- {title} ({}) ---", matching.len());
+            for (idx, item) in matching.iter().enumerate() {
+                let lines: Vec<String> = item.lines.iter().map(ToString::to_string).collect();
+                println!(
+                    "{}. [^{}] {}{} {} (in {})",
+                    idx + 1,
+                    item.label,
+                    location,
+                    if lines.len() > 1 && issue != "duplicate_label" {
+                        "s"
+                    } else {
+                        ""
+                    },
+                    lines.join(", "),
+                    item.note_path
+                );
+            }
+            println!();
+        }
+    }
+
+    if let Some(log) = logger {
+        let _ = log.print_and_log(
+            "diagnose-footnotes",
+            &format!("Found {} footnote issues", issues.len()),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn diagnose_tags_cmd(config: &Config, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let groups = db
+        .conn()
+        .execute_query(query::find_similar_tags)
+        .context("Failed to diagnose tags")?;
+
+    println!("=== TAG DUPLICATES ===");
+    if groups.is_empty() {
+        println!("No likely duplicate tags found!");
+    } else {
+        println!(
+            "Found {} group(s) of likely duplicate tags:\n",
+            groups.len()
+        );
+
+        for (idx, group) in groups.iter().enumerate() {
+            let variants: Vec<String> = group
+                .variants
+                .iter()
+                .map(|v| format!("#{} ({})", v.tag, v.note_count))
+                .collect();
+            println!("{}. {}", idx + 1, variants.join(", "));
+        }
+    }
+
+    if let Some(log) = logger {
+        let _ = log.print_and_log(
+            "diagnose-tags",
+            &format!("Found {} likely duplicate tag groups", groups.len()),
+        );
+    }
+
+    Ok(())
+}
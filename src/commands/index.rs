@@ -6,6 +6,7 @@ use crate::config::Config;
 use crate::db::Database;
 use crate::logger::Logger;
 use crate::parser::MarkdownParser;
+use crate::query::{NoteResolution, NoteResolver};
 use crate::scanner::VaultScanner;
 
 pub fn index_vault(
@@ -27,6 +28,24 @@ pub fn index_vault(
     let mut db = Database::open(&db_path)
         .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
 
+    // Rebuilds the schema when the database was created by an older version
+    db.initialize(false)
+        .context("Failed to upgrade database schema")?;
+    if db
+        .set_fts_tokenizer(&config.search.fts_tokenizer())
+        .context("Failed to rebuild the full-text index")?
+    {
+        let msg = format!(
+            "Rebuilt the full-text index with the {} tokenizer",
+            config.search.fts_tokenizer()
+        );
+        if let Some(log) = logger {
+            let _ = log.print_and_log("index", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
     if verbose {
         let msg = "Starting vault indexing...";
         if let Some(log) = logger {
@@ -121,6 +140,8 @@ pub fn index_vault(
         let note_id = tx
             .insert_note(&file.relative_path, &parsed.title, file.mtime, &hash, None)
             .context("Failed to insert note")?;
+        tx.set_note_size(note_id, file.size)
+            .context("Failed to record note size")?;
 
         if verbose {
             let msg = format!("  → Note: {} (id: {})", parsed.title, note_id);
@@ -145,6 +166,60 @@ pub fn index_vault(
             }
         }
 
+        // Insert aliases
+        for alias in &parsed.aliases {
+            tx.insert_alias(note_id, alias)
+                .context("Failed to insert alias")?;
+            if verbose {
+                let msg = format!("    • Alias: {alias}");
+                if let Some(log) = logger {
+                    let _ = log.print_and_log("index", &msg);
+                } else {
+                    println!("{msg}");
+                }
+            }
+        }
+
+        // Insert headings
+        for heading in &parsed.headings {
+            tx.insert_heading(note_id, heading)
+                .context("Failed to insert heading")?;
+        }
+
+        // Insert block anchors
+        for block in &parsed.blocks {
+            tx.insert_block(note_id, block)
+                .context("Failed to insert block")?;
+        }
+
+        // Insert tasks
+        for task in &parsed.tasks {
+            tx.insert_task(note_id, task)
+                .context("Failed to insert task")?;
+        }
+
+        // Insert callouts; parents precede their nested callouts
+        let mut callout_ids: Vec<i64> = Vec::with_capacity(parsed.callouts.len());
+        for callout in &parsed.callouts {
+            let parent_id = callout.parent.map(|idx| callout_ids[idx]);
+            let callout_id = tx
+                .insert_callout(note_id, callout, parent_id)
+                .context("Failed to insert callout")?;
+            callout_ids.push(callout_id);
+        }
+
+        // Insert footnote references and definitions
+        for footnote in &parsed.footnotes {
+            tx.insert_footnote(note_id, footnote)
+                .context("Failed to insert footnote")?;
+        }
+
+        // Insert frontmatter properties and inline fields
+        for field in &parsed.fields {
+            tx.insert_field(note_id, field)
+                .context("Failed to insert field")?;
+        }
+
         // Insert links
         for link in &parsed.links {
             tx.insert_link(
@@ -214,6 +289,28 @@ pub fn index_vault(
         indexed_count += 1;
     }
 
+    // Resolve links across the whole vault, since new or renamed notes can
+    // satisfy links from notes that were not re-indexed. Links resolve like
+    // note arguments without fuzzy matching; ambiguous ones stay unresolved.
+    let resolver = tx
+        .conn()
+        .execute_query(NoteResolver::load)
+        .context("Failed to load notes for link resolution")?;
+    let resolved = tx
+        .resolve_links(|target| match resolver.resolve_exact(target) {
+            NoteResolution::Found { note, .. } => Some(note.id),
+            NoteResolution::Ambiguous(_) | NoteResolution::NotFound => None,
+        })
+        .context("Failed to resolve links")?;
+    if verbose {
+        let msg = format!("Resolved {resolved} link(s)");
+        if let Some(log) = logger {
+            let _ = log.print_and_log("index", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
     tx.commit().context("Failed to commit transaction")?;
 
     let msg = if skipped_count > 0 {
@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::embeddings::{update_embeddings, Embedder};
+use crate::logger::Logger;
+
+/// Embed the indexed chunks that have no vector from the configured
+/// embedding model yet. The local provider embeds every chunk each time.
+pub fn embed_vault(
+    config: &Config,
+    force: bool,
+    batch_size: usize,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let mut db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Rebuilds the schema when the database was created by an older version
+    db.initialize(false)
+        .context("Failed to upgrade database schema")?;
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let embedder = Embedder::from_config(config, &db)?;
+    let model = embedder.model();
+    let force = force || embedder.reembeds_all();
+    let stats = update_embeddings(&mut db, &model, batch_size, force, |inputs| {
+        embedder.embed(inputs)
+    })
+    .context("Failed to embed chunks")?;
+
+    let mut msg = format!(
+        "Embedded {} chunk(s) with {model} (reused {}, removed {} stale)",
+        stats.embedded, stats.reused, stats.removed
+    );
+    if stats.embedded == 0 && stats.reused == 0 {
+        msg = format!(
+            "All chunks already embedded with {model} (removed {} stale)",
+            stats.removed
+        );
+    }
+    if let Some(log) = logger {
+        let _ = log.print_and_log("index", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    Ok(())
+}
@@ -25,6 +25,8 @@ pub fn initialize_database(config: &Config, force: bool, logger: Option<&Logger>
 
     db.initialize(force)
         .context("Failed to initialize database schema")?;
+    db.set_fts_tokenizer(&config.search.fts_tokenizer())
+        .context("Failed to create the full-text index")?;
 
     let version = db.get_version()?.unwrap_or(0);
     let msg = format!("Database initialized successfully (schema version: {version})");
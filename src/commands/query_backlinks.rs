@@ -25,10 +25,19 @@ pub fn get_backlinks(config: &Config, note: &str, logger: Option<&Logger>) -> Re
         );
     }
 
-    let backlinks = db
+    let resolved = db
         .conn()
-        .execute_query(|conn| query::get_backlinks(conn, note))
-        .context("Failed to get backlinks")?;
+        .execute_query(|conn| query::resolve_note(conn, note))
+        .context("Failed to resolve note")?
+        .into_result(note)?;
+
+    let backlinks = match resolved {
+        Some(target) => db
+            .conn()
+            .execute_query(|conn| query::get_backlinks(conn, target.id))
+            .context("Failed to get backlinks")?,
+        None => Vec::new(),
+    };
 
     if backlinks.is_empty() {
         let msg = format!("No backlinks found for: {note}");
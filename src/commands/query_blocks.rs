@@ -0,0 +1,93 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn find_blocks(
+    config: &Config,
+    id: &str,
+    note: Option<&str>,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let target = match note {
+        Some(note) => db
+            .conn()
+            .execute_query(|conn| query::resolve_note(conn, note))
+            .context("Failed to resolve note")?
+            .into_result(note)?
+            .map(|n| Some(n.id)),
+        None => Some(None),
+    };
+
+    // A note that is not found has no blocks
+    let blocks = match target {
+        Some(note_id) => db
+            .conn()
+            .execute_query(|conn| query::search_blocks(conn, id, note_id))
+            .context("Failed to search blocks")?,
+        None => Vec::new(),
+    };
+
+    let block_id = id.trim().trim_start_matches('^');
+    if blocks.is_empty() {
+        let msg = format!("No block found with id: ^{block_id}");
+        if let Some(log) = logger {
+            let _ = log.print_and_log("blocks", &msg);
+        } else {
+            println!("{msg}");
+        }
+        return Ok(());
+    }
+
+    let msg = format!("Blocks with id '^{}' ({} found):", block_id, blocks.len());
+    if let Some(log) = logger {
+        let _ = log.print_and_log("blocks", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    for (idx, block) in blocks.iter().enumerate() {
+        let text = block
+            .text
+            .lines()
+            .map(|line| format!("   {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let msg = format!(
+            "{}. {} ({}:{})\n{}",
+            idx + 1,
+            block.note_title,
+            block.note_path,
+            block.line,
+            text
+        );
+        if let Some(log) = logger {
+            let _ = log.print_and_log("blocks", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
+    Ok(())
+}
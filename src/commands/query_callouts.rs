@@ -0,0 +1,85 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn list_callouts(
+    config: &Config,
+    kind: Option<&str>,
+    text: Option<&str>,
+    limit: usize,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let callouts = db
+        .conn()
+        .execute_query(|conn| query::search_callouts(conn, kind, text, limit))
+        .context("Failed to search callouts")?;
+
+    if callouts.is_empty() {
+        let msg = "No callouts found".to_string();
+        if let Some(log) = logger {
+            let _ = log.print_and_log("callouts", &msg);
+        } else {
+            println!("{msg}");
+        }
+        return Ok(());
+    }
+
+    let msg = format!("Callouts ({} found):", callouts.len());
+    if let Some(log) = logger {
+        let _ = log.print_and_log("callouts", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    for callout in &callouts {
+        let title = callout
+            .title
+            .as_ref()
+            .map(|t| format!(" {t}"))
+            .unwrap_or_default();
+        let body = callout
+            .body
+            .lines()
+            .map(|line| format!("   {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let msg = format!(
+            "{}[!{}]{} ({}:{})\n{}",
+            "  ".repeat(callout.depth.saturating_sub(1) as usize),
+            callout.kind,
+            title,
+            callout.note_path,
+            callout.line,
+            body
+        );
+        if let Some(log) = logger {
+            let _ = log.print_and_log("callouts", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, DqlKind, DqlResult};
+use anyhow::{Context, Result};
+
+pub fn run_dql_query(config: &Config, dql: &str, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let result = db
+        .conn()
+        .execute_query(|conn| Ok(query::run_dql(conn, dql)))
+        .context("Failed to run DQL query")??;
+
+    let lines = match result.kind {
+        DqlKind::List => format_list(&result),
+        DqlKind::Table => format_table(&result),
+    };
+    for line in lines {
+        if let Some(log) = logger {
+            let _ = log.print_and_log("dql", &line);
+        } else {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+fn format_list(result: &DqlResult) -> Vec<String> {
+    let mut lines: Vec<String> = result
+        .rows
+        .iter()
+        .map(|row| {
+            let value = row.values.first().map(ToString::to_string);
+            match (result.without_id, value) {
+                (true, Some(value)) => format!("- {value}"),
+                (false, Some(value)) => format!("- {}: {value}", row.note_path),
+                (_, None) => format!("- {}", row.note_path),
+            }
+        })
+        .collect();
+    lines.push(format!("{} results", result.rows.len()));
+    lines
+}
+
+/// Plain-text table with columns padded to their widest cell
+fn format_table(result: &DqlResult) -> Vec<String> {
+    let mut header: Vec<String> = Vec::new();
+    if !result.without_id {
+        header.push(format!("File ({})", result.rows.len()));
+    }
+    header.extend(result.columns.iter().cloned());
+
+    let rows: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| {
+            let mut cells = Vec::new();
+            if !result.without_id {
+                cells.push(row.note_path.clone());
+            }
+            cells.extend(row.values.iter().map(ToString::to_string));
+            cells
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(&header)];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-|-"),
+    );
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines
+}
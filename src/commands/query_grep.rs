@@ -0,0 +1,163 @@
+use crate::commands::query_search::highlight_markers;
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, highlight_ranges, GrepMatch, GrepOptions};
+use anyhow::{Context, Result};
+
+/// `path:line:col: text` for matches and `path-line- text` for context, with
+/// `--` between groups of lines that are not adjacent
+fn format_matches(results: &[GrepMatch], start: &str, end: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut last: Option<(&str, usize)> = None;
+
+    for (idx, result) in results.iter().enumerate() {
+        let path = result.note_path.as_str();
+        // Context stops before the next match in the note, which prints itself
+        let next_match = results
+            .get(idx + 1)
+            .filter(|next| next.note_path == path)
+            .map_or(usize::MAX, |next| next.line);
+        // Last line already printed from this note
+        let mut printed = match last {
+            Some((last_path, last_line)) if last_path == path => last_line,
+            _ => 0,
+        };
+        let first = result.before.first().map_or(result.line, |l| l.line);
+        if last.is_some() && (printed == 0 || first > printed + 1) {
+            lines.push("--".to_string());
+        }
+
+        for context in &result.before {
+            if context.line > printed {
+                lines.push(format!("{path}-{}- {}", context.line, context.text));
+                printed = context.line;
+            }
+        }
+        if result.line > printed {
+            lines.push(format!(
+                "{path}:{}:{}: {}",
+                result.line,
+                result.column,
+                highlight_ranges(&result.text, &result.matches, start, end)
+            ));
+            printed = result.line;
+        }
+        for context in result.after.iter().take_while(|l| l.line < next_match) {
+            if context.line > printed {
+                lines.push(format!("{path}-{}- {}", context.line, context.text));
+                printed = context.line;
+            }
+        }
+        last = Some((path, printed));
+    }
+
+    lines
+}
+
+pub fn grep_vault(
+    config: &Config,
+    pattern: &str,
+    options: &GrepOptions,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let results = db
+        .conn()
+        .execute_query(|conn| {
+            Ok(query::grep_notes(
+                conn,
+                &config.vault_path,
+                pattern,
+                options,
+            ))
+        })
+        .context("Failed to search note files")??;
+
+    let msg = if results.is_empty() {
+        format!("No matches found for: {pattern}")
+    } else {
+        let (start, end) = highlight_markers(config);
+        format_matches(&results, &start, &end).join("\n")
+    };
+    if let Some(log) = logger {
+        let _ = log.print_and_log("grep", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::GrepLine;
+
+    fn grep_match(line: usize, context: usize, path: &str) -> GrepMatch {
+        let numbered = |from: usize, to: usize| -> Vec<GrepLine> {
+            (from..to)
+                .map(|line| GrepLine {
+                    line,
+                    text: format!("line {line}"),
+                })
+                .collect()
+        };
+        GrepMatch {
+            note_id: 1,
+            note_path: path.to_string(),
+            note_title: String::new(),
+            line,
+            column: 1,
+            text: "TODO".to_string(),
+            matches: vec![(0, 4)],
+            before: numbered(line.saturating_sub(context).max(1), line),
+            after: numbered(line + 1, line + 1 + context),
+        }
+    }
+
+    #[test]
+    fn test_format_matches_merges_overlapping_context() {
+        let results = vec![
+            grep_match(2, 1, "a.md"),
+            grep_match(3, 1, "a.md"),
+            grep_match(9, 1, "a.md"),
+            grep_match(1, 0, "b.md"),
+        ];
+
+        assert_eq!(
+            format_matches(&results, "[", "]"),
+            vec![
+                "a.md-1- line 1",
+                "a.md:2:1: [TODO]",
+                "a.md:3:1: [TODO]",
+                "a.md-4- line 4",
+                "--",
+                "a.md-8- line 8",
+                "a.md:9:1: [TODO]",
+                "a.md-10- line 10",
+                "--",
+                "b.md:1:1: [TODO]",
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, HybridWeights, SearchOptions};
+use anyhow::{Context, Result};
+
+use super::query_search::highlight_markers;
+use super::query_semantic::{embed_query, excerpt};
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("search", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+/// Run the full-text and embedding searches for `query_str` and fuse their
+/// rankings, returning the embedding model used with the results
+pub fn hybrid_results(
+    config: &Config,
+    db: &Database,
+    query_str: &str,
+    options: &SearchOptions,
+    weights: &HybridWeights,
+) -> Result<(String, Vec<query::HybridResult>)> {
+    let (model, query_vector) = embed_query(config, db, query_str)?;
+    let results = db
+        .conn()
+        .execute_query(|conn| {
+            Ok(query::hybrid_search(
+                conn,
+                query_str,
+                &model,
+                &query_vector,
+                options,
+                weights,
+            ))
+        })
+        .context("Failed to execute hybrid search")??;
+
+    Ok((model, results))
+}
+
+pub fn hybrid_search_vault(
+    config: &Config,
+    query_str: &str,
+    options: &SearchOptions,
+    weights: &HybridWeights,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    if query_str.trim().is_empty() {
+        emit(logger, "Search query cannot be empty");
+        return Ok(());
+    }
+
+    let (_, results) = hybrid_results(config, &db, query_str, options, weights)?;
+    if results.is_empty() {
+        emit(logger, &format!("No results found for: {query_str}"));
+        return Ok(());
+    }
+
+    emit(
+        logger,
+        &format!(
+            "Hybrid Results for '{query_str}' ({} results):",
+            results.len()
+        ),
+    );
+    let (start, end) = highlight_markers(config);
+    for (idx, result) in results.iter().enumerate() {
+        let heading_info = result
+            .heading_path
+            .as_ref()
+            .map(|h| format!(" [{h}]"))
+            .unwrap_or_default();
+        let ranks: Vec<String> = [("keyword", &result.keyword), ("semantic", &result.semantic)]
+            .into_iter()
+            .filter_map(|(name, contribution)| {
+                contribution.as_ref().map(|c| format!("{name} #{}", c.rank))
+            })
+            .collect();
+        // Chunks found only by similarity have no full-text snippet
+        let text = if result.snippet.is_empty() {
+            excerpt(&result.chunk_text)
+        } else {
+            result
+                .highlighted_snippet(&start, &end)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let msg = format!(
+            "{}. {} ({}){} score {:.4} ({})\n   {}",
+            idx + 1,
+            result.note_title,
+            result.note_path,
+            heading_info,
+            result.score,
+            ranks.join(", "),
+            text
+        );
+        emit(logger, &msg);
+    }
+
+    Ok(())
+}
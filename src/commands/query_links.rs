@@ -25,10 +25,19 @@ pub fn get_forward_links(config: &Config, note: &str, logger: Option<&Logger>) -
         );
     }
 
-    let forward_links = db
+    let resolved = db
         .conn()
-        .execute_query(|conn| query::get_forward_links(conn, note))
-        .context("Failed to get forward links")?;
+        .execute_query(|conn| query::resolve_note(conn, note))
+        .context("Failed to resolve note")?
+        .into_result(note)?;
+
+    let forward_links = match resolved {
+        Some(target) => db
+            .conn()
+            .execute_query(|conn| query::get_forward_links(conn, target.id))
+            .context("Failed to get forward links")?,
+        None => Vec::new(),
+    };
 
     if forward_links.is_empty() {
         let msg = format!("No forward links found for: {note}");
@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn list_unlinked_mentions(
+    config: &Config,
+    note: &str,
+    limit: usize,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let resolved = db
+        .conn()
+        .execute_query(|conn| query::resolve_note(conn, note))
+        .context("Failed to resolve note")?
+        .into_result(note)?;
+
+    let mentions = match resolved {
+        Some(target) => db
+            .conn()
+            .execute_query(|conn| query::find_unlinked_mentions(conn, target.id, limit))
+            .context("Failed to find unlinked mentions")?,
+        None => Vec::new(),
+    };
+
+    if mentions.is_empty() {
+        let msg = format!("No unlinked mentions found for: {note}");
+        if let Some(log) = logger {
+            let _ = log.print_and_log("mentions", &msg);
+        } else {
+            println!("{msg}");
+        }
+        return Ok(());
+    }
+
+    let msg = format!(
+        "Unlinked mentions of '{}' ({} found):",
+        note,
+        mentions.len()
+    );
+    if let Some(log) = logger {
+        let _ = log.print_and_log("mentions", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    for (idx, mention) in mentions.iter().enumerate() {
+        let heading_info = mention
+            .heading_path
+            .as_ref()
+            .map(|h| format!(" [{h}]"))
+            .unwrap_or_default();
+        let msg = format!(
+            "{}. {} ({}){}\n   \"{}\": {}",
+            idx + 1,
+            mention.note_title,
+            mention.note_path,
+            heading_info,
+            mention.matched_term,
+            mention.context.chars().take(120).collect::<String>()
+        );
+        if let Some(log) = logger {
+            let _ = log.print_and_log("mentions", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, PropertyFilter};
+use anyhow::{Context, Result};
+
+pub fn search_properties(
+    config: &Config,
+    filters: &[PropertyFilter],
+    limit: usize,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let print = |msg: &str| {
+        if let Some(log) = logger {
+            let _ = log.print_and_log("properties", msg);
+        } else {
+            println!("{msg}");
+        }
+    };
+
+    if filters.is_empty() {
+        let keys = db
+            .conn()
+            .execute_query(query::list_property_keys)
+            .context("Failed to list properties")?;
+
+        if keys.is_empty() {
+            print("No properties found");
+            return Ok(());
+        }
+
+        print(&format!("Properties ({} found):", keys.len()));
+        for key in &keys {
+            print(&format!(
+                "- {} ({} notes, {})",
+                key.key,
+                key.note_count,
+                key.value_types.join(", ")
+            ));
+        }
+        return Ok(());
+    }
+
+    let matches = db
+        .conn()
+        .execute_query(|conn| query::find_notes_by_properties(conn, filters, limit))
+        .context("Failed to search properties")?;
+
+    let filter_list = filters
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if matches.is_empty() {
+        print(&format!("No notes found matching: {filter_list}"));
+        return Ok(());
+    }
+
+    print(&format!(
+        "Notes matching {filter_list} ({} found):",
+        matches.len()
+    ));
+    for note in &matches {
+        print(&format!("- {} ({})", note.note_title, note.note_path));
+        for field in &note.fields {
+            print(&format!(
+                "    {}: {} [{}, line {}]",
+                field.key, field.value, field.source, field.line
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -1,15 +1,49 @@
 use crate::config::Config;
 use crate::db::Database;
 use crate::logger::Logger;
-use crate::query;
+use crate::query::{self, SearchOptions, SearchResult};
 use anyhow::{Context, Result};
+use std::io::IsTerminal;
 
-pub fn search_vault(
-    config: &Config,
-    query_str: &str,
-    limit: usize,
-    logger: Option<&Logger>,
-) -> Result<()> {
+const ANSI_HIGHLIGHT: &str = "\x1b[1;33m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Match markers for text output: the configured ones, otherwise ANSI
+/// highlighting when stdout is a terminal and `NO_COLOR` is unset
+pub(crate) fn highlight_markers(config: &Config) -> (String, String) {
+    match (&config.search.highlight_start, &config.search.highlight_end) {
+        (None, None) => {
+            if std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+                (ANSI_HIGHLIGHT.to_string(), ANSI_RESET.to_string())
+            } else {
+                (String::new(), String::new())
+            }
+        }
+        (start, end) => (
+            start.clone().unwrap_or_default(),
+            end.clone().unwrap_or_default(),
+        ),
+    }
+}
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("search", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+/// Snippet with matches marked and whitespace collapsed to single spaces
+fn display_snippet(result: &SearchResult, start: &str, end: &str) -> String {
+    result
+        .highlighted_snippet(start, end)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn open_indexed_database(config: &Config) -> Result<Database> {
     let db_path = config.database_path();
 
     if !db_path.exists() {
@@ -30,67 +64,155 @@ pub fn search_vault(
         );
     }
 
-    if query_str.is_empty() {
-        if let Some(log) = logger {
-            let _ = log.print_and_log("search", "Search query cannot be empty");
-        } else {
-            println!("Search query cannot be empty");
+    Ok(db)
+}
+
+/// Print "Did you mean" with the query corrected by the closest indexed
+/// terms, followed by the alternatives for each misspelled word
+fn emit_suggestions(db: &Database, query_str: &str, logger: Option<&Logger>) -> Result<()> {
+    let suggestions = db
+        .conn()
+        .execute_query(|conn| {
+            Ok(query::suggest_terms(
+                conn,
+                query_str,
+                query::SUGGESTIONS_PER_TERM,
+            ))
+        })
+        .context("Failed to suggest search terms")??;
+    let Some(corrected) = query::corrected_query(query_str, &suggestions) else {
+        return Ok(());
+    };
+
+    let mut msg = format!("Did you mean: {corrected}?");
+    let mut terms: Vec<&str> = suggestions.iter().map(|s| s.term.as_str()).collect();
+    terms.dedup();
+    for term in terms {
+        let alternatives: Vec<&str> = suggestions
+            .iter()
+            .filter(|s| s.term == term)
+            .map(|s| s.suggestion.as_str())
+            .collect();
+        if alternatives.len() > 1 {
+            msg.push_str(&format!("\n   {term}: {}", alternatives.join(", ")));
         }
+    }
+    emit(logger, &msg);
+
+    Ok(())
+}
+
+pub fn search_vault(
+    config: &Config,
+    query_str: &str,
+    options: &SearchOptions,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db = open_indexed_database(config)?;
+
+    if query_str.is_empty() {
+        emit(logger, "Search query cannot be empty");
         return Ok(());
     }
 
     let results = db
         .conn()
-        .execute_query(|conn| query::search_chunks(conn, query_str, limit))
-        .context("Failed to execute search")?;
+        .execute_query(|conn| Ok(query::search_chunks(conn, query_str, options)))
+        .context("Failed to execute search")??;
 
     if results.is_empty() {
         let msg = format!("No results found for: {query_str}");
-        if let Some(log) = logger {
-            let _ = log.print_and_log("search", &msg);
-        } else {
-            println!("{msg}");
-        }
-        return Ok(());
+        emit(logger, &msg);
+        return emit_suggestions(&db, query_str, logger);
     }
 
     let msg = format!(
         "Search Results for '{query_str}' ({} results):",
         results.len()
     );
-    if let Some(log) = logger {
-        let _ = log.print_and_log("search", &msg);
-    } else {
-        println!("{msg}");
-    }
+    emit(logger, &msg);
 
+    let (start, end) = highlight_markers(config);
     for (idx, result) in results.iter().enumerate() {
         let heading_info = result
             .heading_path
             .as_ref()
             .map(|h| format!(" [{h}]"))
             .unwrap_or_default();
+        let snippet = display_snippet(result, &start, &end);
         let msg = format!(
             "{}. {} ({}){}\n   {}",
             idx + 1,
             result.note_title,
             result.note_path,
             heading_info,
-            result
-                .chunk_text
-                .lines()
-                .next()
-                .unwrap_or("")
-                .chars()
-                .take(80)
-                .collect::<String>()
+            snippet
         );
-        if let Some(log) = logger {
-            let _ = log.print_and_log("search", &msg);
+        emit(logger, &msg);
+    }
+
+    emit_suggestions(&db, query_str, logger)
+}
+
+/// Search and list one result per note, ranked with the `[search]` boosts
+pub fn search_vault_by_note(
+    config: &Config,
+    query_str: &str,
+    options: &SearchOptions,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db = open_indexed_database(config)?;
+
+    if query_str.is_empty() {
+        emit(logger, "Search query cannot be empty");
+        return Ok(());
+    }
+
+    let boosts = config.search.note_boosts();
+    let results = db
+        .conn()
+        .execute_query(|conn| Ok(query::search_notes(conn, query_str, options, &boosts)))
+        .context("Failed to execute search")??;
+
+    if results.is_empty() {
+        emit(logger, &format!("No results found for: {query_str}"));
+        return emit_suggestions(&db, query_str, logger);
+    }
+
+    emit(
+        logger,
+        &format!(
+            "Search Results for '{query_str}' ({} notes):",
+            results.len()
+        ),
+    );
+
+    let (start, end) = highlight_markers(config);
+    for (idx, result) in results.iter().enumerate() {
+        let chunks = if result.chunk_count == 1 {
+            "1 chunk".to_string()
         } else {
-            println!("{msg}");
+            format!("{} chunks", result.chunk_count)
+        };
+        let mut msg = format!(
+            "{}. {} ({}) score {:.2}, {chunks}",
+            idx + 1,
+            result.note_title,
+            result.note_path,
+            result.score
+        );
+        if !result.heading_paths.is_empty() {
+            msg.push_str(&format!(
+                "\n   Headings: {}",
+                result.heading_paths.join(" | ")
+            ));
         }
+        msg.push_str(&format!(
+            "\n   {}",
+            display_snippet(&result.best, &start, &end)
+        ));
+        emit(logger, &msg);
     }
 
-    Ok(())
+    emit_suggestions(&db, query_str, logger)
 }
@@ -0,0 +1,154 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::embeddings::Embedder;
+use crate::logger::Logger;
+use crate::query::{self, SearchOptions};
+use anyhow::{Context, Result};
+
+/// Characters of chunk text shown for each result
+const EXCERPT_CHARS: usize = 200;
+
+fn emit(logger: Option<&Logger>, msg: &str) {
+    if let Some(log) = logger {
+        let _ = log.print_and_log("search", msg);
+    } else {
+        println!("{msg}");
+    }
+}
+
+/// Chunk text with whitespace collapsed, cut to `EXCERPT_CHARS`
+pub(crate) fn excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}...", &collapsed[..end]),
+        None => collapsed,
+    }
+}
+
+/// Whether any chunk is embedded with `model`
+pub fn has_embeddings(db: &Database, model: &str) -> Result<bool> {
+    db.conn()
+        .execute_query(|conn| {
+            conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM embeddings WHERE model = ?1)",
+                [model],
+                |row| row.get::<_, bool>(0),
+            )
+        })
+        .context("Failed to read embeddings")
+}
+
+/// Embed `query_str` with the configured embedding model, returning the model
+/// and the vector. Fails when no chunk has been embedded with that model.
+pub fn embed_query(config: &Config, db: &Database, query_str: &str) -> Result<(String, Vec<f32>)> {
+    let embedder = Embedder::from_config(config, db)?;
+    let model = embedder.model();
+
+    if !has_embeddings(db, &model)? {
+        anyhow::bail!(
+            "No chunks are embedded with {model}. Run 'obsidian-cli-inspector index embed' first"
+        );
+    }
+
+    let query_vector = embedder
+        .embed(&[query_str.to_string()])?
+        .pop()
+        .context("Embedding endpoint returned no vector for the query")?;
+
+    Ok((model, query_vector))
+}
+
+/// Embed `query_str` and return the closest chunks, with the embedding model used
+pub fn semantic_results(
+    config: &Config,
+    db: &Database,
+    query_str: &str,
+    options: &SearchOptions,
+) -> Result<(String, Vec<query::SemanticResult>)> {
+    let (model, query_vector) = embed_query(config, db, query_str)?;
+    let results = db
+        .conn()
+        .execute_query(|conn| query::semantic_search(conn, &model, &query_vector, options))
+        .context("Failed to execute semantic search")?;
+
+    Ok((model, results))
+}
+
+pub fn semantic_search_vault(
+    config: &Config,
+    query_str: &str,
+    options: &SearchOptions,
+    logger: Option<&Logger>,
+) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    if query_str.trim().is_empty() {
+        emit(logger, "Search query cannot be empty");
+        return Ok(());
+    }
+
+    let (_, results) = semantic_results(config, &db, query_str, options)?;
+    if results.is_empty() {
+        emit(logger, &format!("No results found for: {query_str}"));
+        return Ok(());
+    }
+
+    emit(
+        logger,
+        &format!(
+            "Semantic Results for '{query_str}' ({} results):",
+            results.len()
+        ),
+    );
+    for (idx, result) in results.iter().enumerate() {
+        let heading_info = result
+            .heading_path
+            .as_ref()
+            .map(|h| format!(" [{h}]"))
+            .unwrap_or_default();
+        let msg = format!(
+            "{}. {} ({}){} similarity {:.3}\n   {}",
+            idx + 1,
+            result.note_title,
+            result.note_path,
+            heading_info,
+            result.score,
+            excerpt(&result.chunk_text)
+        );
+        emit(logger, &msg);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt_collapses_and_cuts() {
+        assert_eq!(excerpt("a\n\n  b"), "a b");
+        let long = "é".repeat(EXCERPT_CHARS + 5);
+        let cut = excerpt(&long);
+        assert!(cut.ends_with("..."));
+        assert_eq!(cut.chars().count(), EXCERPT_CHARS + 3);
+    }
+}
@@ -8,6 +8,7 @@ pub fn list_notes_by_tag(
     config: &Config,
     tag: &Option<String>,
     all: bool,
+    exact: bool,
     logger: Option<&Logger>,
 ) -> Result<()> {
     let db_path = config.database_path();
@@ -64,7 +65,7 @@ pub fn list_notes_by_tag(
     } else if let Some(tag_name) = tag {
         let notes = db
             .conn()
-            .execute_query(|conn| query::get_notes_by_tag(conn, tag_name))
+            .execute_query(|conn| query::get_notes_by_tag(conn, tag_name, exact))
             .context("Failed to get notes by tag")?;
 
         if notes.is_empty() {
@@ -102,3 +103,72 @@ pub fn list_notes_by_tag(
 
     Ok(())
 }
+
+/// Print the tag hierarchy, or the subtree under `root`, with note counts
+pub fn show_tag_tree(config: &Config, root: Option<&str>, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let tree = db
+        .conn()
+        .execute_query(|conn| query::tag_tree(conn, root))
+        .context("Failed to build tag tree")?;
+
+    let msg = match (root, tree.is_empty()) {
+        (Some(root), true) => format!("No tags found under: {root}"),
+        (None, true) => "No tags found in vault".to_string(),
+        (_, false) => format!(
+            "Tag tree ({} top-level tags, note counts include nested tags):",
+            tree.len()
+        ),
+    };
+    if let Some(log) = logger {
+        let _ = log.print_and_log("tags", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    let mut lines = Vec::new();
+    for node in &tree {
+        push_tree_lines(node, 0, &mut lines);
+    }
+    for line in lines {
+        if let Some(log) = logger {
+            let _ = log.print_and_log("tags", &line);
+        } else {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+fn push_tree_lines(node: &query::TagNode, depth: usize, lines: &mut Vec<String>) {
+    let name = if depth == 0 { &node.tag } else { &node.name };
+    lines.push(format!(
+        "{}{} ({})",
+        "  ".repeat(depth),
+        name,
+        node.note_count
+    ));
+    for child in &node.children {
+        push_tree_lines(child, depth + 1, lines);
+    }
+}
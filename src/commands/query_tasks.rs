@@ -0,0 +1,100 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query::{self, TaskFilter};
+use anyhow::{Context, Result};
+
+/// Today's date as used for `--overdue`
+pub fn today() -> String {
+    chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+pub fn list_tasks(config: &Config, filter: &TaskFilter, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let today = today();
+    let tasks = db
+        .conn()
+        .execute_query(|conn| query::search_tasks(conn, filter, &today))
+        .context("Failed to search tasks")?;
+
+    if tasks.is_empty() {
+        let msg = "No tasks found".to_string();
+        if let Some(log) = logger {
+            let _ = log.print_and_log("tasks", &msg);
+        } else {
+            println!("{msg}");
+        }
+        return Ok(());
+    }
+
+    let msg = format!("Tasks ({} found):", tasks.len());
+    if let Some(log) = logger {
+        let _ = log.print_and_log("tasks", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    for task in &tasks {
+        let mut details = Vec::new();
+        if let Some(priority) = &task.priority {
+            details.push(format!("priority: {priority}"));
+        }
+        if let Some(due) = &task.due {
+            let overdue = task.is_open() && due.as_str() < today.as_str();
+            details.push(format!(
+                "📅 {due}{}",
+                if overdue { " (overdue)" } else { "" }
+            ));
+        }
+        if let Some(scheduled) = &task.scheduled {
+            details.push(format!("⏳ {scheduled}"));
+        }
+        if let Some(done) = &task.done {
+            details.push(format!("✅ {done}"));
+        }
+
+        let heading_info = task
+            .heading
+            .as_ref()
+            .map(|h| format!(" [{h}]"))
+            .unwrap_or_default();
+        let detail_info = if details.is_empty() {
+            String::new()
+        } else {
+            format!("\n   {}", details.join("  "))
+        };
+        let msg = format!(
+            "- [{}] {} ({}:{}){}{}",
+            task.status, task.text, task.note_path, task.line, heading_info, detail_info
+        );
+        if let Some(log) = logger {
+            let _ = log.print_and_log("tasks", &msg);
+        } else {
+            println!("{msg}");
+        }
+    }
+
+    Ok(())
+}
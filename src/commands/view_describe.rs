@@ -25,10 +25,19 @@ pub fn get_note_describe(config: &Config, filename: &str, logger: Option<&Logger
         );
     }
 
-    let note = db
+    let resolved = db
         .conn()
-        .execute_query(|conn| query::get_note_by_filename(conn, filename))
-        .context("Failed to get note metadata")?;
+        .execute_query(|conn| query::resolve_note(conn, filename))
+        .context("Failed to resolve note")?
+        .into_result(filename)?;
+
+    let note = match resolved {
+        Some(resolved) => db
+            .conn()
+            .execute_query(|conn| query::get_note_by_id(conn, resolved.id))
+            .context("Failed to get note metadata")?,
+        None => None,
+    };
 
     match note {
         Some(note) => {
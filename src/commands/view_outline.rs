@@ -0,0 +1,75 @@
+use crate::config::Config;
+use crate::db::Database;
+use crate::logger::Logger;
+use crate::query;
+use anyhow::{Context, Result};
+
+pub fn show_outline(config: &Config, note: &str, logger: Option<&Logger>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Database not found at: {}\nRun 'obsidian-cli init' first",
+            db_path.display()
+        );
+    }
+
+    let db = Database::open(&db_path)
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+
+    // Check if database has been indexed
+    let stats = db.get_stats().context("Failed to get database stats")?;
+    if stats.note_count == 0 {
+        anyhow::bail!(
+            "Database is empty. Run 'obsidian-cli-inspector index' to index your vault first"
+        );
+    }
+
+    let resolved = db
+        .conn()
+        .execute_query(|conn| query::resolve_note(conn, note))
+        .context("Failed to resolve note")?
+        .into_result(note)?;
+
+    let Some(resolved) = resolved else {
+        let msg = format!("Note not found: {note}");
+        if let Some(log) = logger {
+            let _ = log.print_and_log("outline", &msg);
+        } else {
+            println!("{msg}");
+        }
+        return Ok(());
+    };
+
+    let headings = db
+        .conn()
+        .execute_query(|conn| query::get_headings(conn, resolved.id))
+        .context("Failed to get headings")?;
+
+    let mut lines = vec![format!(
+        "Outline of '{}' ({}):",
+        resolved.title, resolved.path
+    )];
+    if headings.is_empty() {
+        lines.push("  (no headings)".to_string());
+    }
+
+    // Indent relative to the shallowest heading so notes without an H1 start flush
+    let min_level = headings.iter().map(|h| h.level).min().unwrap_or(1);
+    for heading in &headings {
+        let indent = "  ".repeat(usize::from(heading.level - min_level));
+        lines.push(format!(
+            "{}- {} (line {})",
+            indent, heading.text, heading.line
+        ));
+    }
+
+    let msg = lines.join("\n");
+    if let Some(log) = logger {
+        let _ = log.print_and_log("outline", &msg);
+    } else {
+        println!("{msg}");
+    }
+
+    Ok(())
+}
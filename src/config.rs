@@ -16,6 +16,8 @@ pub struct Config {
     pub graph: GraphConfig,
     #[serde(default)]
     pub llm: Option<LlmConfig>,
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -28,6 +30,59 @@ pub struct ExcludeConfig {
 pub struct SearchConfig {
     #[serde(default = "default_search_limit")]
     pub default_limit: usize,
+    /// Tokens in each search result snippet (1-64)
+    #[serde(default = "default_snippet_tokens")]
+    pub snippet_tokens: usize,
+    /// Marker before each match in text output; ANSI highlighting on a terminal when unset
+    #[serde(default)]
+    pub highlight_start: Option<String>,
+    /// Marker after each match in text output
+    #[serde(default)]
+    pub highlight_end: Option<String>,
+    /// `--group-by note` boost for query terms in the note title or aliases
+    #[serde(default = "default_title_boost")]
+    pub title_boost: f64,
+    /// `--group-by note` boost for query terms in matching heading paths
+    #[serde(default = "default_heading_boost")]
+    pub heading_boost: f64,
+    /// `--group-by note` boost for recently modified notes
+    #[serde(default = "default_recency_boost")]
+    pub recency_boost: f64,
+    /// Age in days at which the recency boost is halved
+    #[serde(default = "default_recency_half_life_days")]
+    pub recency_half_life_days: f64,
+    /// `--group-by note` boost for notes with many incoming links
+    #[serde(default = "default_inlink_boost")]
+    pub inlink_boost: f64,
+    /// How chunk text is split into searchable terms; changing it rebuilds the
+    /// full-text index on the next `init` or `index`
+    #[serde(default)]
+    pub tokenizer: FtsTokenizer,
+    /// Match `café` when searching `cafe` and the other way round
+    #[serde(default = "default_remove_diacritics")]
+    pub remove_diacritics: bool,
+    /// `search hybrid` weight of the full-text ranking
+    #[serde(default = "default_hybrid_keyword_weight")]
+    pub hybrid_keyword_weight: f64,
+    /// `search hybrid` weight of the embedding similarity ranking
+    #[serde(default = "default_hybrid_semantic_weight")]
+    pub hybrid_semantic_weight: f64,
+    /// Rank offset `k` of reciprocal-rank fusion
+    #[serde(default = "default_hybrid_rrf_k")]
+    pub hybrid_rrf_k: f64,
+}
+
+/// Full-text search tokenizer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsTokenizer {
+    /// Words split on whitespace and punctuation
+    #[default]
+    Unicode61,
+    /// Words reduced to their English stem, so `run` also finds `running`
+    Porter,
+    /// Three-character sequences: substring search and text without spaces, such as CJK
+    Trigram,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,10 +91,39 @@ pub struct GraphConfig {
     pub max_depth: usize,
 }
 
+/// Where `index embed`, `search semantic` and `search hybrid` get vectors from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// The embedding endpoint of `[llm]`
+    #[default]
+    Llm,
+    /// Hashed TF-IDF vectors computed offline; no endpoint needed, lower quality
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    #[serde(default)]
+    pub provider: EmbeddingProvider,
+    /// Vector size of the local provider
+    #[serde(default = "default_local_dimensions")]
+    pub dimensions: usize,
+}
+
+/// An OpenAI-compatible API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmConfig {
+    /// Base URL such as `https://api.openai.com/v1`; a full `/chat/completions`
+    /// or `/embeddings` URL also works
     pub api_url: String,
     pub model: String,
+    /// Model for `index embed` and `search semantic`, when different from `model`
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    /// Environment variable holding the API key sent as a bearer token
+    #[serde(default)]
+    pub api_key_env: Option<String>,
     #[serde(default = "default_timeout")]
     pub timeout_seconds: u64,
 }
@@ -56,10 +140,54 @@ fn default_search_limit() -> usize {
     20
 }
 
+fn default_snippet_tokens() -> usize {
+    crate::query::DEFAULT_SNIPPET_TOKENS
+}
+
+fn default_title_boost() -> f64 {
+    crate::query::NoteBoosts::default().title
+}
+
+fn default_heading_boost() -> f64 {
+    crate::query::NoteBoosts::default().heading
+}
+
+fn default_recency_boost() -> f64 {
+    crate::query::NoteBoosts::default().recency
+}
+
+fn default_recency_half_life_days() -> f64 {
+    crate::query::NoteBoosts::default().recency_half_life_days
+}
+
+fn default_inlink_boost() -> f64 {
+    crate::query::NoteBoosts::default().inlinks
+}
+
+fn default_hybrid_keyword_weight() -> f64 {
+    crate::query::HybridWeights::default().keyword
+}
+
+fn default_hybrid_semantic_weight() -> f64 {
+    crate::query::HybridWeights::default().semantic
+}
+
+fn default_hybrid_rrf_k() -> f64 {
+    crate::query::HybridWeights::default().rrf_k
+}
+
+fn default_remove_diacritics() -> bool {
+    true
+}
+
 fn default_max_depth() -> usize {
     3
 }
 
+fn default_local_dimensions() -> usize {
+    crate::embeddings::DEFAULT_LOCAL_DIMENSIONS
+}
+
 fn default_timeout() -> u64 {
     30
 }
@@ -68,10 +196,85 @@ impl Default for SearchConfig {
     fn default() -> Self {
         Self {
             default_limit: default_search_limit(),
+            snippet_tokens: default_snippet_tokens(),
+            highlight_start: None,
+            highlight_end: None,
+            title_boost: default_title_boost(),
+            heading_boost: default_heading_boost(),
+            recency_boost: default_recency_boost(),
+            recency_half_life_days: default_recency_half_life_days(),
+            inlink_boost: default_inlink_boost(),
+            tokenizer: FtsTokenizer::default(),
+            remove_diacritics: default_remove_diacritics(),
+            hybrid_keyword_weight: default_hybrid_keyword_weight(),
+            hybrid_semantic_weight: default_hybrid_semantic_weight(),
+            hybrid_rrf_k: default_hybrid_rrf_k(),
         }
     }
 }
 
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProvider::default(),
+            dimensions: default_local_dimensions(),
+        }
+    }
+}
+
+impl SearchConfig {
+    /// FTS5 `tokenize` option for the configured tokenizer
+    pub fn fts_tokenizer(&self) -> String {
+        // unicode61 removes diacritics unless told otherwise; trigram keeps them
+        let diacritics = match (self.tokenizer, self.remove_diacritics) {
+            (FtsTokenizer::Trigram, true) => " remove_diacritics 1",
+            (FtsTokenizer::Trigram, false) | (_, true) => "",
+            (_, false) => " remove_diacritics 0",
+        };
+        match self.tokenizer {
+            FtsTokenizer::Unicode61 => format!("unicode61{diacritics}"),
+            FtsTokenizer::Porter => format!("porter unicode61{diacritics}"),
+            FtsTokenizer::Trigram => format!("trigram{diacritics}"),
+        }
+    }
+
+    /// Reciprocal-rank fusion weights for `search hybrid`
+    pub fn hybrid_weights(&self) -> crate::query::HybridWeights {
+        crate::query::HybridWeights {
+            keyword: self.hybrid_keyword_weight,
+            semantic: self.hybrid_semantic_weight,
+            rrf_k: self.hybrid_rrf_k,
+        }
+    }
+
+    /// Boost weights for `search notes --group-by note`
+    pub fn note_boosts(&self) -> crate::query::NoteBoosts {
+        crate::query::NoteBoosts {
+            title: self.title_boost,
+            heading: self.heading_boost,
+            recency: self.recency_boost,
+            recency_half_life_days: self.recency_half_life_days,
+            inlinks: self.inlink_boost,
+        }
+    }
+}
+
+impl LlmConfig {
+    /// URL of the API endpoint `path`, such as `embeddings`
+    pub fn endpoint(&self, path: &str) -> String {
+        let base = self.api_url.trim_end_matches('/');
+        let base = ["/chat/completions", "/embeddings"]
+            .iter()
+            .find_map(|suffix| base.strip_suffix(suffix))
+            .unwrap_or(base);
+        format!("{base}/{path}")
+    }
+
+    pub fn embedding_model(&self) -> &str {
+        self.embedding_model.as_deref().unwrap_or(&self.model)
+    }
+}
+
 impl Default for GraphConfig {
     fn default() -> Self {
         Self {
@@ -123,6 +326,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         assert_eq!(config.database_path(), PathBuf::from("/custom/path/db.db"));
@@ -138,6 +342,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         let db_path = config.database_path();
@@ -154,6 +359,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         assert_eq!(config.log_dir(), PathBuf::from("/custom/logs"));
@@ -169,6 +375,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         let log_path = config.log_dir();
@@ -180,6 +387,8 @@ mod tests {
         let llm = LlmConfig {
             api_url: "http://api.example.com".to_string(),
             model: "gpt-4".to_string(),
+            embedding_model: None,
+            api_key_env: None,
             timeout_seconds: 60,
         };
 
@@ -187,6 +396,40 @@ mod tests {
         assert_eq!(llm.timeout_seconds, 60);
     }
 
+    #[test]
+    fn test_llm_config_endpoint_and_embedding_model() {
+        let mut llm: LlmConfig =
+            toml::from_str("api_url = \"http://localhost:8080/v1/\"\nmodel = \"chat\"").unwrap();
+        assert_eq!(
+            llm.endpoint("embeddings"),
+            "http://localhost:8080/v1/embeddings"
+        );
+        assert_eq!(llm.embedding_model(), "chat");
+
+        llm.api_url = "https://example.com/v1/chat/completions".to_string();
+        llm.embedding_model = Some("embed".to_string());
+        assert_eq!(
+            llm.endpoint("embeddings"),
+            "https://example.com/v1/embeddings"
+        );
+        assert_eq!(llm.embedding_model(), "embed");
+    }
+
+    #[test]
+    fn test_embeddings_config_provider() {
+        let config: Config = toml::from_str("vault_path = \"/vault\"").unwrap();
+        assert_eq!(config.embeddings.provider, EmbeddingProvider::Llm);
+        assert_eq!(config.embeddings.dimensions, 256);
+
+        let config: Config =
+            toml::from_str("vault_path = \"/vault\"\n[embeddings]\nprovider = \"local\"").unwrap();
+        assert_eq!(config.embeddings.provider, EmbeddingProvider::Local);
+        assert!(
+            toml::from_str::<Config>("vault_path = \"/v\"\n[embeddings]\nprovider = \"x\"")
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_config_default_search_limit() {
         assert_eq!(super::default_search_limit(), 20);
@@ -220,6 +463,7 @@ mod tests {
             search: SearchConfig::default(),
             graph: GraphConfig::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         assert_eq!(config.vault_path, PathBuf::from("/test/vault"));
@@ -231,6 +475,27 @@ mod tests {
     fn test_search_config_default_implementation() {
         let search = SearchConfig::default();
         assert_eq!(search.default_limit, 20);
+        assert_eq!(search.snippet_tokens, 16);
+        assert!(search.highlight_start.is_none());
+        assert_eq!(search.note_boosts().title, 1.0);
+        assert_eq!(search.fts_tokenizer(), "unicode61");
+        assert_eq!(search.hybrid_weights().rrf_k, 60.0);
+    }
+
+    #[test]
+    fn test_search_config_tokenizer() {
+        let search: SearchConfig =
+            toml::from_str("tokenizer = \"porter\"\nremove_diacritics = false").unwrap();
+        assert_eq!(search.tokenizer, FtsTokenizer::Porter);
+        assert_eq!(
+            search.fts_tokenizer(),
+            "porter unicode61 remove_diacritics 0"
+        );
+
+        let search: SearchConfig = toml::from_str("tokenizer = \"trigram\"").unwrap();
+        assert_eq!(search.fts_tokenizer(), "trigram remove_diacritics 1");
+
+        assert!(toml::from_str::<SearchConfig>("tokenizer = \"icu\"").is_err());
     }
 
     #[test]
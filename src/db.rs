@@ -1,13 +1,15 @@
 use rusqlite::{Connection, OptionalExtension, Result, Transaction};
 use std::path::Path;
 
+use crate::parser::{Block, Callout, Field, Footnote, Heading, Task};
+
 mod operations;
 mod schema;
 mod stats;
 
 pub use stats::DatabaseStats;
 
-pub const SCHEMA_VERSION: i32 = 1;
+pub const SCHEMA_VERSION: i32 = 13;
 
 #[derive(Debug, Clone)]
 pub struct NoteMetadata {
@@ -16,6 +18,15 @@ pub struct NoteMetadata {
     pub hash: String,
 }
 
+/// A chunk to embed, with the title of its note
+#[derive(Debug, Clone)]
+pub struct PendingChunk {
+    pub chunk_id: i64,
+    pub note_title: String,
+    pub heading_path: Option<String>,
+    pub text: String,
+}
+
 pub struct DatabaseTransaction<'a> {
     tx: Transaction<'a>,
 }
@@ -60,7 +71,21 @@ impl Database {
             )
             .ok();
 
-        if current_version.is_none() || force {
+        // The index is a derived cache of the vault, so an outdated schema is rebuilt
+        // from scratch rather than migrated in place.
+        let outdated = current_version.is_some_and(|v| v < SCHEMA_VERSION);
+        if outdated {
+            schema::drop_tables(&self.conn)?;
+            self.conn.execute(
+                "CREATE TABLE IF NOT EXISTS schema_version (
+                    version INTEGER PRIMARY KEY,
+                    applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+                [],
+            )?;
+        }
+
+        if current_version.is_none() || force || outdated {
             schema::create_schema(&self.conn)?;
             self.conn.execute(
                 "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
@@ -81,6 +106,21 @@ impl Database {
             .optional()
     }
 
+    /// Recreate `fts_chunks` with the FTS5 `tokenize` option `tokenizer` and
+    /// reindex every chunk, unless it already uses it. Returns whether it was rebuilt.
+    pub fn set_fts_tokenizer(&self, tokenizer: &str) -> Result<bool> {
+        if schema::fts_tokenizer(&self.conn)?.as_deref() == Some(tokenizer) {
+            return Ok(false);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DROP TABLE IF EXISTS fts_chunks", [])?;
+        schema::create_fts_table(&tx, tokenizer)?;
+        tx.execute("INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild')", [])?;
+        tx.commit()?;
+        Ok(true)
+    }
+
     pub fn get_stats(&self) -> Result<DatabaseStats> {
         stats::get_stats(&self.conn)
     }
@@ -96,6 +136,10 @@ impl Database {
         operations::insert_note(&self.conn, path, title, mtime, hash, frontmatter_json)
     }
 
+    pub fn set_note_size(&self, note_id: i64, size: u64) -> Result<()> {
+        operations::set_note_size(&self.conn, note_id, size)
+    }
+
     pub fn get_note_by_path(&self, path: &str) -> Result<Option<i64>> {
         operations::get_note_by_path(&self.conn, path)
     }
@@ -108,6 +152,39 @@ impl Database {
         operations::insert_tag(&self.conn, note_id, tag)
     }
 
+    pub fn insert_alias(&self, note_id: i64, alias: &str) -> Result<()> {
+        operations::insert_alias(&self.conn, note_id, alias)
+    }
+
+    pub fn insert_heading(&self, note_id: i64, heading: &Heading) -> Result<()> {
+        operations::insert_heading(&self.conn, note_id, heading)
+    }
+
+    pub fn insert_block(&self, note_id: i64, block: &Block) -> Result<()> {
+        operations::insert_block(&self.conn, note_id, block)
+    }
+
+    pub fn insert_task(&self, note_id: i64, task: &Task) -> Result<i64> {
+        operations::insert_task(&self.conn, note_id, task)
+    }
+
+    pub fn insert_field(&self, note_id: i64, field: &Field) -> Result<()> {
+        operations::insert_field(&self.conn, note_id, field)
+    }
+
+    pub fn insert_callout(
+        &self,
+        note_id: i64,
+        callout: &Callout,
+        parent_id: Option<i64>,
+    ) -> Result<i64> {
+        operations::insert_callout(&self.conn, note_id, callout, parent_id)
+    }
+
+    pub fn insert_footnote(&self, note_id: i64, footnote: &Footnote) -> Result<()> {
+        operations::insert_footnote(&self.conn, note_id, footnote)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn insert_link(
         &self,
@@ -157,6 +234,29 @@ impl Database {
         operations::clear_note_data(&self.conn, note_id)
     }
 
+    pub fn prune_embeddings(&self) -> Result<usize> {
+        operations::prune_embeddings(&self.conn)
+    }
+
+    pub fn cached_llm_response(
+        &self,
+        kind: &str,
+        model: &str,
+        hash: &str,
+    ) -> Result<Option<String>> {
+        operations::cached_llm_response(&self.conn, kind, model, hash)
+    }
+
+    pub fn store_llm_response(
+        &self,
+        kind: &str,
+        model: &str,
+        hash: &str,
+        response: &str,
+    ) -> Result<()> {
+        operations::store_llm_response(&self.conn, kind, model, hash, response)
+    }
+
     /// Execute a query function with access to the database connection
     pub fn conn(&self) -> DatabaseQueryExecutor<'_> {
         DatabaseQueryExecutor { conn: &self.conn }
@@ -175,6 +275,10 @@ impl DatabaseTransaction<'_> {
         operations::insert_note(&self.tx, path, title, mtime, hash, frontmatter_json)
     }
 
+    pub fn set_note_size(&self, note_id: i64, size: u64) -> Result<()> {
+        operations::set_note_size(&self.tx, note_id, size)
+    }
+
     pub fn get_note_metadata_by_path(&self, path: &str) -> Result<Option<NoteMetadata>> {
         operations::get_note_metadata_by_path(&self.tx, path)
     }
@@ -183,6 +287,39 @@ impl DatabaseTransaction<'_> {
         operations::insert_tag(&self.tx, note_id, tag)
     }
 
+    pub fn insert_alias(&self, note_id: i64, alias: &str) -> Result<()> {
+        operations::insert_alias(&self.tx, note_id, alias)
+    }
+
+    pub fn insert_heading(&self, note_id: i64, heading: &Heading) -> Result<()> {
+        operations::insert_heading(&self.tx, note_id, heading)
+    }
+
+    pub fn insert_block(&self, note_id: i64, block: &Block) -> Result<()> {
+        operations::insert_block(&self.tx, note_id, block)
+    }
+
+    pub fn insert_task(&self, note_id: i64, task: &Task) -> Result<i64> {
+        operations::insert_task(&self.tx, note_id, task)
+    }
+
+    pub fn insert_field(&self, note_id: i64, field: &Field) -> Result<()> {
+        operations::insert_field(&self.tx, note_id, field)
+    }
+
+    pub fn insert_callout(
+        &self,
+        note_id: i64,
+        callout: &Callout,
+        parent_id: Option<i64>,
+    ) -> Result<i64> {
+        operations::insert_callout(&self.tx, note_id, callout, parent_id)
+    }
+
+    pub fn insert_footnote(&self, note_id: i64, footnote: &Footnote) -> Result<()> {
+        operations::insert_footnote(&self.tx, note_id, footnote)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn insert_link(
         &self,
@@ -228,9 +365,40 @@ impl DatabaseTransaction<'_> {
         operations::clear_note_data(&self.tx, note_id)
     }
 
+    pub fn resolve_links(&self, resolve: impl Fn(&str) -> Option<i64>) -> Result<usize> {
+        operations::resolve_links(&self.tx, resolve)
+    }
+
+    pub fn chunks_without_embedding(&self, model: &str) -> Result<Vec<PendingChunk>> {
+        operations::chunks_without_embedding(&self.tx, model)
+    }
+
+    pub fn reuse_embedding(&self, chunk_id: i64, model: &str, hash: &str) -> Result<bool> {
+        operations::reuse_embedding(&self.tx, chunk_id, model, hash)
+    }
+
+    pub fn insert_embedding(
+        &self,
+        chunk_id: i64,
+        model: &str,
+        hash: &str,
+        vector: &[f32],
+    ) -> Result<()> {
+        operations::insert_embedding(&self.tx, chunk_id, model, hash, vector)
+    }
+
+    pub fn clear_embeddings(&self, model: &str) -> Result<()> {
+        operations::clear_embeddings(&self.tx, model)
+    }
+
     pub fn commit(self) -> Result<()> {
         self.tx.commit()
     }
+
+    /// Execute a query function with access to the transaction
+    pub fn conn(&self) -> DatabaseQueryExecutor<'_> {
+        DatabaseQueryExecutor { conn: &self.tx }
+    }
 }
 
 pub struct DatabaseQueryExecutor<'a> {
@@ -269,7 +437,7 @@ mod tests {
         db.initialize(false).unwrap();
 
         let version = db.get_version().unwrap();
-        assert_eq!(version, Some(1));
+        assert_eq!(version, Some(SCHEMA_VERSION));
     }
 
     #[test]
@@ -332,6 +500,61 @@ mod tests {
 
     #[test]
     fn test_schema_version_constant() {
-        assert_eq!(SCHEMA_VERSION, 1);
+        assert_eq!(SCHEMA_VERSION, 13);
+    }
+
+    #[test]
+    fn test_set_fts_tokenizer_rebuilds_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(temp_dir.path().join("test.db")).unwrap();
+        db.initialize(false).unwrap();
+        let note_id = db.insert_note("run.md", "Run", 0, "h", None).unwrap();
+        db.insert_chunk(note_id, None, "Running every morning")
+            .unwrap();
+
+        let matches = |db: &Database, query: &str| -> i64 {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM fts_chunks WHERE fts_chunks MATCH ?1",
+                    [query],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert!(!db.set_fts_tokenizer(schema::DEFAULT_FTS_TOKENIZER).unwrap());
+        assert_eq!(matches(&db, "run"), 0);
+
+        assert!(db.set_fts_tokenizer("porter unicode61").unwrap());
+        assert_eq!(matches(&db, "run"), 1);
+        assert!(!db.set_fts_tokenizer("porter unicode61").unwrap());
+
+        assert!(db.set_fts_tokenizer("trigram remove_diacritics 1").unwrap());
+        assert_eq!(matches(&db, "\"nning\""), 1);
+
+        // The vocabulary table follows the rebuilt index
+        let trigrams: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM fts_chunks_vocab WHERE term = 'nni'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(trigrams, 1);
+    }
+
+    #[test]
+    fn test_database_initialize_rebuilds_outdated_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let db = Database::open(&db_path).unwrap();
+        db.initialize(false).unwrap();
+        db.conn
+            .execute("UPDATE schema_version SET version = 1", [])
+            .unwrap();
+
+        db.initialize(false).unwrap();
+        assert_eq!(db.get_version().unwrap(), Some(SCHEMA_VERSION));
     }
 }
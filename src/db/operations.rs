@@ -1,6 +1,7 @@
 use rusqlite::{Connection, OptionalExtension, Result};
 
-use super::NoteMetadata;
+use super::{NoteMetadata, PendingChunk};
+use crate::parser::{field_value_type, tag_key, Block, Callout, Field, Footnote, Heading, Task};
 
 pub fn insert_note(
     conn: &Connection,
@@ -29,6 +30,15 @@ pub fn insert_note(
     Ok(note_id)
 }
 
+/// Record the file size in bytes of an indexed note
+pub fn set_note_size(conn: &Connection, note_id: i64, size: u64) -> Result<()> {
+    conn.execute(
+        "UPDATE notes SET size = ?1 WHERE id = ?2",
+        rusqlite::params![size as i64, note_id],
+    )?;
+    Ok(())
+}
+
 pub fn get_note_by_path(conn: &Connection, path: &str) -> Result<Option<i64>> {
     conn.query_row("SELECT id FROM notes WHERE path = ?1", [path], |row| {
         row.get(0)
@@ -51,10 +61,145 @@ pub fn get_note_metadata_by_path(conn: &Connection, path: &str) -> Result<Option
     .optional()
 }
 
+/// Insert a tag under its case-folded key, keeping `tag` as the display spelling
 pub fn insert_tag(conn: &Connection, note_id: i64, tag: &str) -> Result<()> {
     conn.execute(
-        "INSERT OR IGNORE INTO tags (note_id, tag) VALUES (?1, ?2)",
-        rusqlite::params![note_id, tag],
+        "INSERT OR IGNORE INTO tags (note_id, tag, display) VALUES (?1, ?2, ?3)",
+        rusqlite::params![note_id, tag_key(tag), tag.trim_start_matches('#')],
+    )?;
+    Ok(())
+}
+
+pub fn insert_alias(conn: &Connection, note_id: i64, alias: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO aliases (note_id, alias) VALUES (?1, ?2)",
+        rusqlite::params![note_id, alias],
+    )?;
+    Ok(())
+}
+
+pub fn insert_heading(conn: &Connection, note_id: i64, heading: &Heading) -> Result<()> {
+    conn.execute(
+        "INSERT INTO headings (note_id, level, text, slug, line, byte_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            note_id,
+            heading.level,
+            heading.text,
+            heading.slug,
+            heading.line as i64,
+            heading.byte_offset as i64
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn insert_block(conn: &Connection, note_id: i64, block: &Block) -> Result<()> {
+    conn.execute(
+        "INSERT INTO blocks (note_id, block_id, text, line, byte_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            note_id,
+            block.id,
+            block.text,
+            block.line as i64,
+            block.byte_offset as i64
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn insert_task(conn: &Connection, note_id: i64, task: &Task) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO tasks (note_id, status, text, line, byte_offset, heading, due, scheduled, done, priority)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            note_id,
+            task.status.to_string(),
+            task.text,
+            task.line as i64,
+            task.byte_offset as i64,
+            task.heading,
+            task.due,
+            task.scheduled,
+            task.done,
+            task.priority
+        ],
+    )?;
+    let task_id = conn.last_insert_rowid();
+
+    for tag in &task.tags {
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag) VALUES (?1, ?2)",
+            rusqlite::params![task_id, tag],
+        )?;
+    }
+
+    Ok(task_id)
+}
+
+/// Insert a callout; `parent_id` is the row id of the enclosing callout
+pub fn insert_callout(
+    conn: &Connection,
+    note_id: i64,
+    callout: &Callout,
+    parent_id: Option<i64>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO callouts (note_id, parent_id, type, title, body, fold, depth, line, byte_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![
+            note_id,
+            parent_id,
+            callout.kind,
+            callout.title,
+            callout.body,
+            callout.fold,
+            callout.depth as i64,
+            callout.line as i64,
+            callout.byte_offset as i64
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn insert_footnote(conn: &Connection, note_id: i64, footnote: &Footnote) -> Result<()> {
+    conn.execute(
+        "INSERT INTO footnotes (note_id, label, kind, text, line, byte_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            note_id,
+            footnote.label,
+            footnote.kind.as_str(),
+            footnote.text,
+            footnote.line as i64,
+            footnote.byte_offset as i64
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn insert_field(conn: &Connection, note_id: i64, field: &Field) -> Result<()> {
+    let value_type = field_value_type(&field.value);
+    let number = if value_type == "number" {
+        field.value.trim().parse::<f64>().ok()
+    } else {
+        None
+    };
+
+    conn.execute(
+        "INSERT INTO fields (note_id, key, value, value_type, number, source, line, byte_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            note_id,
+            field.key,
+            field.value,
+            value_type,
+            number,
+            field.source.as_str(),
+            field.line as i64,
+            field.byte_offset as i64
+        ],
     )?;
     Ok(())
 }
@@ -86,6 +231,27 @@ pub fn insert_link(
     Ok(())
 }
 
+/// Point every link at the note `resolve` returns for its target, or at none.
+/// Returns the number of resolved links.
+pub fn resolve_links(conn: &Connection, resolve: impl Fn(&str) -> Option<i64>) -> Result<usize> {
+    let mut stmt = conn.prepare("SELECT DISTINCT dst_text FROM links")?;
+    let targets = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut update = conn.prepare("UPDATE links SET dst_note_id = ?1 WHERE dst_text = ?2")?;
+    let mut resolved = 0;
+    for target in targets {
+        let note_id = resolve(&target);
+        let updated = update.execute(rusqlite::params![note_id, target])?;
+        if note_id.is_some() {
+            resolved += updated;
+        }
+    }
+
+    Ok(resolved)
+}
+
 pub fn insert_chunk(
     conn: &Connection,
     note_id: i64,
@@ -117,9 +283,116 @@ pub fn insert_chunk_with_offset(
     Ok(())
 }
 
+/// Chunks without an embedding from `model`, in id order
+pub fn chunks_without_embedding(conn: &Connection, model: &str) -> Result<Vec<PendingChunk>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, n.title, c.heading_path, c.text
+         FROM chunks c
+         JOIN notes n ON n.id = c.note_id
+         LEFT JOIN embeddings e ON e.chunk_id = c.id AND e.model = ?1
+         WHERE e.chunk_id IS NULL
+         ORDER BY c.id",
+    )?;
+    let chunks = stmt
+        .query_map([model], |row| {
+            Ok(PendingChunk {
+                chunk_id: row.get(0)?,
+                note_title: row.get(1)?,
+                heading_path: row.get(2)?,
+                text: row.get(3)?,
+            })
+        })?
+        .collect();
+    chunks
+}
+
+/// Copy the vector of text with `hash` already embedded by `model` to
+/// `chunk_id`. Returns whether such a vector existed.
+pub fn reuse_embedding(conn: &Connection, chunk_id: i64, model: &str, hash: &str) -> Result<bool> {
+    let copied = conn.execute(
+        "INSERT OR REPLACE INTO embeddings (chunk_id, model, hash, dimensions, vector)
+         SELECT ?1, model, hash, dimensions, vector FROM embeddings
+         WHERE model = ?2 AND hash = ?3 AND chunk_id != ?1
+         LIMIT 1",
+        rusqlite::params![chunk_id, model, hash],
+    )?;
+    Ok(copied > 0)
+}
+
+/// Store `vector` as little-endian `f32`s, replacing any vector of the chunk
+pub fn insert_embedding(
+    conn: &Connection,
+    chunk_id: i64,
+    model: &str,
+    hash: &str,
+    vector: &[f32],
+) -> Result<()> {
+    let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+    conn.execute(
+        "INSERT OR REPLACE INTO embeddings (chunk_id, model, hash, dimensions, vector)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![chunk_id, model, hash, vector.len() as i64, blob],
+    )?;
+    Ok(())
+}
+
+pub fn clear_embeddings(conn: &Connection, model: &str) -> Result<()> {
+    conn.execute("DELETE FROM embeddings WHERE model = ?1", [model])?;
+    Ok(())
+}
+
+/// Chat model reply of `kind` stored for the content with `hash`
+pub fn cached_llm_response(
+    conn: &Connection,
+    kind: &str,
+    model: &str,
+    hash: &str,
+) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT response FROM llm_cache WHERE kind = ?1 AND model = ?2 AND hash = ?3",
+        rusqlite::params![kind, model, hash],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Store a chat model reply, replacing an earlier one for the same content
+pub fn store_llm_response(
+    conn: &Connection,
+    kind: &str,
+    model: &str,
+    hash: &str,
+    response: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO llm_cache (kind, model, hash, response) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![kind, model, hash, response],
+    )?;
+    Ok(())
+}
+
+/// Delete the embeddings of chunks that no longer exist
+pub fn prune_embeddings(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM embeddings WHERE chunk_id NOT IN (SELECT id FROM chunks)",
+        [],
+    )
+}
+
 pub fn clear_note_data(conn: &Connection, note_id: i64) -> Result<()> {
     conn.execute("DELETE FROM links WHERE src_note_id = ?1", [note_id])?;
     conn.execute("DELETE FROM tags WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM aliases WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM headings WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM blocks WHERE note_id = ?1", [note_id])?;
+    conn.execute(
+        "DELETE FROM task_tags WHERE task_id IN (SELECT id FROM tasks WHERE note_id = ?1)",
+        [note_id],
+    )?;
+    conn.execute("DELETE FROM tasks WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM fields WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM callouts WHERE note_id = ?1", [note_id])?;
+    conn.execute("DELETE FROM footnotes WHERE note_id = ?1", [note_id])?;
     conn.execute("DELETE FROM chunks WHERE note_id = ?1", [note_id])?;
     Ok(())
 }
@@ -127,6 +400,7 @@ pub fn clear_note_data(conn: &Connection, note_id: i64) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::{FieldSource, FootnoteKind};
     use tempfile::TempDir;
 
     fn create_test_db() -> (TempDir, Connection) {
@@ -140,6 +414,7 @@ mod tests {
                 path TEXT NOT NULL UNIQUE,
                 title TEXT NOT NULL,
                 mtime INTEGER NOT NULL,
+                size INTEGER NOT NULL DEFAULT 0,
                 hash TEXT NOT NULL,
                 frontmatter_json TEXT,
                 created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
@@ -154,12 +429,127 @@ mod tests {
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 note_id INTEGER NOT NULL,
                 tag TEXT NOT NULL,
+                display TEXT NOT NULL,
                 UNIQUE(note_id, tag)
             )",
             [],
         )
         .unwrap();
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS aliases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                alias TEXT NOT NULL,
+                UNIQUE(note_id, alias)
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS headings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                level INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                slug TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blocks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                block_id TEXT NOT NULL,
+                text TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                text TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL,
+                heading TEXT,
+                due TEXT,
+                scheduled TEXT,
+                done TEXT,
+                priority TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                UNIQUE(task_id, tag)
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS callouts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                parent_id INTEGER,
+                type TEXT NOT NULL,
+                title TEXT,
+                body TEXT NOT NULL,
+                fold TEXT,
+                depth INTEGER NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS footnotes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                label TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                text TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fields (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                value_type TEXT NOT NULL,
+                number REAL,
+                source TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                byte_offset INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS links (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -189,6 +579,31 @@ mod tests {
         )
         .unwrap();
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                chunk_id INTEGER PRIMARY KEY,
+                model TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                dimensions INTEGER NOT NULL,
+                vector BLOB NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS llm_cache (
+                kind TEXT NOT NULL,
+                model TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (kind, model, hash)
+            )",
+            [],
+        )
+        .unwrap();
+
         (temp_dir, conn)
     }
 
@@ -271,6 +686,275 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_insert_tag_case_folded() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        insert_tag(&conn, note_id, "#Project").unwrap();
+        insert_tag(&conn, note_id, "project").unwrap();
+
+        let rows: Vec<(String, String)> = conn
+            .prepare("SELECT tag, display FROM tags")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(rows, vec![("project".to_string(), "Project".to_string())]);
+    }
+
+    #[test]
+    fn test_insert_alias() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+
+        insert_alias(&conn, note_id, "Other Name").unwrap();
+        insert_alias(&conn, note_id, "Other Name").unwrap();
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM aliases WHERE note_id = ?1",
+                [note_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_insert_heading() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        let heading = Heading {
+            level: 2,
+            text: "Key Ideas".to_string(),
+            slug: "key-ideas".to_string(),
+            line: 3,
+            byte_offset: 12,
+        };
+
+        insert_heading(&conn, note_id, &heading).unwrap();
+
+        let (level, slug): (i64, String) = conn
+            .query_row(
+                "SELECT level, slug FROM headings WHERE note_id = ?1",
+                [note_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(level, 2);
+        assert_eq!(slug, "key-ideas");
+    }
+
+    #[test]
+    fn test_insert_block() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        let block = Block {
+            id: "quote1".to_string(),
+            text: "A quoted idea".to_string(),
+            line: 7,
+            byte_offset: 80,
+        };
+
+        insert_block(&conn, note_id, &block).unwrap();
+
+        let (block_id, text): (String, String) = conn
+            .query_row(
+                "SELECT block_id, text FROM blocks WHERE note_id = ?1",
+                [note_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(block_id, "quote1");
+        assert_eq!(text, "A quoted idea");
+    }
+
+    #[test]
+    fn test_insert_task_and_clear() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        let task = Task {
+            status: '/',
+            text: "Draft outline #writing".to_string(),
+            line: 4,
+            byte_offset: 30,
+            heading: Some("Todo".to_string()),
+            due: Some("2024-05-01".to_string()),
+            scheduled: None,
+            done: None,
+            priority: Some("high".to_string()),
+            tags: vec!["writing".to_string()],
+        };
+
+        let task_id = insert_task(&conn, note_id, &task).unwrap();
+
+        let (status, due): (String, String) = conn
+            .query_row(
+                "SELECT status, due FROM tasks WHERE id = ?1",
+                [task_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(status, "/");
+        assert_eq!(due, "2024-05-01");
+
+        let tag_count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM task_tags WHERE task_id = ?1",
+                [task_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tag_count, 1);
+
+        clear_note_data(&conn, note_id).unwrap();
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM task_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_set_note_size() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        set_note_size(&conn, note_id, 2048).unwrap();
+
+        let size: i64 = conn
+            .query_row("SELECT size FROM notes WHERE id = ?1", [note_id], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(size, 2048);
+    }
+
+    #[test]
+    fn test_insert_callout_with_parent_and_clear() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        let callout = Callout {
+            kind: "decision".to_string(),
+            title: Some("Use SQLite".to_string()),
+            body: "Context".to_string(),
+            fold: None,
+            depth: 1,
+            parent: None,
+            line: 3,
+            byte_offset: 12,
+        };
+        let parent_id = insert_callout(&conn, note_id, &callout, None).unwrap();
+        let nested = Callout {
+            kind: "note".to_string(),
+            title: None,
+            fold: Some("collapsed".to_string()),
+            depth: 2,
+            parent: Some(0),
+            ..callout
+        };
+        insert_callout(&conn, note_id, &nested, Some(parent_id)).unwrap();
+
+        let stored_parent: Option<i64> = conn
+            .query_row(
+                "SELECT parent_id FROM callouts WHERE type = 'note'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored_parent, Some(parent_id));
+
+        clear_note_data(&conn, note_id).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM callouts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_insert_footnote_and_clear() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        let footnote = Footnote {
+            label: "1".to_string(),
+            kind: FootnoteKind::Definition,
+            text: "Source".to_string(),
+            line: 7,
+            byte_offset: 40,
+        };
+        insert_footnote(&conn, note_id, &footnote).unwrap();
+
+        let (kind, text): (String, String) = conn
+            .query_row("SELECT kind, text FROM footnotes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(kind, "definition");
+        assert_eq!(text, "Source");
+
+        clear_note_data(&conn, note_id).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM footnotes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_insert_field_types() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        for (key, value) in [("rating", "4.5"), ("status", "done")] {
+            let field = Field {
+                key: key.to_string(),
+                value: value.to_string(),
+                source: FieldSource::Inline,
+                line: 3,
+                byte_offset: 20,
+            };
+            insert_field(&conn, note_id, &field).unwrap();
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT key, value_type, number, source FROM fields ORDER BY id")
+            .unwrap();
+        let rows: Vec<(String, String, Option<f64>, String)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(
+            rows[0],
+            (
+                "rating".to_string(),
+                "number".to_string(),
+                Some(4.5),
+                "inline".to_string()
+            )
+        );
+        assert_eq!(rows[1].1, "text");
+        assert_eq!(rows[1].2, None);
+    }
+
     #[test]
     fn test_insert_link() {
         let (_temp_dir, conn) = create_test_db();
@@ -304,6 +988,34 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_resolve_links() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let src = insert_note(&conn, "src.md", "Source", 1, "h1", None).unwrap();
+        let js = insert_note(&conn, "dev/JavaScript.md", "JavaScript", 1, "h2", None).unwrap();
+        for target in ["JS", "Missing", "JS"] {
+            insert_link(&conn, src, target, "wikilink", false, None, None, None).unwrap();
+        }
+        let targets = |conn: &Connection| -> Vec<Option<i64>> {
+            let mut stmt = conn
+                .prepare("SELECT dst_note_id FROM links ORDER BY id")
+                .unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+
+        let resolve = |target: &str| (target == "JS").then_some(js);
+        assert_eq!(resolve_links(&conn, resolve).unwrap(), 2);
+        assert_eq!(targets(&conn), vec![Some(js), None, Some(js)]);
+
+        // Links whose target no longer resolves are cleared
+        assert_eq!(resolve_links(&conn, |_| None).unwrap(), 0);
+        assert_eq!(targets(&conn), vec![None, None, None]);
+    }
+
     #[test]
     fn test_insert_chunk() {
         let (_temp_dir, conn) = create_test_db();
@@ -315,6 +1027,66 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_embeddings_reuse_and_prune() {
+        let (_temp_dir, conn) = create_test_db();
+
+        let note_id =
+            insert_note(&conn, "test.md", "Test Note", 1234567890, "hash123", None).unwrap();
+        insert_chunk(&conn, note_id, None, "First").unwrap();
+        insert_chunk(&conn, note_id, None, "Second").unwrap();
+
+        let pending = chunks_without_embedding(&conn, "m").unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].note_title, "Test Note");
+        insert_embedding(&conn, pending[0].chunk_id, "m", "h1", &[0.6, 0.8]).unwrap();
+        assert_eq!(chunks_without_embedding(&conn, "m").unwrap().len(), 1);
+        assert_eq!(chunks_without_embedding(&conn, "other").unwrap().len(), 2);
+
+        // Reindexing replaces the chunks; the vector follows its text
+        clear_note_data(&conn, note_id).unwrap();
+        insert_chunk(&conn, note_id, None, "First").unwrap();
+        let pending = chunks_without_embedding(&conn, "m").unwrap();
+        assert_eq!(pending.len(), 1);
+        assert!(reuse_embedding(&conn, pending[0].chunk_id, "m", "h1").unwrap());
+        assert!(!reuse_embedding(&conn, pending[0].chunk_id, "m", "h2").unwrap());
+        assert_eq!(prune_embeddings(&conn).unwrap(), 1);
+
+        let (dimensions, blob): (i64, Vec<u8>) = conn
+            .query_row(
+                "SELECT dimensions, vector FROM embeddings WHERE chunk_id = ?1",
+                [pending[0].chunk_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(dimensions, 2);
+        assert_eq!(blob[..4], 0.6f32.to_le_bytes());
+
+        clear_embeddings(&conn, "m").unwrap();
+        assert_eq!(chunks_without_embedding(&conn, "m").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_llm_cache() {
+        let (_temp_dir, conn) = create_test_db();
+
+        assert_eq!(
+            cached_llm_response(&conn, "summary", "m", "h").unwrap(),
+            None
+        );
+        store_llm_response(&conn, "summary", "m", "h", "First").unwrap();
+        store_llm_response(&conn, "summary", "m", "h", "Second").unwrap();
+        assert_eq!(
+            cached_llm_response(&conn, "summary", "m", "h").unwrap(),
+            Some("Second".to_string())
+        );
+        assert_eq!(cached_llm_response(&conn, "tags", "m", "h").unwrap(), None);
+        assert_eq!(
+            cached_llm_response(&conn, "summary", "other", "h").unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_insert_chunk_with_offset() {
         let (_temp_dir, conn) = create_test_db();
@@ -1,4 +1,48 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+
+/// FTS5 `tokenize` option `fts_chunks` is created with before the configured
+/// tokenizer is applied
+pub const DEFAULT_FTS_TOKENIZER: &str = "unicode61";
+
+/// Create `fts_chunks` over `chunks` with the given FTS5 `tokenize` option
+pub fn create_fts_table(conn: &Connection, tokenizer: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS fts_chunks USING fts5(
+                note_id UNINDEXED,
+                heading_path,
+                text,
+                content=chunks,
+                content_rowid=id,
+                tokenize = '{}'
+            )",
+            tokenizer.replace('\'', "''")
+        ),
+        [],
+    )?;
+    Ok(())
+}
+
+/// The `tokenize` option `fts_chunks` was created with; `unicode61` when it
+/// predates configurable tokenizers, `None` without the table
+pub fn fts_tokenizer(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'fts_chunks'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(sql.map(|sql| {
+        sql.split_once("tokenize = '")
+            .and_then(|(_, rest)| rest.split_once('\''))
+            .map_or_else(
+                || "unicode61".to_string(),
+                |(tokenizer, _)| tokenizer.to_string(),
+            )
+    }))
+}
 
 pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
     // Notes table
@@ -8,6 +52,7 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
             path TEXT NOT NULL UNIQUE,
             title TEXT NOT NULL,
             mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL DEFAULT 0,
             hash TEXT NOT NULL,
             frontmatter_json TEXT,
             created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
@@ -34,18 +79,142 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         [],
     )?;
 
-    // Tags table
+    // Tags table (tag is the case-folded key, display the spelling used in the note)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS tags (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             note_id INTEGER NOT NULL,
             tag TEXT NOT NULL,
+            display TEXT NOT NULL,
             FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
             UNIQUE(note_id, tag)
         )",
         [],
     )?;
 
+    // Aliases table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS aliases (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            alias TEXT NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+            UNIQUE(note_id, alias)
+        )",
+        [],
+    )?;
+
+    // Headings table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS headings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            level INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Blocks table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS blocks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            block_id TEXT NOT NULL,
+            text TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Tasks table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            text TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            heading TEXT,
+            due TEXT,
+            scheduled TEXT,
+            done TEXT,
+            priority TEXT,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Task tags table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            UNIQUE(task_id, tag)
+        )",
+        [],
+    )?;
+
+    // Callouts table (`> [!type] Title` blocks, nested through parent_id)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS callouts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            parent_id INTEGER,
+            type TEXT NOT NULL,
+            title TEXT,
+            body TEXT NOT NULL,
+            fold TEXT,
+            depth INTEGER NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+            FOREIGN KEY (parent_id) REFERENCES callouts(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Footnotes table (`[^label]` references and `[^label]: text` definitions)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS footnotes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            text TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Fields table (frontmatter properties and Dataview inline fields)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS fields (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            value_type TEXT NOT NULL,
+            number REAL,
+            source TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
     // Chunks table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS chunks (
@@ -61,13 +230,38 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
     )?;
 
     // FTS5 virtual table for full-text search
+    create_fts_table(conn, DEFAULT_FTS_TOKENIZER)?;
+
+    // Terms in fts_chunks with their chunk and occurrence counts, for spelling suggestions
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS fts_chunks_vocab USING fts5vocab(fts_chunks, 'row')",
+        [],
+    )?;
+
+    // Chunk embeddings for semantic search. Not tied to chunks by a foreign key:
+    // reindexed notes get new chunk ids, and vectors are reused by content hash
+    // before `index embed` prunes the rows of chunks that are gone.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS embeddings (
+            chunk_id INTEGER PRIMARY KEY,
+            model TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            dimensions INTEGER NOT NULL,
+            vector BLOB NOT NULL
+        )",
+        [],
+    )?;
+
+    // Replies of the `[llm]` chat model, keyed by the SHA-256 of the note
+    // content they were asked about, so unchanged notes are not sent again
     conn.execute(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS fts_chunks USING fts5(
-            note_id UNINDEXED,
-            heading_path,
-            text,
-            content=chunks,
-            content_rowid=id
+        "CREATE TABLE IF NOT EXISTS llm_cache (
+            kind TEXT NOT NULL,
+            model TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            response TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (kind, model, hash)
         )",
         [],
     )?;
@@ -106,7 +300,22 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_links_dst_text ON links(dst_text)",
         "CREATE INDEX IF NOT EXISTS idx_tags_note ON tags(note_id)",
         "CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag)",
+        "CREATE INDEX IF NOT EXISTS idx_aliases_note ON aliases(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_aliases_alias ON aliases(alias COLLATE NOCASE)",
+        "CREATE INDEX IF NOT EXISTS idx_headings_note ON headings(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_blocks_note ON blocks(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_blocks_block_id ON blocks(block_id COLLATE NOCASE)",
+        "CREATE INDEX IF NOT EXISTS idx_tasks_note ON tasks(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_tasks_due ON tasks(due)",
+        "CREATE INDEX IF NOT EXISTS idx_task_tags_task ON task_tags(task_id)",
+        "CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag)",
+        "CREATE INDEX IF NOT EXISTS idx_callouts_note ON callouts(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_callouts_type ON callouts(type)",
+        "CREATE INDEX IF NOT EXISTS idx_footnotes_note ON footnotes(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_fields_note ON fields(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_fields_key ON fields(key)",
         "CREATE INDEX IF NOT EXISTS idx_chunks_note ON chunks(note_id)",
+        "CREATE INDEX IF NOT EXISTS idx_embeddings_hash ON embeddings(model, hash)",
     ];
 
     for stmt in index_statements {
@@ -117,8 +326,19 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
 }
 
 pub fn drop_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("DROP TABLE IF EXISTS llm_cache", [])?;
+    conn.execute("DROP TABLE IF EXISTS embeddings", [])?;
+    conn.execute("DROP TABLE IF EXISTS fts_chunks_vocab", [])?;
     conn.execute("DROP TABLE IF EXISTS fts_chunks", [])?;
     conn.execute("DROP TABLE IF EXISTS chunks", [])?;
+    conn.execute("DROP TABLE IF EXISTS callouts", [])?;
+    conn.execute("DROP TABLE IF EXISTS footnotes", [])?;
+    conn.execute("DROP TABLE IF EXISTS fields", [])?;
+    conn.execute("DROP TABLE IF EXISTS task_tags", [])?;
+    conn.execute("DROP TABLE IF EXISTS tasks", [])?;
+    conn.execute("DROP TABLE IF EXISTS blocks", [])?;
+    conn.execute("DROP TABLE IF EXISTS headings", [])?;
+    conn.execute("DROP TABLE IF EXISTS aliases", [])?;
     conn.execute("DROP TABLE IF EXISTS tags", [])?;
     conn.execute("DROP TABLE IF EXISTS links", [])?;
     conn.execute("DROP TABLE IF EXISTS notes", [])?;
@@ -0,0 +1,206 @@
+// Chunk embeddings for semantic search
+use crate::config::{Config, EmbeddingProvider};
+use crate::db::Database;
+use crate::llm::LlmClient;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+mod local;
+
+pub use local::{LocalEmbedder, DEFAULT_LOCAL_DIMENSIONS};
+
+/// Chunks sent to the embedding model per request
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Outcome of `update_embeddings`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingStats {
+    /// Chunks embedded by the model
+    pub embedded: usize,
+    /// Chunks whose text was already embedded under another chunk id
+    pub reused: usize,
+    /// Rows of chunks that no longer exist
+    pub removed: usize,
+}
+
+/// Source of chunk and query vectors, chosen by `[embeddings] provider`
+pub enum Embedder {
+    /// The embedding endpoint of `[llm]`
+    Remote(LlmClient),
+    /// Hashed TF-IDF vectors computed offline
+    Local(LocalEmbedder),
+}
+
+impl Embedder {
+    /// The configured embedder; the local one counts word frequencies over the
+    /// chunks indexed in `db`
+    pub fn from_config(config: &Config, db: &Database) -> Result<Self> {
+        match config.embeddings.provider {
+            EmbeddingProvider::Llm => {
+                let llm = config.llm.as_ref().context(
+                    "No [llm] section in the config; set api_url and model to use an embedding \
+                     endpoint, or set provider = \"local\" in [embeddings] to embed offline",
+                )?;
+                Ok(Self::Remote(LlmClient::new(llm)?))
+            }
+            EmbeddingProvider::Local => {
+                if config.embeddings.dimensions == 0 {
+                    anyhow::bail!("[embeddings] dimensions must be at least 1");
+                }
+                let embedder = db
+                    .conn()
+                    .execute_query(|conn| {
+                        LocalEmbedder::from_chunks(conn, config.embeddings.dimensions)
+                    })
+                    .context("Failed to count chunk words")?;
+                Ok(Self::Local(embedder))
+            }
+        }
+    }
+
+    /// Model name stored with the vectors
+    pub fn model(&self) -> String {
+        match self {
+            Self::Remote(client) => client.embedding_model().to_string(),
+            Self::Local(embedder) => embedder.model(),
+        }
+    }
+
+    /// Embed each of `inputs`, in input order
+    pub fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Self::Remote(client) => Ok(client.embed(inputs)?),
+            Self::Local(embedder) => Ok(inputs.iter().map(|input| embedder.embed(input)).collect()),
+        }
+    }
+
+    /// Local vectors depend on word frequencies across the whole vault, so
+    /// stored ones go stale whenever any chunk changes
+    pub fn reembeds_all(&self) -> bool {
+        matches!(self, Self::Local(_))
+    }
+}
+
+/// Text embedded for a chunk: the note title and heading path give the
+/// model the context a chunk's text alone may lack. Surrounding whitespace is
+/// dropped, so blank lines added around a chunk do not make it look changed.
+pub fn embedding_input(note_title: &str, heading_path: Option<&str>, text: &str) -> String {
+    let text = text.trim();
+    match heading_path {
+        Some(heading) => format!("{note_title}\n{heading}\n\n{text}"),
+        None => format!("{note_title}\n\n{text}"),
+    }
+}
+
+/// Hex SHA-256 of an embedding input
+pub fn content_hash(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Scale `vector` to unit length, so cosine similarity is a dot product
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Embed every chunk without a vector from `model`, `batch_size` chunks per
+/// call to `embed`. Text embedded before under another chunk id, as happens
+/// when a note is reindexed, is copied instead; with `force` every chunk is
+/// embedded again. Each batch is committed on its own, so an interrupted run
+/// resumes where it stopped.
+pub fn update_embeddings<F>(
+    db: &mut Database,
+    model: &str,
+    batch_size: usize,
+    force: bool,
+    mut embed: F,
+) -> Result<EmbeddingStats>
+where
+    F: FnMut(&[String]) -> Result<Vec<Vec<f32>>>,
+{
+    let mut stats = EmbeddingStats::default();
+
+    let tx = db.transaction()?;
+    if force {
+        tx.clear_embeddings(model)
+            .context("Failed to clear embeddings")?;
+    }
+    let mut pending = Vec::new();
+    for chunk in tx
+        .chunks_without_embedding(model)
+        .context("Failed to list chunks to embed")?
+    {
+        let input = embedding_input(
+            &chunk.note_title,
+            chunk.heading_path.as_deref(),
+            &chunk.text,
+        );
+        let hash = content_hash(&input);
+        if tx
+            .reuse_embedding(chunk.chunk_id, model, &hash)
+            .context("Failed to reuse embedding")?
+        {
+            stats.reused += 1;
+        } else {
+            pending.push((chunk.chunk_id, hash, input));
+        }
+    }
+    tx.commit().context("Failed to commit transaction")?;
+
+    for batch in pending.chunks(batch_size.max(1)) {
+        let inputs: Vec<String> = batch.iter().map(|(_, _, input)| input.clone()).collect();
+        let vectors = embed(&inputs)?;
+        if vectors.len() != batch.len() {
+            anyhow::bail!("Expected {} embeddings, got {}", batch.len(), vectors.len());
+        }
+
+        let tx = db.transaction()?;
+        for ((chunk_id, hash, _), mut vector) in batch.iter().zip(vectors) {
+            normalize(&mut vector);
+            tx.insert_embedding(*chunk_id, model, hash, &vector)
+                .context("Failed to store embedding")?;
+        }
+        tx.commit().context("Failed to commit transaction")?;
+        stats.embedded += batch.len();
+    }
+
+    stats.removed = db
+        .prune_embeddings()
+        .context("Failed to remove stale embeddings")?;
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_input_and_hash() {
+        assert_eq!(
+            embedding_input("Deep Work", Some("Deep Work > Rules"), "Focus."),
+            "Deep Work\nDeep Work > Rules\n\nFocus."
+        );
+        assert_eq!(embedding_input("Ideas", None, "Text\n\n"), "Ideas\n\nText");
+        assert_eq!(
+            content_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut vector = vec![3.0, 4.0];
+        normalize(&mut vector);
+        assert_eq!(vector, vec![0.6, 0.8]);
+
+        let mut zero = vec![0.0, 0.0];
+        normalize(&mut zero);
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+}
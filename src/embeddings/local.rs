@@ -0,0 +1,162 @@
+// Offline embeddings from feature-hashed TF-IDF weights
+use super::embedding_input;
+use rusqlite::{Connection, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Vector size of the local provider
+pub const DEFAULT_LOCAL_DIMENSIONS: usize = 256;
+
+/// Embeds text without a model. Each word adds its TF-IDF weight to one of
+/// `dimensions` buckets picked by its hash, with a sign also taken from the
+/// hash so that colliding words tend to cancel out rather than pile up.
+/// Document frequencies come from the indexed chunks, so vectors are only
+/// comparable within one snapshot of the vault.
+#[derive(Debug, Clone)]
+pub struct LocalEmbedder {
+    dimensions: usize,
+    document_count: usize,
+    document_frequencies: HashMap<String, usize>,
+}
+
+/// Lowercased words of two or more characters
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().nth(1).is_some())
+        .map(str::to_lowercase)
+}
+
+/// 64-bit FNV-1a, stable across platforms and releases
+fn fnv1a(word: &str) -> u64 {
+    word.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl LocalEmbedder {
+    /// Embedder with document frequencies counted over `documents`
+    pub fn new<'a>(dimensions: usize, documents: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut document_count = 0;
+        let mut document_frequencies: HashMap<String, usize> = HashMap::new();
+        for document in documents {
+            document_count += 1;
+            let unique: HashSet<String> = words(document).collect();
+            for word in unique {
+                *document_frequencies.entry(word).or_default() += 1;
+            }
+        }
+
+        Self {
+            dimensions: dimensions.max(1),
+            document_count,
+            document_frequencies,
+        }
+    }
+
+    /// Embedder over the embedding input of every indexed chunk
+    pub fn from_chunks(conn: &Connection, dimensions: usize) -> Result<Self> {
+        let mut stmt = conn.prepare(
+            "SELECT n.title, c.heading_path, c.text
+             FROM chunks c JOIN notes n ON n.id = c.note_id",
+        )?;
+        let inputs = stmt
+            .query_map([], |row| {
+                Ok(embedding_input(
+                    &row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?.as_deref(),
+                    &row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(dimensions, inputs.iter().map(String::as_str)))
+    }
+
+    /// Model name stored with the vectors; it changes with the dimensions
+    pub fn model(&self) -> String {
+        format!("local-tfidf-{}", self.dimensions)
+    }
+
+    /// Smoothed inverse document frequency, highest for unseen words
+    fn idf(&self, word: &str) -> f32 {
+        let df = self.document_frequencies.get(word).copied().unwrap_or(0);
+        ((1 + self.document_count) as f32 / (1 + df) as f32).ln() + 1.0
+    }
+
+    /// Hashed TF-IDF vector of `text`, with sublinear term frequency
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for word in words(text) {
+            *counts.entry(word).or_default() += 1;
+        }
+
+        let mut vector = vec![0.0; self.dimensions];
+        for (word, count) in counts {
+            let hash = fnv1a(&word);
+            let weight = (1.0 + (count as f32).ln()) * self.idf(&word);
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % self.dimensions as u64) as usize] += sign * weight;
+        }
+        vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        dot / (norm(a) * norm(b))
+    }
+
+    #[test]
+    fn test_words_lowercase_and_skip_single_characters() {
+        let found: Vec<String> = words("Deep-work, a Café! x 42").collect();
+        assert_eq!(found, vec!["deep", "work", "café", "42"]);
+    }
+
+    #[test]
+    fn test_local_embedding_ranks_shared_rare_words() {
+        let documents = [
+            "focus on deep work without distraction",
+            "the garden needs water",
+            "the meeting notes",
+            "the weekly review",
+        ];
+        let embedder = LocalEmbedder::new(64, documents);
+        assert_eq!(embedder.model(), "local-tfidf-64");
+
+        let query = embedder.embed("How to focus on deep work");
+        let similarities: Vec<f32> = documents
+            .iter()
+            .map(|document| cosine(&query, &embedder.embed(document)))
+            .collect();
+        assert!(similarities[0] > 0.5);
+        assert!(similarities[1..].iter().all(|s| *s < similarities[0]));
+
+        // Common words weigh less than rare ones
+        assert!(embedder.idf("the") < embedder.idf("garden"));
+        assert_eq!(embedder.embed("Deep work"), embedder.embed("work deep"));
+        assert!(embedder.embed("").iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_local_embedder_from_chunks() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, title TEXT);
+             CREATE TABLE chunks (id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT);
+             INSERT INTO notes VALUES (1, 'Garden'), (2, 'Work');
+             INSERT INTO chunks VALUES (1, 1, NULL, 'water plants'), (2, 2, 'Work > Focus', 'focus');",
+        )
+        .unwrap();
+
+        let embedder = LocalEmbedder::from_chunks(&conn, 16).unwrap();
+        assert_eq!(embedder.document_count, 2);
+        // Title, heading path and text all count
+        assert_eq!(embedder.document_frequencies["work"], 1);
+        assert_eq!(embedder.document_frequencies["focus"], 1);
+        assert_eq!(embedder.embed("water").len(), 16);
+    }
+}
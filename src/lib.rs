@@ -4,6 +4,8 @@ pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod db;
+pub mod embeddings;
+pub mod llm;
 pub mod logger;
 pub mod machine_contract;
 pub mod parser;
@@ -0,0 +1,197 @@
+// Client for the OpenAI-compatible API configured in `[llm]`
+use crate::config::LlmConfig;
+use serde_json::Value;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LlmError {
+    #[error("Environment variable {0} holding the API key is not set")]
+    MissingApiKey(String),
+
+    #[error("Request to {url} failed: {message}")]
+    Request { url: String, message: String },
+
+    #[error("Unexpected response from {url}: {message}")]
+    Response { url: String, message: String },
+}
+
+pub struct LlmClient {
+    agent: ureq::Agent,
+    config: LlmConfig,
+    api_key: Option<String>,
+}
+
+impl LlmClient {
+    pub fn new(config: &LlmConfig) -> Result<Self, LlmError> {
+        let api_key = match &config.api_key_env {
+            Some(var) => Some(
+                std::env::var(var)
+                    .ok()
+                    .filter(|key| !key.is_empty())
+                    .ok_or_else(|| LlmError::MissingApiKey(var.clone()))?,
+            ),
+            None => None,
+        };
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build();
+
+        Ok(Self {
+            agent,
+            config: config.clone(),
+            api_key,
+        })
+    }
+
+    pub fn embedding_model(&self) -> &str {
+        self.config.embedding_model()
+    }
+
+    /// Embed each of `inputs` with the embedding model, in input order
+    pub fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let url = self.config.endpoint("embeddings");
+        let response = self.post(
+            &url,
+            serde_json::json!({"model": self.embedding_model(), "input": inputs}),
+        )?;
+        parse_embeddings(&response, inputs.len()).map_err(|message| LlmError::Response {
+            url: url.clone(),
+            message,
+        })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Reply of the chat model to a system prompt and a user message
+    pub fn chat(&self, system: &str, user: &str) -> Result<String, LlmError> {
+        let url = self.config.endpoint("chat/completions");
+        let response = self.post(
+            &url,
+            serde_json::json!({
+                "model": self.model(),
+                "messages": [
+                    {"role": "system", "content": system},
+                    {"role": "user", "content": user}
+                ]
+            }),
+        )?;
+        parse_chat_reply(&response).map_err(|message| LlmError::Response {
+            url: url.clone(),
+            message,
+        })
+    }
+
+    fn post(&self, url: &str, body: Value) -> Result<Value, LlmError> {
+        let mut request = self.agent.post(url);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+
+        let response = request.send_json(body).map_err(|e| {
+            let message = match e {
+                ureq::Error::Status(code, response) => {
+                    let body = response.into_string().unwrap_or_default();
+                    format!("HTTP {code}: {}", body.trim())
+                }
+                ureq::Error::Transport(transport) => transport.to_string(),
+            };
+            LlmError::Request {
+                url: url.to_string(),
+                message,
+            }
+        })?;
+        response.into_json().map_err(|e| LlmError::Response {
+            url: url.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Message content of the first choice in a `/chat/completions` response
+fn parse_chat_reply(response: &Value) -> Result<String, String> {
+    response
+        .pointer("/choices/0/message/content")
+        .and_then(Value::as_str)
+        .map(|content| content.trim().to_string())
+        .ok_or_else(|| "missing `choices[0].message.content`".to_string())
+}
+
+/// Vectors from an `/embeddings` response, ordered by their `index`
+fn parse_embeddings(response: &Value, expected: usize) -> Result<Vec<Vec<f32>>, String> {
+    let data = response
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or("missing `data` array")?;
+
+    let mut vectors: Vec<Option<Vec<f32>>> = vec![None; expected];
+    for (position, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(Value::as_u64)
+            .map_or(position, |i| i as usize);
+        let embedding = item
+            .get("embedding")
+            .and_then(Value::as_array)
+            .ok_or("missing `embedding` array")?
+            .iter()
+            .map(|v| v.as_f64().map(|f| f as f32))
+            .collect::<Option<Vec<f32>>>()
+            .ok_or("non-numeric embedding value")?;
+        match vectors.get_mut(index) {
+            Some(slot) => *slot = Some(embedding),
+            None => return Err(format!("embedding index {index} out of range")),
+        }
+    }
+
+    vectors
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("expected {expected} embeddings, got {}", data.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_embeddings_orders_by_index() {
+        let response = serde_json::json!({
+            "data": [
+                {"index": 1, "embedding": [0.5, 1.0]},
+                {"index": 0, "embedding": [1, 0]}
+            ]
+        });
+        assert_eq!(
+            parse_embeddings(&response, 2).unwrap(),
+            vec![vec![1.0, 0.0], vec![0.5, 1.0]]
+        );
+
+        assert!(parse_embeddings(&response, 3).is_err());
+        assert!(parse_embeddings(&serde_json::json!({"error": "x"}), 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_chat_reply() {
+        let response = serde_json::json!({
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": " Answer [1]\n"}}]
+        });
+        assert_eq!(parse_chat_reply(&response).unwrap(), "Answer [1]");
+        assert!(parse_chat_reply(&serde_json::json!({"choices": []})).is_err());
+    }
+
+    #[test]
+    fn test_missing_api_key_variable() {
+        let config = LlmConfig {
+            api_url: "http://localhost".to_string(),
+            model: "m".to_string(),
+            embedding_model: None,
+            api_key_env: Some("OBSIDIAN_CLI_INSPECTOR_TEST_UNSET_KEY".to_string()),
+            timeout_seconds: 1,
+        };
+        let err = LlmClient::new(&config).err().unwrap();
+        assert!(matches!(err, LlmError::MissingApiKey(_)));
+    }
+}
@@ -13,6 +13,66 @@ impl ResultDataBuilder {
         serde_json::json!({ "total": items.len(), "items": items })
     }
 
+    fn tag_node_json(node: &query::TagNode) -> Value {
+        serde_json::json!({
+            "tag": node.tag,
+            "name": node.name,
+            "note_count": node.note_count,
+            "direct_count": node.direct_count,
+            "children": node.children.iter().map(Self::tag_node_json).collect::<Vec<_>>()
+        })
+    }
+
+    /// Search limit, snippet window and note filters from `search.*` params
+    fn search_options_param(config: &Config, params: &Value) -> Result<query::SearchOptions> {
+        let strings = |key: &str| -> Vec<String> {
+            params
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let string_param = |key: &str| params.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        Ok(query::SearchOptions {
+            limit: params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
+            snippet_tokens: params
+                .get("snippet_tokens")
+                .and_then(|v| v.as_u64())
+                .map_or(config.search.snippet_tokens, |v| v as usize),
+            paths: strings("paths"),
+            folders: strings("folders"),
+            tags: strings("tags"),
+            all_tags: params
+                .get("all_tags")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            modified_after: string_param("modified_after"),
+            modified_before: string_param("modified_before"),
+            properties: strings("props")
+                .iter()
+                .map(|expr| query::PropertyFilter::parse(expr))
+                .collect::<std::result::Result<Vec<_>, String>>()
+                .map_err(|e| anyhow::anyhow!(e))?,
+        })
+    }
+
+    /// Id of the note `note` resolves to, or none when nothing matches
+    fn resolve_note_param(db: &Database, note: &str) -> Result<Option<i64>> {
+        let resolved = db
+            .conn()
+            .execute_query(|conn| query::resolve_note(conn, note))
+            .context("Failed to resolve note")?
+            .into_result(note)?;
+        Ok(resolved.map(|n| n.id))
+    }
+
     pub fn build_query_result_data(
         config: &Config,
         command: &str,
@@ -40,12 +100,77 @@ impl ResultDataBuilder {
         match command {
             "search.notes" => {
                 let query = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
-                let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+                let options = Self::search_options_param(config, params)?;
+                let ranges = |ranges: &[(usize, usize)]| {
+                    ranges
+                        .iter()
+                        .map(|(start, end)| serde_json::json!({"start": start, "end": end}))
+                        .collect::<Vec<_>>()
+                };
+                let suggestions = db
+                    .conn()
+                    .execute_query(|conn| {
+                        Ok(query::suggest_terms(
+                            conn,
+                            query,
+                            query::SUGGESTIONS_PER_TERM,
+                        ))
+                    })
+                    .context("Failed to suggest search terms")??;
+                let suggestions: Vec<Value> = suggestions
+                    .iter()
+                    .map(|s| {
+                        serde_json::json!({
+                            "term": s.term,
+                            "suggestion": s.suggestion,
+                            "distance": s.distance,
+                            "documents": s.documents,
+                            "score": s.score
+                        })
+                    })
+                    .collect();
+
+                if params.get("group_by").and_then(|v| v.as_str()) == Some("note") {
+                    let boosts = config.search.note_boosts();
+                    let results = db
+                        .conn()
+                        .execute_query(|conn| {
+                            Ok(query::search_notes(conn, query, &options, &boosts))
+                        })
+                        .context("Failed to execute search query")??;
+
+                    let items = results
+                        .iter()
+                        .map(|result| {
+                            serde_json::json!({
+                                "note_id": result.note_id,
+                                "note_path": result.note_path,
+                                "note_title": result.note_title,
+                                "score": result.score,
+                                "relevance": result.relevance,
+                                "boosts": {
+                                    "title": result.boosts.title,
+                                    "heading": result.boosts.heading,
+                                    "recency": result.boosts.recency,
+                                    "inlinks": result.boosts.inlinks
+                                },
+                                "chunk_count": result.chunk_count,
+                                "heading_paths": result.heading_paths,
+                                "best_chunk_id": result.best.chunk_id,
+                                "snippet": result.best.snippet,
+                                "snippet_matches": ranges(&result.best.snippet_matches)
+                            })
+                        })
+                        .collect();
+                    let mut result = Self::query_result(items);
+                    result["suggestions"] = Value::from(suggestions);
+                    return Ok(result);
+                }
 
                 let results = db
                     .conn()
-                    .execute_query(|conn| query::search_chunks(conn, query, limit))
-                    .context("Failed to execute search query")?;
+                    .execute_query(|conn| Ok(query::search_chunks(conn, query, &options)))
+                    .context("Failed to execute search query")??;
 
                 let items = results
                     .iter()
@@ -57,20 +182,292 @@ impl ResultDataBuilder {
                             "note_title": result.note_title,
                             "heading_path": result.heading_path,
                             "chunk_text": result.chunk_text,
-                            "rank": result.rank
+                            "rank": result.rank,
+                            "snippet": result.snippet,
+                            "snippet_matches": ranges(&result.snippet_matches),
+                            "matches": ranges(&result.matches)
                         })
                     })
                     .collect();
 
-                Ok(Self::query_result(items))
+                let mut result = Self::query_result(items);
+                result["suggestions"] = Value::from(suggestions);
+                Ok(result)
             }
-            "search.backlinks" => {
+            "search.semantic" => {
+                let query = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
+                let options = query::SearchOptions {
+                    limit: params.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize,
+                    ..Self::search_options_param(config, params)?
+                };
+                let (model, results) = crate::commands::query_semantic::semantic_results(
+                    config, &db, query, &options,
+                )?;
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "chunk_id": result.chunk_id,
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "heading_path": result.heading_path,
+                            "chunk_text": result.chunk_text,
+                            "score": result.score
+                        })
+                    })
+                    .collect();
+
+                let mut result = Self::query_result(items);
+                result["model"] = Value::from(model);
+                Ok(result)
+            }
+            "search.hybrid" => {
+                let query = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
+                let options = Self::search_options_param(config, params)?;
+                let defaults = config.search.hybrid_weights();
+                let weights = query::HybridWeights {
+                    keyword: params
+                        .get("keyword_weight")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(defaults.keyword),
+                    semantic: params
+                        .get("semantic_weight")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(defaults.semantic),
+                    ..defaults
+                };
+                weights.validate().map_err(|e| anyhow::anyhow!(e))?;
+                let (model, results) = crate::commands::query_hybrid::hybrid_results(
+                    config, &db, query, &options, &weights,
+                )?;
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        let keyword = result.keyword.as_ref().map(|c| {
+                            serde_json::json!({
+                                "rank": c.rank,
+                                "bm25": c.value,
+                                "weight": c.weight,
+                                "contribution": c.score
+                            })
+                        });
+                        let semantic = result.semantic.as_ref().map(|c| {
+                            serde_json::json!({
+                                "rank": c.rank,
+                                "similarity": c.value,
+                                "weight": c.weight,
+                                "contribution": c.score
+                            })
+                        });
+                        serde_json::json!({
+                            "chunk_id": result.chunk_id,
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "heading_path": result.heading_path,
+                            "chunk_text": result.chunk_text,
+                            "snippet": result.snippet,
+                            "snippet_matches": result
+                                .snippet_matches
+                                .iter()
+                                .map(|(start, end)| serde_json::json!({"start": start, "end": end}))
+                                .collect::<Vec<_>>(),
+                            "score": result.score,
+                            "explanation": {"keyword": keyword, "semantic": semantic}
+                        })
+                    })
+                    .collect();
+
+                let mut result = Self::query_result(items);
+                result["model"] = Value::from(model);
+                result["weights"] = serde_json::json!({
+                    "keyword": weights.keyword,
+                    "semantic": weights.semantic,
+                    "rrf_k": weights.rrf_k
+                });
+                Ok(result)
+            }
+            "ask" => {
+                let question = params
+                    .get("question")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let show_context = params
+                    .get("show_context")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let options = query::SearchOptions {
+                    limit: params
+                        .get("limit")
+                        .and_then(|v| v.as_u64())
+                        .map_or(crate::commands::ask::DEFAULT_CONTEXT_CHUNKS, |v| v as usize),
+                    ..Self::search_options_param(config, params)?
+                };
+                let answer =
+                    crate::commands::ask::answer_question(config, &db, question, &options)?;
+
+                let items = answer
+                    .sources
+                    .iter()
+                    .map(|source| {
+                        let mut item = serde_json::json!({
+                            "number": source.number,
+                            "chunk_id": source.chunk_id,
+                            "note_path": source.note_path,
+                            "note_title": source.note_title,
+                            "heading_path": source.heading_path,
+                            "link": source.link(),
+                            "cited": source.cited
+                        });
+                        if show_context {
+                            item["text"] = Value::from(source.text.clone());
+                        }
+                        item
+                    })
+                    .collect();
+
+                let mut result = Self::query_result(items);
+                result["question"] = Value::from(question);
+                result["answer"] = Value::from(answer.answer);
+                result["model"] = Value::from(answer.model);
+                result["retrieval"] = Value::from(answer.retrieval.as_str());
+                Ok(result)
+            }
+            "analyze.summarize" => {
+                use crate::commands::analyze_summarize as summarize;
+                let note = params.get("note").and_then(|v| v.as_str());
+                let refresh = params
+                    .get("refresh")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let filters = Self::search_options_param(config, params)?;
+                let client = summarize::chat_client(config)?;
+                summarize::prepare_cache(&db)?;
+
+                let mut items = Vec::new();
+                for note in summarize::select_notes(&db, note, &filters)? {
+                    let summary = summarize::summarize_note(&db, &client, note, refresh)?;
+                    items.push(serde_json::json!({
+                        "note_path": summary.note_path,
+                        "note_title": summary.note_title,
+                        "summary": summary.summary,
+                        "cached": summary.cached
+                    }));
+                }
+
+                let mut result = Self::query_result(items);
+                result["model"] = Value::from(client.model());
+                Ok(result)
+            }
+            "analyze.suggest-tags" => {
+                use crate::commands::analyze_summarize as summarize;
                 let note = params.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                let refresh = params
+                    .get("refresh")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let client = summarize::chat_client(config)?;
+                summarize::prepare_cache(&db)?;
+
+                let mut result = Self::query_result(Vec::new());
+                result["model"] = Value::from(client.model());
+                result["note_path"] = Value::Null;
+                result["note_title"] = Value::Null;
+                result["title"] = Value::Null;
+                result["cached"] = Value::from(false);
+                if let Some(resolved) = summarize::resolve_note(&db, note)? {
+                    let suggestions = crate::commands::analyze_suggest_tags::suggest_for_note(
+                        &db, &client, resolved, refresh,
+                    )?;
+                    let items: Vec<Value> = suggestions
+                        .tags
+                        .iter()
+                        .map(|t| serde_json::json!({"tag": t.tag, "existing": t.existing}))
+                        .collect();
+                    result["total"] = Value::from(items.len());
+                    result["items"] = Value::from(items);
+                    result["note_path"] = Value::from(suggestions.note_path);
+                    result["note_title"] = Value::from(suggestions.note_title);
+                    result["title"] = Value::from(suggestions.title);
+                    result["cached"] = Value::from(suggestions.cached);
+                }
+                Ok(result)
+            }
+            "search.grep" => {
+                let pattern = params.get("pattern").and_then(|v| v.as_str()).unwrap_or("");
+                let filters = Self::search_options_param(config, params)?;
+                let options = query::GrepOptions {
+                    limit: params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize,
+                    context: params.get("context").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+                    ignore_case: params
+                        .get("ignore_case")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    paths: filters.paths,
+                    folders: filters.folders,
+                    tags: filters.tags,
+                    all_tags: filters.all_tags,
+                };
 
                 let results = db
                     .conn()
-                    .execute_query(|conn| query::get_backlinks(conn, note))
-                    .context("Failed to get backlinks")?;
+                    .execute_query(|conn| {
+                        Ok(query::grep_notes(
+                            conn,
+                            &config.vault_path,
+                            pattern,
+                            &options,
+                        ))
+                    })
+                    .context("Failed to search note files")??;
+                let lines = |lines: &[query::GrepLine]| {
+                    lines
+                        .iter()
+                        .map(|l| serde_json::json!({"line": l.line, "text": l.text}))
+                        .collect::<Vec<_>>()
+                };
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "line": result.line,
+                            "column": result.column,
+                            "text": result.text,
+                            "matches": result
+                                .matches
+                                .iter()
+                                .map(|(start, end)| serde_json::json!({
+                                    "start": start,
+                                    "end": end,
+                                    "text": &result.text[*start..*end]
+                                }))
+                                .collect::<Vec<_>>(),
+                            "before": lines(&result.before),
+                            "after": lines(&result.after)
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "search.backlinks" => {
+                let note = params.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                let target = Self::resolve_note_param(&db, note)?;
+
+                let results = match target {
+                    Some(target) => db
+                        .conn()
+                        .execute_query(|conn| query::get_backlinks(conn, target))
+                        .context("Failed to get backlinks")?,
+                    None => Vec::new(),
+                };
 
                 let items = results
                     .iter()
@@ -91,11 +488,15 @@ impl ResultDataBuilder {
             }
             "search.links" => {
                 let note = params.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                let target = Self::resolve_note_param(&db, note)?;
 
-                let results = db
-                    .conn()
-                    .execute_query(|conn| query::get_forward_links(conn, note))
-                    .context("Failed to get forward links")?;
+                let results = match target {
+                    Some(target) => db
+                        .conn()
+                        .execute_query(|conn| query::get_forward_links(conn, target))
+                        .context("Failed to get forward links")?,
+                    None => Vec::new(),
+                };
 
                 let items = results
                     .iter()
@@ -137,14 +538,414 @@ impl ResultDataBuilder {
 
                 Ok(Self::query_result(items))
             }
+            "search.mentions" => {
+                let note = params.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                let target = Self::resolve_note_param(&db, note)?;
+                let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+                let results = match target {
+                    Some(target) => db
+                        .conn()
+                        .execute_query(|conn| query::find_unlinked_mentions(conn, target, limit))
+                        .context("Failed to find unlinked mentions")?,
+                    None => Vec::new(),
+                };
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "chunk_id": result.chunk_id,
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "heading_path": result.heading_path,
+                            "matched_term": result.matched_term,
+                            "context": result.context
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "search.blocks" => {
+                let id = params.get("id").and_then(|v| v.as_str()).unwrap_or("");
+                let target = match params.get("note").and_then(|v| v.as_str()) {
+                    Some(note) => Self::resolve_note_param(&db, note)?.map(Some),
+                    None => Some(None),
+                };
+
+                // A note that is not found has no blocks
+                let results = match target {
+                    Some(note_id) => db
+                        .conn()
+                        .execute_query(|conn| query::search_blocks(conn, id, note_id))
+                        .context("Failed to search blocks")?,
+                    None => Vec::new(),
+                };
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "block_id": result.block_id,
+                            "text": result.text,
+                            "line": result.line,
+                            "byte_offset": result.byte_offset
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "search.properties" => {
+                let filters = params
+                    .get("filters")
+                    .and_then(|v| v.as_array())
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|v| v.as_str())
+                            .map(query::PropertyFilter::parse)
+                            .collect::<std::result::Result<Vec<_>, String>>()
+                    })
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!(e))?
+                    .unwrap_or_default();
+                let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
+
+                let items = if filters.is_empty() {
+                    db.conn()
+                        .execute_query(query::list_property_keys)
+                        .context("Failed to list properties")?
+                        .iter()
+                        .map(|result| {
+                            serde_json::json!({
+                                "key": result.key,
+                                "note_count": result.note_count,
+                                "value_types": result.value_types
+                            })
+                        })
+                        .collect()
+                } else {
+                    db.conn()
+                        .execute_query(|conn| {
+                            query::find_notes_by_properties(conn, &filters, limit)
+                        })
+                        .context("Failed to search properties")?
+                        .iter()
+                        .map(|result| {
+                            let fields: Vec<_> = result
+                                .fields
+                                .iter()
+                                .map(|field| {
+                                    serde_json::json!({
+                                        "key": field.key,
+                                        "value": field.value,
+                                        "value_type": field.value_type,
+                                        "source": field.source,
+                                        "line": field.line
+                                    })
+                                })
+                                .collect();
+                            serde_json::json!({
+                                "note_id": result.note_id,
+                                "note_path": result.note_path,
+                                "note_title": result.note_title,
+                                "fields": fields
+                            })
+                        })
+                        .collect()
+                };
+
+                Ok(Self::query_result(items))
+            }
+            "query.dql" => {
+                let dql = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
+
+                let result = db
+                    .conn()
+                    .execute_query(|conn| Ok(query::run_dql(conn, dql)))
+                    .context("Failed to run DQL query")??;
+
+                let items: Vec<Value> = result
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let values: serde_json::Map<String, Value> = result
+                            .columns
+                            .iter()
+                            .zip(&row.values)
+                            .map(|(column, value)| (column.clone(), value.to_json()))
+                            .collect();
+                        serde_json::json!({
+                            "note_path": row.note_path,
+                            "note_title": row.note_title,
+                            "values": values
+                        })
+                    })
+                    .collect();
+
+                Ok(serde_json::json!({
+                    "type": match result.kind {
+                        query::DqlKind::List => "list",
+                        query::DqlKind::Table => "table",
+                    },
+                    "columns": result.columns,
+                    "total": items.len(),
+                    "items": items
+                }))
+            }
+            "search.callouts" => {
+                let kind = params.get("type").and_then(|v| v.as_str());
+                let text = params.get("query").and_then(|v| v.as_str());
+                let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
+
+                let results = db
+                    .conn()
+                    .execute_query(|conn| query::search_callouts(conn, kind, text, limit))
+                    .context("Failed to search callouts")?;
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "id": result.id,
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "type": result.kind,
+                            "title": result.title,
+                            "body": result.body,
+                            "fold": result.fold,
+                            "depth": result.depth,
+                            "parent_id": result.parent_id,
+                            "line": result.line
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "diagnose.footnotes" => {
+                let results = db
+                    .conn()
+                    .execute_query(query::diagnose_footnotes)
+                    .context("Failed to diagnose footnotes")?;
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "label": result.label,
+                            "issue": result.issue,
+                            "lines": result.lines
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "analyze.tags" => {
+                let defaults = query::TagAnalysisOptions::default();
+                let usize_param = |key: &str, default: usize| {
+                    params
+                        .get(key)
+                        .and_then(|v| v.as_u64())
+                        .map_or(default, |v| v as usize)
+                };
+                let options = query::TagAnalysisOptions {
+                    min_count: usize_param("min_count", defaults.min_count),
+                    max_note_tags: usize_param("max_tags", defaults.max_note_tags),
+                    suggestions_per_note: usize_param("suggestions", defaults.suggestions_per_note),
+                    limit: usize_param("limit", defaults.limit),
+                };
+
+                let analysis = db
+                    .conn()
+                    .execute_query(|conn| query::analyze_tags(conn, &options))
+                    .context("Failed to analyze tags")?;
+
+                let pairs: Vec<Value> = analysis
+                    .pairs
+                    .iter()
+                    .map(|pair| {
+                        serde_json::json!({
+                            "tag_a": pair.tag_a,
+                            "tag_b": pair.tag_b,
+                            "together": pair.together,
+                            "count_a": pair.count_a,
+                            "count_b": pair.count_b,
+                            "pmi": pair.pmi
+                        })
+                    })
+                    .collect();
+                let companions: Vec<Value> = analysis
+                    .companions
+                    .iter()
+                    .map(|companion| {
+                        serde_json::json!({
+                            "tag": companion.tag,
+                            "implies": companion.implies,
+                            "together": companion.together,
+                            "tag_count": companion.tag_count,
+                            "confidence": companion.confidence
+                        })
+                    })
+                    .collect();
+                let suggestions: Vec<Value> = analysis
+                    .suggestions
+                    .iter()
+                    .map(|note| {
+                        serde_json::json!({
+                            "note_id": note.note_id,
+                            "note_path": note.note_path,
+                            "note_title": note.note_title,
+                            "current_tags": note.current_tags,
+                            "suggestions": note.suggestions.iter().map(|s| serde_json::json!({
+                                "tag": s.tag,
+                                "score": s.score,
+                                "link_score": s.link_score,
+                                "content_score": s.content_score
+                            })).collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+
+                Ok(serde_json::json!({
+                    "total_notes": analysis.total_notes,
+                    "tagged_notes": analysis.tagged_notes,
+                    "tag_count": analysis.tag_count,
+                    "pairs": pairs,
+                    "companions": companions,
+                    "suggestions": suggestions
+                }))
+            }
+            "diagnose.tags" => {
+                let groups = db
+                    .conn()
+                    .execute_query(query::find_similar_tags)
+                    .context("Failed to diagnose tags")?;
+
+                let items = groups
+                    .iter()
+                    .map(|group| {
+                        serde_json::json!({
+                            "note_count": group.note_count(),
+                            "variants": group.variants.iter().map(|v| serde_json::json!({
+                                "tag": v.tag,
+                                "note_count": v.note_count
+                            })).collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "search.tasks" => {
+                let string_param =
+                    |key: &str| params.get(key).and_then(|v| v.as_str()).map(String::from);
+                let filter = query::TaskFilter {
+                    open: params
+                        .get("open")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    overdue: params
+                        .get("overdue")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    due_before: string_param("due_before"),
+                    tag: string_param("tag"),
+                    notes: Self::search_options_param(config, params)?,
+                    limit: params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize,
+                };
+                let today = crate::commands::query_tasks::today();
+
+                let results = db
+                    .conn()
+                    .execute_query(|conn| query::search_tasks(conn, &filter, &today))
+                    .context("Failed to search tasks")?;
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "note_id": result.note_id,
+                            "note_path": result.note_path,
+                            "note_title": result.note_title,
+                            "status": result.status,
+                            "open": result.is_open(),
+                            "text": result.text,
+                            "line": result.line,
+                            "heading": result.heading,
+                            "due": result.due,
+                            "scheduled": result.scheduled,
+                            "done": result.done,
+                            "priority": result.priority,
+                            "tags": result.tags
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
+            "view.outline" => {
+                let note = params.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                let target = Self::resolve_note_param(&db, note)?;
+
+                let results = match target {
+                    Some(target) => db
+                        .conn()
+                        .execute_query(|conn| query::get_headings(conn, target))
+                        .context("Failed to get headings")?,
+                    None => Vec::new(),
+                };
+
+                let items = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "level": result.level,
+                            "text": result.text,
+                            "slug": result.slug,
+                            "line": result.line,
+                            "byte_offset": result.byte_offset
+                        })
+                    })
+                    .collect();
+
+                Ok(Self::query_result(items))
+            }
             "search.tags" => {
                 let list_all = params
                     .get("list")
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
                 let tag = params.get("tag").and_then(|v| v.as_str());
+                let exact = params
+                    .get("exact")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let tree = params
+                    .get("tree")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                if tree {
+                    let nodes = db
+                        .conn()
+                        .execute_query(|conn| query::tag_tree(conn, tag))
+                        .context("Failed to build tag tree")?;
 
-                if list_all || tag.is_none() {
+                    let items = nodes.iter().map(Self::tag_node_json).collect();
+                    Ok(Self::query_result(items))
+                } else if list_all || tag.is_none() {
                     let tags = db
                         .conn()
                         .execute_query(query::list_tags)
@@ -159,7 +960,7 @@ impl ResultDataBuilder {
                 } else if let Some(tag_name) = tag {
                     let results = db
                         .conn()
-                        .execute_query(|conn| query::get_notes_by_tag(conn, tag_name))
+                        .execute_query(|conn| query::get_notes_by_tag(conn, tag_name, exact))
                         .context("Failed to get notes by tag")?;
 
                     let items = results
@@ -245,6 +1046,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         let params = serde_json::json!({});
@@ -271,6 +1073,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         let params = serde_json::json!({});
@@ -294,6 +1097,7 @@ mod tests {
             search: Default::default(),
             graph: Default::default(),
             llm: None,
+            embeddings: Default::default(),
         };
 
         let result = ResultDataBuilder::build_view_stats_result_data(&config);
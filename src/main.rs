@@ -3,12 +3,16 @@ use clap::Parser;
 use obsidian_cli_inspector::{
     cli::{
         AnalyzeCommands, Cli, Commands, DiagnoseCommands, IndexCommands, InitCommands,
-        SearchCommands, ViewCommands,
+        QueryCommands, SearchCommands, SearchGroupBy, ViewCommands,
     },
     commands::*,
     config::Config,
     logger::Logger,
     machine_contract::ResultDataBuilder,
+    query::{
+        AmbiguousNoteError, GrepOptions, HybridWeights, SearchOptions, TagAnalysisOptions,
+        TaskFilter,
+    },
 };
 use serde_json::Value;
 use std::path::PathBuf;
@@ -89,17 +93,129 @@ fn main() -> Result<()> {
             )
         }
 
+        Commands::Index(IndexCommands::Embed { force, batch_size }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("index", "Starting Embed Command");
+            }
+            (
+                CommandMetadata {
+                    name: "index.embed".to_string(),
+                    params: serde_json::json!({"force": force, "batch_size": batch_size}),
+                },
+                embed_vault(&config, force, batch_size, logger.as_ref()),
+            )
+        }
+
         // ============================================================================
         // SEARCH Commands
         // ============================================================================
-        Commands::Search(SearchCommands::Notes { query, limit }) => {
+        Commands::Search(SearchCommands::Notes {
+            query,
+            limit,
+            snippet_tokens,
+            paths,
+            folders,
+            tags,
+            all_tags,
+            modified_after,
+            modified_before,
+            props,
+            group_by,
+        }) => {
             let config = load_config(cli.config)?;
             if let Some(ref log) = logger {
                 let _ = log.log_section("search.notes", "Starting Search Command");
             }
+            let options = SearchOptions {
+                limit,
+                snippet_tokens: snippet_tokens.unwrap_or(config.search.snippet_tokens),
+                paths,
+                folders,
+                tags,
+                all_tags,
+                modified_after,
+                modified_before,
+                properties: props,
+            };
+            let prop_strings: Vec<String> =
+                options.properties.iter().map(ToString::to_string).collect();
             let metadata = CommandMetadata {
                 name: "search.notes".to_string(),
-                params: serde_json::json!({"query": query, "limit": limit}),
+                params: serde_json::json!({
+                    "query": query,
+                    "limit": limit,
+                    "snippet_tokens": options.snippet_tokens,
+                    "paths": options.paths,
+                    "folders": options.folders,
+                    "tags": options.tags,
+                    "all_tags": options.all_tags,
+                    "modified_after": options.modified_after,
+                    "modified_before": options.modified_before,
+                    "props": prop_strings,
+                    "group_by": group_by.as_str()
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                let result = match group_by {
+                    SearchGroupBy::Chunk => {
+                        search_vault(&config, &query, &options, logger.as_ref())
+                    }
+                    SearchGroupBy::Note => {
+                        search_vault_by_note(&config, &query, &options, logger.as_ref())
+                    }
+                };
+                (metadata, result)
+            }
+        }
+        Commands::Search(SearchCommands::Grep {
+            pattern,
+            limit,
+            context,
+            ignore_case,
+            paths,
+            folders,
+            tags,
+            all_tags,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.grep", "Starting Grep Command");
+            }
+            let options = GrepOptions {
+                limit,
+                context,
+                ignore_case,
+                paths,
+                folders,
+                tags,
+                all_tags,
+            };
+            let metadata = CommandMetadata {
+                name: "search.grep".to_string(),
+                params: serde_json::json!({
+                    "pattern": pattern,
+                    "limit": limit,
+                    "context": context,
+                    "ignore_case": ignore_case,
+                    "paths": options.paths,
+                    "folders": options.folders,
+                    "tags": options.tags,
+                    "all_tags": options.all_tags
+                }),
             };
 
             if is_json {
@@ -117,7 +233,124 @@ fn main() -> Result<()> {
             } else {
                 (
                     metadata,
-                    search_vault(&config, &query, limit, logger.as_ref()),
+                    grep_vault(&config, &pattern, &options, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Semantic {
+            query,
+            limit,
+            paths,
+            folders,
+            tags,
+            all_tags,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.semantic", "Starting Semantic Search Command");
+            }
+            let options = SearchOptions {
+                limit,
+                paths,
+                folders,
+                tags,
+                all_tags,
+                ..Default::default()
+            };
+            let metadata = CommandMetadata {
+                name: "search.semantic".to_string(),
+                params: serde_json::json!({
+                    "query": query,
+                    "limit": limit,
+                    "paths": options.paths,
+                    "folders": options.folders,
+                    "tags": options.tags,
+                    "all_tags": options.all_tags
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    semantic_search_vault(&config, &query, &options, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Hybrid {
+            query,
+            limit,
+            snippet_tokens,
+            keyword_weight,
+            semantic_weight,
+            paths,
+            folders,
+            tags,
+            all_tags,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.hybrid", "Starting Hybrid Search Command");
+            }
+            let options = SearchOptions {
+                limit,
+                snippet_tokens: snippet_tokens.unwrap_or(config.search.snippet_tokens),
+                paths,
+                folders,
+                tags,
+                all_tags,
+                ..Default::default()
+            };
+            let defaults = config.search.hybrid_weights();
+            let weights = HybridWeights {
+                keyword: keyword_weight.unwrap_or(defaults.keyword),
+                semantic: semantic_weight.unwrap_or(defaults.semantic),
+                ..defaults
+            };
+            let metadata = CommandMetadata {
+                name: "search.hybrid".to_string(),
+                params: serde_json::json!({
+                    "query": query,
+                    "limit": limit,
+                    "snippet_tokens": options.snippet_tokens,
+                    "keyword_weight": weights.keyword,
+                    "semantic_weight": weights.semantic,
+                    "paths": options.paths,
+                    "folders": options.folders,
+                    "tags": options.tags,
+                    "all_tags": options.all_tags
+                }),
+            };
+
+            if let Err(e) = weights.validate() {
+                (metadata, Err(anyhow::anyhow!(e)))
+            } else if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    hybrid_search_vault(&config, &query, &options, &weights, logger.as_ref()),
                 )
             }
         }
@@ -199,14 +432,227 @@ fn main() -> Result<()> {
                 (metadata, list_unresolved_links(&config, logger.as_ref()))
             }
         }
-        Commands::Search(SearchCommands::Tags { tag, list }) => {
+        Commands::Search(SearchCommands::Mentions { note, limit }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.mentions", "Starting Mentions Command");
+            }
+            let metadata = CommandMetadata {
+                name: "search.mentions".to_string(),
+                params: serde_json::json!({"note": note, "limit": limit}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    list_unlinked_mentions(&config, &note, limit, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Blocks { id, note }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.blocks", "Starting Blocks Command");
+            }
+            let metadata = CommandMetadata {
+                name: "search.blocks".to_string(),
+                params: serde_json::json!({"id": id, "note": note}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    find_blocks(&config, &id, note.as_deref(), logger.as_ref()),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Callouts { query, kind, limit }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.callouts", "Starting Callouts Command");
+            }
+            let metadata = CommandMetadata {
+                name: "search.callouts".to_string(),
+                params: serde_json::json!({"query": query, "type": kind, "limit": limit}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    list_callouts(
+                        &config,
+                        kind.as_deref(),
+                        query.as_deref(),
+                        limit,
+                        logger.as_ref(),
+                    ),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Properties { filters, limit }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.properties", "Starting Properties Command");
+            }
+            let filter_strings: Vec<String> = filters.iter().map(ToString::to_string).collect();
+            let metadata = CommandMetadata {
+                name: "search.properties".to_string(),
+                params: serde_json::json!({"filters": filter_strings, "limit": limit}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    search_properties(&config, &filters, limit, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Search(SearchCommands::Tasks {
+            open,
+            overdue,
+            due_before,
+            tag,
+            paths,
+            folders,
+            limit,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("search.tasks", "Starting Tasks Command");
+            }
+            let metadata = CommandMetadata {
+                name: "search.tasks".to_string(),
+                params: serde_json::json!({
+                    "open": open,
+                    "overdue": overdue,
+                    "due_before": due_before,
+                    "tag": tag,
+                    "paths": paths,
+                    "folders": folders,
+                    "limit": limit
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                let filter = TaskFilter {
+                    open,
+                    overdue,
+                    due_before,
+                    tag,
+                    notes: SearchOptions {
+                        paths,
+                        folders,
+                        ..Default::default()
+                    },
+                    limit,
+                };
+                (metadata, list_tasks(&config, &filter, logger.as_ref()))
+            }
+        }
+        Commands::Query(QueryCommands::Dql { query }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("query.dql", "Starting DQL Command");
+            }
+            let metadata = CommandMetadata {
+                name: "query.dql".to_string(),
+                params: serde_json::json!({"query": query}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (metadata, run_dql_query(&config, &query, logger.as_ref()))
+            }
+        }
+        Commands::Search(SearchCommands::Tags {
+            tag,
+            list,
+            exact,
+            tree,
+        }) => {
             let config = load_config(cli.config)?;
             if let Some(ref log) = logger {
                 let _ = log.log_section("search.tags", "Starting Tags Command");
             }
             let metadata = CommandMetadata {
                 name: "search.tags".to_string(),
-                params: serde_json::json!({"tag": tag, "list": list}),
+                params: serde_json::json!({
+                    "tag": tag,
+                    "list": list,
+                    "exact": exact,
+                    "tree": tree
+                }),
             };
 
             if is_json {
@@ -221,10 +667,15 @@ fn main() -> Result<()> {
                     }
                     Err(e) => (metadata, Err(e)),
                 }
+            } else if tree {
+                (
+                    metadata,
+                    show_tag_tree(&config, tag.as_deref(), logger.as_ref()),
+                )
             } else {
                 (
                     metadata,
-                    list_notes_by_tag(&config, &tag, list, logger.as_ref()),
+                    list_notes_by_tag(&config, &tag, list, exact, logger.as_ref()),
                 )
             }
         }
@@ -260,6 +711,136 @@ fn main() -> Result<()> {
                 Ok(()),
             )
         }
+        Commands::Analyze(AnalyzeCommands::Tags {
+            min_count,
+            max_tags,
+            suggestions,
+            limit,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("analyze.tags", "Starting Analyze Tags Command");
+            }
+            let metadata = CommandMetadata {
+                name: "analyze.tags".to_string(),
+                params: serde_json::json!({
+                    "min_count": min_count,
+                    "max_tags": max_tags,
+                    "suggestions": suggestions,
+                    "limit": limit
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                let options = TagAnalysisOptions {
+                    min_count,
+                    max_note_tags: max_tags,
+                    suggestions_per_note: suggestions,
+                    limit,
+                };
+                (
+                    metadata,
+                    analyze_tags_cmd(&config, &options, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Analyze(AnalyzeCommands::Summarize {
+            note,
+            paths,
+            folders,
+            tags,
+            all_tags,
+            modified_after,
+            modified_before,
+            refresh,
+        }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("analyze.summarize", "Starting Summarize Command");
+            }
+            let filters = SearchOptions {
+                paths,
+                folders,
+                tags,
+                all_tags,
+                modified_after,
+                modified_before,
+                ..Default::default()
+            };
+            let metadata = CommandMetadata {
+                name: "analyze.summarize".to_string(),
+                params: serde_json::json!({
+                    "note": note,
+                    "paths": filters.paths,
+                    "folders": filters.folders,
+                    "tags": filters.tags,
+                    "all_tags": filters.all_tags,
+                    "modified_after": filters.modified_after,
+                    "modified_before": filters.modified_before,
+                    "refresh": refresh
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    summarize_vault(&config, note.as_deref(), &filters, refresh, logger.as_ref()),
+                )
+            }
+        }
+        Commands::Analyze(AnalyzeCommands::SuggestTags { note, refresh }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("analyze.suggest-tags", "Starting Suggest Tags Command");
+            }
+            let metadata = CommandMetadata {
+                name: "analyze.suggest-tags".to_string(),
+                params: serde_json::json!({"note": note, "refresh": refresh}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    suggest_tags_vault(&config, &note, refresh, logger.as_ref()),
+                )
+            }
+        }
 
         // ============================================================================
         // DIAGNOSE Commands
@@ -296,6 +877,59 @@ fn main() -> Result<()> {
                 diagnose_broken_links_cmd(&config, logger.as_ref()),
             )
         }
+        Commands::Diagnose(DiagnoseCommands::Footnotes) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ =
+                    log.log_section("diagnose.footnotes", "Starting Diagnose Footnotes Command");
+            }
+            let metadata = CommandMetadata {
+                name: "diagnose.footnotes".to_string(),
+                params: serde_json::json!({}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (metadata, diagnose_footnotes_cmd(&config, logger.as_ref()))
+            }
+        }
+        Commands::Diagnose(DiagnoseCommands::Tags) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("diagnose.tags", "Starting Diagnose Tags Command");
+            }
+            let metadata = CommandMetadata {
+                name: "diagnose.tags".to_string(),
+                params: serde_json::json!({}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (metadata, diagnose_tags_cmd(&config, logger.as_ref()))
+            }
+        }
 
         // ============================================================================
         // VIEW Commands
@@ -326,6 +960,89 @@ fn main() -> Result<()> {
                 get_note_describe(&config, &filename, logger.as_ref()),
             )
         }
+        Commands::View(ViewCommands::Outline { note }) => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("view.outline", "Starting Outline Command");
+            }
+            let metadata = CommandMetadata {
+                name: "view.outline".to_string(),
+                params: serde_json::json!({"note": note}),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (metadata, show_outline(&config, &note, logger.as_ref()))
+            }
+        }
+
+        // ============================================================================
+        // ASK Command
+        // ============================================================================
+        Commands::Ask {
+            question,
+            limit,
+            show_context,
+            paths,
+            folders,
+            tags,
+            all_tags,
+        } => {
+            let config = load_config(cli.config)?;
+            if let Some(ref log) = logger {
+                let _ = log.log_section("ask", "Starting Ask Command");
+            }
+            let options = SearchOptions {
+                limit,
+                paths,
+                folders,
+                tags,
+                all_tags,
+                ..Default::default()
+            };
+            let metadata = CommandMetadata {
+                name: "ask".to_string(),
+                params: serde_json::json!({
+                    "question": question,
+                    "limit": limit,
+                    "show_context": show_context,
+                    "paths": options.paths,
+                    "folders": options.folders,
+                    "tags": options.tags,
+                    "all_tags": options.all_tags
+                }),
+            };
+
+            if is_json {
+                match ResultDataBuilder::build_query_result_data(
+                    &config,
+                    &metadata.name,
+                    &metadata.params,
+                ) {
+                    Ok(result_data) => {
+                        query_result_override = Some(result_data);
+                        (metadata, Ok(()))
+                    }
+                    Err(e) => (metadata, Err(e)),
+                }
+            } else {
+                (
+                    metadata,
+                    ask_vault(&config, &question, &options, show_context, logger.as_ref()),
+                )
+            }
+        }
 
         // ============================================================================
         // TUI
@@ -384,17 +1101,37 @@ fn main() -> Result<()> {
                 );
             }
             Err(e) => {
-                let error = serde_json::json!({
-                    "error": {
-                        "code": 1,
-                        "message": e.to_string()
-                    }
-                });
+                let ambiguous = e
+                    .chain()
+                    .find_map(|cause| cause.downcast_ref::<AmbiguousNoteError>());
+                let (code, error) = match ambiguous {
+                    Some(ambiguous) => (
+                        3,
+                        serde_json::json!({
+                            "error": {
+                                "code": 3,
+                                "kind": "ambiguous_note",
+                                "message": e.to_string(),
+                                "identifier": ambiguous.identifier,
+                                "candidates": ambiguous.candidates
+                            }
+                        }),
+                    ),
+                    None => (
+                        1,
+                        serde_json::json!({
+                            "error": {
+                                "code": 1,
+                                "message": e.to_string()
+                            }
+                        }),
+                    ),
+                };
                 eprintln!(
                     "{}",
                     serde_json::to_string_pretty(&error).unwrap_or_default()
                 );
-                std::process::exit(1);
+                std::process::exit(code);
             }
         }
     } else {
@@ -483,6 +1220,7 @@ fn interactive_config_setup(path: Option<PathBuf>) -> Result<Config> {
                 search: Default::default(),
                 graph: Default::default(),
                 llm: None,
+                embeddings: Default::default(),
             }
         }
     };
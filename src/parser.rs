@@ -1,9 +1,24 @@
 use std::collections::HashMap;
 
+mod block;
+mod callout;
+mod field;
+mod footnote;
+mod heading;
 mod markdown;
+mod tag;
+mod task;
 mod wikilink;
 
+pub use block::{extract_blocks, Block};
+pub use callout::{callout_type_names, extract_callouts, Callout};
+pub(crate) use field::is_date;
+pub use field::{extract_inline_fields, field_value_type, normalize_field_key, Field, FieldSource};
+pub use footnote::{extract_footnotes, Footnote, FootnoteKind};
+pub use heading::{extract_headings, slugify, Heading};
 pub use markdown::{build_markdown_link, extract_markdown_links};
+pub use tag::{extract_inline_tags, normalize_tag, tag_key};
+pub use task::{extract_tasks, Task};
 pub use wikilink::{extract_wikilinks, parse_wikilink};
 
 #[derive(Debug, Clone)]
@@ -11,6 +26,15 @@ pub struct ParsedNote {
     pub title: String,
     pub frontmatter: HashMap<String, String>,
     pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    pub headings: Vec<Heading>,
+    pub blocks: Vec<Block>,
+    pub tasks: Vec<Task>,
+    pub callouts: Vec<Callout>,
+    /// Footnote references and definitions in document order
+    pub footnotes: Vec<Footnote>,
+    /// Frontmatter properties followed by inline fields
+    pub fields: Vec<Field>,
     pub links: Vec<Link>,
     pub text: String,
 }
@@ -40,12 +64,37 @@ impl LinkType {
     }
 }
 
+/// Something found at a byte offset and 1-based line of the text it was extracted from
+trait Positioned {
+    fn shift(&mut self, bytes: usize, lines: usize);
+}
+
+macro_rules! impl_positioned {
+    ($($item:ty),*) => {
+        $(impl Positioned for $item {
+            fn shift(&mut self, bytes: usize, lines: usize) {
+                self.byte_offset += bytes;
+                self.line += lines;
+            }
+        })*
+    };
+}
+
+impl_positioned!(Heading, Block, Task, Callout, Footnote, Field);
+
 pub struct MarkdownParser;
 
 impl MarkdownParser {
     pub fn parse(content: &str) -> ParsedNote {
         let (frontmatter, rest) = Self::extract_frontmatter(content);
         let tags = Self::extract_tags(&frontmatter, rest);
+        let aliases = Self::extract_aliases(&frontmatter);
+        let headings = Self::in_body(content, rest, extract_headings);
+        let blocks = Self::in_body(content, rest, extract_blocks);
+        let tasks = Self::in_body(content, rest, extract_tasks);
+        let callouts = Self::in_body(content, rest, extract_callouts);
+        let footnotes = Self::in_body(content, rest, extract_footnotes);
+        let fields = Self::extract_fields(content, &frontmatter, rest);
         let links = Self::extract_links(rest);
         let title = Self::extract_title(&frontmatter, rest);
 
@@ -53,6 +102,13 @@ impl MarkdownParser {
             title,
             frontmatter,
             tags,
+            aliases,
+            headings,
+            blocks,
+            tasks,
+            callouts,
+            footnotes,
+            fields,
             links,
             text: rest.to_string(),
         }
@@ -70,16 +126,32 @@ impl MarkdownParser {
             let frontmatter_text = &rest[..end_pos];
             let content_after = &rest[end_pos + 3..].trim_start();
 
+            // Key whose value continues as a YAML block list (`key:` followed by `- item` lines)
+            let mut list_key: Option<String> = None;
+
             for line in frontmatter_text.lines() {
                 let line = line.trim();
                 if line.is_empty() {
                     continue;
                 }
 
+                if let (Some(key), Some(item)) = (&list_key, line.strip_prefix('-')) {
+                    let item = item.trim().trim_matches('"').trim_matches('\'');
+                    if !item.is_empty() {
+                        Self::insert_list_item(&mut map, key, item);
+                    }
+                    continue;
+                }
+                list_key = None;
+
                 if let Some(colon_pos) = line.find(':') {
                     let key = line[..colon_pos].trim().to_lowercase();
                     let value = line[colon_pos + 1..].trim().to_string();
 
+                    if value.is_empty() {
+                        list_key = Some(key.clone());
+                    }
+
                     // Special handling for tags which might be arrays
                     if key == "tags" {
                         let tags_str = value.trim_start_matches('[').trim_end_matches(']');
@@ -101,6 +173,37 @@ impl MarkdownParser {
         (map, content)
     }
 
+    fn insert_list_item(map: &mut HashMap<String, String>, key: &str, item: &str) {
+        if key == "tags" {
+            map.insert(format!("tag_{item}"), item.to_string());
+            return;
+        }
+
+        let entry = map.entry(key.to_string()).or_default();
+        if !entry.is_empty() {
+            entry.push_str(", ");
+        }
+        entry.push_str(item);
+    }
+
+    fn extract_aliases(frontmatter: &HashMap<String, String>) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+
+        for key in ["aliases", "alias"] {
+            if let Some(value) = frontmatter.get(key) {
+                let list = value.trim_start_matches('[').trim_end_matches(']');
+                for alias in list.split(',') {
+                    let clean = alias.trim().trim_matches('"').trim_matches('\'');
+                    if !clean.is_empty() && !aliases.iter().any(|a| a == clean) {
+                        aliases.push(clean.to_string());
+                    }
+                }
+            }
+        }
+
+        aliases
+    }
+
     fn extract_title(frontmatter: &HashMap<String, String>, content: &str) -> String {
         // Try to get from frontmatter
         if let Some(title) = frontmatter.get("title") {
@@ -119,31 +222,90 @@ impl MarkdownParser {
         String::new()
     }
 
+    /// Valid tags from frontmatter and inline `#tags`, one per case-folded
+    /// key, keeping the first spelling (frontmatter first)
     fn extract_tags(frontmatter: &HashMap<String, String>, content: &str) -> Vec<String> {
-        let mut tags = Vec::new();
-
-        // From frontmatter
-        for (key, value) in frontmatter {
-            if key.starts_with("tag_") {
-                tags.push(value.clone());
+        let mut frontmatter_tags: Vec<&String> = frontmatter
+            .iter()
+            .filter(|(key, _)| key.starts_with("tag_"))
+            .map(|(_, value)| value)
+            .collect();
+        frontmatter_tags.sort();
+
+        let mut tags: Vec<String> = Vec::new();
+        let candidates = frontmatter_tags
+            .into_iter()
+            .filter_map(|tag| normalize_tag(tag))
+            .chain(extract_inline_tags(content));
+        for tag in candidates {
+            if !tags.iter().any(|t| tag_key(t) == tag_key(&tag)) {
+                tags.push(tag);
             }
         }
 
-        // From inline tags in content
-        for word in content.split_whitespace() {
-            if word.starts_with('#') && word.len() > 1 {
-                let tag = word
-                    .trim_matches(|c: char| !c.is_alphanumeric() && c != '/' && c != '_')
-                    .trim_start_matches('#');
-                if !tag.is_empty() && !tags.contains(&tag.to_string()) {
-                    tags.push(tag.to_string());
+        tags.sort_by_key(|tag| tag_key(tag));
+        tags
+    }
+
+    /// Run an extractor on the body and shift what it finds to positions in
+    /// the full file content. Extractors see only the text after the frontmatter,
+    /// so their byte offsets and lines start at the body.
+    fn in_body<T: Positioned>(content: &str, body: &str, extract: fn(&str) -> Vec<T>) -> Vec<T> {
+        let body_offset = content.len() - body.len();
+        let line_offset = content[..body_offset].matches('\n').count();
+
+        let mut items = extract(body);
+        for item in &mut items {
+            item.shift(body_offset, line_offset);
+        }
+        items
+    }
+
+    /// Frontmatter keys (except tags) followed by inline fields of the body
+    fn extract_fields(
+        content: &str,
+        frontmatter: &HashMap<String, String>,
+        body: &str,
+    ) -> Vec<Field> {
+        let mut fields = Vec::new();
+
+        if let Some(end_pos) = content
+            .strip_prefix("---")
+            .and_then(|rest| rest.find("---"))
+        {
+            let mut offset = 3;
+            for (idx, line) in content[3..3 + end_pos].split_inclusive('\n').enumerate() {
+                let line_offset = offset;
+                offset += line.len();
+
+                if line.starts_with(char::is_whitespace) || line.starts_with('-') {
+                    continue;
+                }
+                let Some(colon_pos) = line.find(':') else {
+                    continue;
+                };
+                let key = line[..colon_pos].trim().to_lowercase();
+                if key.is_empty() || key == "tags" {
+                    continue;
+                }
+                if let Some(value) = frontmatter.get(&key) {
+                    let value = value.trim().trim_matches('"').trim_matches('\'');
+                    if !value.is_empty() {
+                        fields.push(Field {
+                            key: normalize_field_key(&key),
+                            value: value.to_string(),
+                            source: FieldSource::Frontmatter,
+                            line: idx + 1,
+                            byte_offset: line_offset,
+                        });
+                    }
                 }
             }
         }
 
-        tags.sort();
-        tags.dedup();
-        tags
+        fields.extend(Self::in_body(content, body, extract_inline_fields));
+
+        fields
     }
 
     fn extract_links(content: &str) -> Vec<Link> {
@@ -227,10 +389,126 @@ mod tests {
             title: "Test".to_string(),
             frontmatter: HashMap::new(),
             tags: vec!["tag1".to_string()],
+            aliases: vec![],
+            headings: vec![],
+            blocks: vec![],
+            tasks: vec![],
+            callouts: vec![],
+            footnotes: vec![],
+            fields: vec![],
             links: vec![],
             text: "Content".to_string(),
         };
         assert_eq!(note.title, "Test");
         assert_eq!(note.tags.len(), 1);
     }
+
+    #[test]
+    fn test_parse_frontmatter_block_lists() {
+        let content = "---\ntags:\n  - focus\n  - work\naliases:\n  - Deep Focus\n  - \"Flow State\"\ncreated: 2024-02-01\n---\n# Deep Work\n";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.tags, vec!["focus", "work"]);
+        assert_eq!(parsed.aliases, vec!["Deep Focus", "Flow State"]);
+        assert_eq!(
+            parsed.frontmatter.get("created"),
+            Some(&"2024-02-01".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_headings_offset_past_frontmatter() {
+        let content = "---\ntitle: T\n---\n# Title\n\n## Part";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.headings.len(), 2);
+        assert_eq!(parsed.headings[0].line, 4);
+        assert_eq!(parsed.headings[1].line, 6);
+        assert_eq!(&content[parsed.headings[1].byte_offset..], "## Part");
+    }
+
+    #[test]
+    fn test_parse_blocks_offset_past_frontmatter() {
+        let content = "---\ntitle: T\n---\nIntro\n\nQuoted idea ^idea1";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.blocks.len(), 1);
+        assert_eq!(parsed.blocks[0].id, "idea1");
+        assert_eq!(parsed.blocks[0].text, "Quoted idea");
+        assert_eq!(parsed.blocks[0].line, 6);
+        assert_eq!(
+            &content[parsed.blocks[0].byte_offset..],
+            "Quoted idea ^idea1"
+        );
+    }
+
+    #[test]
+    fn test_parse_tasks_offset_past_frontmatter() {
+        let content = "---\ntitle: T\n---\n# Todo\n- [ ] Write 📅 2024-05-01";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.tasks.len(), 1);
+        assert_eq!(parsed.tasks[0].line, 5);
+        assert_eq!(parsed.tasks[0].heading.as_deref(), Some("Todo"));
+        assert_eq!(parsed.tasks[0].due.as_deref(), Some("2024-05-01"));
+        assert!(content[parsed.tasks[0].byte_offset..].starts_with("- [ ] Write"));
+    }
+
+    #[test]
+    fn test_parse_callouts_offset_past_frontmatter() {
+        let content = "---\ntitle: T\n---\n# ADR\n> [!decision] Use SQLite\n> Simple to ship";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.callouts.len(), 1);
+        assert_eq!(parsed.callouts[0].line, 5);
+        assert_eq!(parsed.callouts[0].body, "Simple to ship");
+        assert!(content[parsed.callouts[0].byte_offset..].starts_with("> [!decision]"));
+    }
+
+    #[test]
+    fn test_parse_footnotes_offset_past_frontmatter() {
+        let content = "---\ntitle: T\n---\nClaim[^1]\n\n[^1]: Source";
+        let parsed = MarkdownParser::parse(content);
+        assert_eq!(parsed.footnotes.len(), 2);
+        assert_eq!(parsed.footnotes[0].line, 4);
+        assert!(content[parsed.footnotes[0].byte_offset..].starts_with("[^1]"));
+        assert_eq!(parsed.footnotes[1].line, 6);
+        assert_eq!(parsed.footnotes[1].text, "Source");
+    }
+
+    #[test]
+    fn test_parse_fields_frontmatter_and_inline() {
+        let content =
+            "---\ntags: [a]\nrating: 4\nstatus: \"draft\"\n---\n# Book\n\nRating:: 5\nRead [on:: 2024-01-02]";
+        let parsed = MarkdownParser::parse(content);
+
+        let summary: Vec<(&str, &str, &str, usize)> = parsed
+            .fields
+            .iter()
+            .map(|f| (f.key.as_str(), f.value.as_str(), f.source.as_str(), f.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("rating", "4", "frontmatter", 3),
+                ("status", "draft", "frontmatter", 4),
+                ("rating", "5", "inline", 8),
+                ("on", "2024-01-02", "inline", 9),
+            ]
+        );
+        assert!(content[parsed.fields[2].byte_offset..].starts_with("Rating:: 5"));
+        assert!(content[parsed.fields[1].byte_offset..].starts_with("status:"));
+    }
+
+    #[test]
+    fn test_parse_aliases_inline_and_singular() {
+        let parsed = MarkdownParser::parse("---\naliases: [One, \"Two\"]\n---\nBody");
+        assert_eq!(parsed.aliases, vec!["One", "Two"]);
+
+        let parsed = MarkdownParser::parse("---\nalias: Solo\n---\nBody");
+        assert_eq!(parsed.aliases, vec!["Solo"]);
+    }
+
+    #[test]
+    fn test_parse_tags_case_folded_and_validated() {
+        let parsed = MarkdownParser::parse(
+            "---\ntags: [Project, \"2024\"]\n---\n#project #Meeting #2024 #y2024",
+        );
+        assert_eq!(parsed.tags, vec!["Meeting", "Project", "y2024"]);
+    }
 }
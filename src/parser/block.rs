@@ -0,0 +1,228 @@
+/// A paragraph or block marked with a `^block-id` anchor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub id: String,
+    /// Paragraph text without the anchor
+    pub text: String,
+    /// 1-based line number of the first line of the block
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+struct BlockLine<'a> {
+    line: usize,
+    byte_offset: usize,
+    text: &'a str,
+}
+
+/// Extract `^id` anchors outside fenced code blocks. An anchor at the end of a
+/// line marks the paragraph it closes, or the list item when the line is part
+/// of one; an anchor on its own line marks the block right above it (lists,
+/// quotes and tables) unless that block already has an anchor.
+pub fn extract_blocks(content: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+    // Lines of the block being read, where its current list item starts and
+    // whether any of it is already anchored
+    let mut current: Vec<BlockLine> = Vec::new();
+    let mut item_start = 0;
+    let mut current_anchored = false;
+    let mut previous: Vec<BlockLine> = Vec::new();
+    let mut previous_anchored = false;
+
+    for (idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw_line.len();
+
+        let line = raw_line.trim_end();
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            current.clear();
+            item_start = 0;
+            current_anchored = false;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            if !current.is_empty() {
+                previous = std::mem::take(&mut current);
+                previous_anchored = current_anchored;
+                item_start = 0;
+                current_anchored = false;
+            }
+            continue;
+        }
+
+        if let Some(id) = trimmed.strip_prefix('^').filter(|id| is_block_id(id)) {
+            let (target, anchored) = if current.is_empty() {
+                (&previous, &mut previous_anchored)
+            } else {
+                (&current, &mut current_anchored)
+            };
+            if !*anchored {
+                if let Some(block) = build_block(id, target) {
+                    blocks.push(block);
+                    *anchored = true;
+                }
+            }
+            continue;
+        }
+
+        if is_list_item(trimmed) {
+            item_start = current.len();
+        }
+        let anchor = split_trailing_anchor(line);
+        current.push(BlockLine {
+            line: idx + 1,
+            byte_offset: line_offset,
+            text: anchor.map_or(line, |(text, _)| text),
+        });
+        let Some((_, id)) = anchor else {
+            continue;
+        };
+
+        if is_list_item(current[item_start].text.trim()) {
+            // The item is the block; the list goes on
+            if let Some(block) = build_block(id, &current[item_start..]) {
+                blocks.push(block);
+            }
+            item_start = current.len();
+            current_anchored = true;
+        } else {
+            // The paragraph ends at its anchor
+            if let Some(block) = build_block(id, &current) {
+                blocks.push(block);
+            }
+            previous = std::mem::take(&mut current);
+            previous_anchored = true;
+            item_start = 0;
+            current_anchored = false;
+        }
+    }
+
+    blocks
+}
+
+/// Whether `line` starts a bullet or numbered list item
+fn is_list_item(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix(['-', '*', '+']) {
+        return rest.starts_with(' ');
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0
+        && line[digits..]
+            .strip_prefix(['.', ')'])
+            .is_some_and(|rest| rest.starts_with(' '))
+}
+
+fn build_block(id: &str, lines: &[BlockLine]) -> Option<Block> {
+    let first = lines.first()?;
+    let text = lines
+        .iter()
+        .map(|l| l.text.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(Block {
+        id: id.to_string(),
+        text,
+        line: first.line,
+        byte_offset: first.byte_offset,
+    })
+}
+
+/// Split `text ^id` into the text and the id
+fn split_trailing_anchor(line: &str) -> Option<(&str, &str)> {
+    let caret = line.rfind('^')?;
+    let (text, id) = (&line[..caret], &line[caret + 1..]);
+
+    if !text.ends_with(char::is_whitespace) || !is_block_id(id) {
+        return None;
+    }
+    Some((text.trim_end(), id))
+}
+
+fn is_block_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_blocks_trailing_anchor() {
+        let content = "Intro line.\n\nFirst line\nsecond line ^quote-1\n\nAfter.";
+        let blocks = extract_blocks(content);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].id, "quote-1");
+        assert_eq!(blocks[0].text, "First line\nsecond line");
+        assert_eq!(blocks[0].line, 3);
+        assert_eq!(blocks[0].byte_offset, 13);
+    }
+
+    #[test]
+    fn test_extract_blocks_standalone_anchor() {
+        let content = "- one\n- two\n\n^list1\n\n| a | b |\n^table1";
+        let blocks = extract_blocks(content);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "list1");
+        assert_eq!(blocks[0].text, "- one\n- two");
+        assert_eq!(blocks[1].id, "table1");
+        assert_eq!(blocks[1].line, 6);
+    }
+
+    #[test]
+    fn test_extract_blocks_ignores_code_and_carets() {
+        let content = "```\ncode ^inside\n```\nx^2 is math\n2^10 ^ok-id\nend ^not_valid";
+        let blocks = extract_blocks(content);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].id, "ok-id");
+        assert_eq!(blocks[0].text, "x^2 is math\n2^10");
+    }
+
+    #[test]
+    fn test_extract_blocks_anchored_list_items() {
+        let content = "- item one ^a\n- item two ^b\n  more of two\n- item three\n\nAfter.";
+        let blocks = extract_blocks(content);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "a");
+        assert_eq!(blocks[0].text, "- item one");
+        assert_eq!(blocks[0].line, 1);
+        assert_eq!(blocks[1].id, "b");
+        assert_eq!(blocks[1].text, "- item two");
+        assert_eq!(blocks[1].line, 2);
+        assert_eq!(blocks[1].byte_offset, 14);
+
+        let blocks = extract_blocks("1. first\n2. second ^two");
+        assert_eq!(blocks[0].text, "2. second");
+        assert_eq!(blocks[0].line, 2);
+    }
+
+    #[test]
+    fn test_extract_blocks_standalone_anchor_after_anchored_block() {
+        let content = "- item one ^a\n- item two ^b\n\nPara ^c\n\n^d\n";
+        let blocks = extract_blocks(content);
+
+        let ids: Vec<&str> = blocks.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(blocks[2].text, "Para");
+        assert_eq!(blocks[2].line, 4);
+
+        // A paragraph right after an anchored one starts a new block
+        let blocks = extract_blocks("One ^x\nTwo\n^y\n^z");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].id, "y");
+        assert_eq!(blocks[1].text, "Two");
+        assert_eq!(blocks[1].line, 2);
+    }
+}
@@ -0,0 +1,231 @@
+/// Built-in callout types and their aliases, as Obsidian groups them
+const CALLOUT_ALIASES: [(&str, &[&str]); 13] = [
+    ("abstract", &["summary", "tldr"]),
+    ("tip", &["hint", "important"]),
+    ("success", &["check", "done"]),
+    ("question", &["help", "faq"]),
+    ("warning", &["caution", "attention"]),
+    ("failure", &["fail", "missing"]),
+    ("danger", &["error"]),
+    ("quote", &["cite"]),
+    ("note", &[]),
+    ("info", &[]),
+    ("todo", &[]),
+    ("bug", &[]),
+    ("example", &[]),
+];
+
+/// An Obsidian callout (`> [!type]+ Title`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Callout {
+    /// Type as written, lowercased (`warning`, `decision`, ...)
+    pub kind: String,
+    /// Title after the type marker, if any
+    pub title: Option<String>,
+    /// Content lines without the callout's quote markers; nested callouts are
+    /// kept as their own entries
+    pub body: String,
+    /// `expanded` for `[!type]+`, `collapsed` for `[!type]-`, `None` when not foldable
+    pub fold: Option<String>,
+    /// Quote depth: 1 for a top-level callout, 2 for a callout nested in it, ...
+    pub depth: usize,
+    /// Index of the enclosing callout in the extracted list
+    pub parent: Option<usize>,
+    /// 1-based line number of the callout header
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+/// All type names matching `kind`: the type itself plus its aliases or
+/// canonical name, so `warning` also finds `caution` and `attention`
+pub fn callout_type_names(kind: &str) -> Vec<String> {
+    let kind = kind.trim().to_lowercase();
+    CALLOUT_ALIASES
+        .iter()
+        .find(|(name, aliases)| *name == kind || aliases.contains(&kind.as_str()))
+        .map(|(name, aliases)| {
+            std::iter::once(*name)
+                .chain(aliases.iter().copied())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_else(|| vec![kind])
+}
+
+/// Extract callouts outside fenced code blocks
+pub fn extract_callouts(content: &str) -> Vec<Callout> {
+    let mut callouts: Vec<Callout> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for (idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw_line.len();
+
+        let line = raw_line.trim_end();
+        let (depth, text) = split_quote(line);
+
+        if depth == 0 {
+            open.clear();
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        while open.last().is_some_and(|&i| callouts[i].depth > depth) {
+            open.pop();
+        }
+
+        let parent_depth = open.last().map_or(0, |&i| callouts[i].depth);
+        if depth > parent_depth {
+            if let Some((kind, fold, title)) = parse_header(text) {
+                callouts.push(Callout {
+                    kind,
+                    title,
+                    body: String::new(),
+                    fold,
+                    depth,
+                    parent: open.last().copied(),
+                    line: idx + 1,
+                    byte_offset: line_offset,
+                });
+                open.push(callouts.len() - 1);
+                continue;
+            }
+        }
+
+        if let Some(&current) = open.last() {
+            let body_line = strip_quote_markers(line, callouts[current].depth);
+            let body = &mut callouts[current].body;
+            if !body.is_empty() {
+                body.push('\n');
+            }
+            body.push_str(body_line);
+        }
+    }
+
+    for callout in &mut callouts {
+        callout.body = callout.body.trim().to_string();
+    }
+    callouts
+}
+
+/// Quote depth of a line and its text after all quote markers
+fn split_quote(line: &str) -> (usize, &str) {
+    let mut depth = 0;
+    let mut rest = line.trim_start();
+    while let Some(stripped) = rest.strip_prefix('>') {
+        depth += 1;
+        rest = stripped.trim_start();
+    }
+    (depth, rest)
+}
+
+/// Remove `depth` quote markers, keeping deeper markers
+fn strip_quote_markers(line: &str, depth: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..depth {
+        match rest.strip_prefix('>') {
+            Some(stripped) => rest = stripped.strip_prefix(' ').unwrap_or(stripped),
+            None => break,
+        }
+        if rest.trim_start().starts_with('>') {
+            rest = rest.trim_start();
+        }
+    }
+    rest
+}
+
+/// Parse `[!type]`, an optional fold marker and the title
+fn parse_header(text: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let rest = text.strip_prefix("[!")?;
+    let close = rest.find(']')?;
+    let kind = &rest[..close];
+    if kind.is_empty()
+        || !kind
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+
+    let mut rest = &rest[close + 1..];
+    let fold = match rest.chars().next() {
+        Some('+') => Some("expanded".to_string()),
+        Some('-') => Some("collapsed".to_string()),
+        _ => None,
+    };
+    if fold.is_some() {
+        rest = &rest[1..];
+    }
+
+    let title = rest.trim();
+    let title = (!title.is_empty()).then(|| title.to_string());
+    Some((kind.to_lowercase(), fold, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_callouts_type_title_fold() {
+        let content =
+            "Intro\n> [!Warning]- Careful now\n> Body line\n> second line\n\n> plain quote";
+        let callouts = extract_callouts(content);
+
+        assert_eq!(callouts.len(), 1);
+        assert_eq!(callouts[0].kind, "warning");
+        assert_eq!(callouts[0].title.as_deref(), Some("Careful now"));
+        assert_eq!(callouts[0].fold.as_deref(), Some("collapsed"));
+        assert_eq!(callouts[0].body, "Body line\nsecond line");
+        assert_eq!(callouts[0].line, 2);
+        assert_eq!(callouts[0].byte_offset, 6);
+    }
+
+    #[test]
+    fn test_extract_nested_callouts() {
+        let content =
+            "> [!decision] Use SQLite\n> Context\n> > [!note]\n> > Nested body\n> Back to outer\n> [!todo] not a header";
+        let callouts = extract_callouts(content);
+
+        assert_eq!(callouts.len(), 2);
+        assert_eq!(callouts[0].kind, "decision");
+        assert_eq!(
+            callouts[0].body,
+            "Context\nBack to outer\n[!todo] not a header"
+        );
+        assert_eq!(callouts[1].kind, "note");
+        assert_eq!(callouts[1].title, None);
+        assert_eq!(callouts[1].fold, None);
+        assert_eq!(callouts[1].depth, 2);
+        assert_eq!(callouts[1].parent, Some(0));
+        assert_eq!(callouts[1].body, "Nested body");
+    }
+
+    #[test]
+    fn test_extract_callouts_skips_code() {
+        let content = "```\n> [!note] in code\n```\n>[!tip]+\n>body";
+        let callouts = extract_callouts(content);
+
+        assert_eq!(callouts.len(), 1);
+        assert_eq!(callouts[0].kind, "tip");
+        assert_eq!(callouts[0].fold.as_deref(), Some("expanded"));
+        assert_eq!(callouts[0].body, "body");
+    }
+
+    #[test]
+    fn test_callout_type_names() {
+        assert_eq!(
+            callout_type_names("Caution"),
+            vec!["warning", "caution", "attention"]
+        );
+        assert_eq!(callout_type_names("decision"), vec!["decision"]);
+    }
+}
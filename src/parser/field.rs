@@ -0,0 +1,242 @@
+/// Where a note property was declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldSource {
+    Frontmatter,
+    Inline,
+}
+
+impl FieldSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldSource::Frontmatter => "frontmatter",
+            FieldSource::Inline => "inline",
+        }
+    }
+}
+
+/// A note property: a frontmatter key or a Dataview inline field (`key:: value`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub key: String,
+    pub value: String,
+    pub source: FieldSource,
+    /// 1-based line number
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+/// Canonical property key: lowercase, inner whitespace replaced by `-`
+pub fn normalize_field_key(key: &str) -> String {
+    key.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Type of a property value: `number`, `date`, `bool`, `link` or `text`
+pub fn field_value_type(value: &str) -> &'static str {
+    let value = value.trim();
+    if is_date(value) {
+        "date"
+    } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        "number"
+    } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        "bool"
+    } else if value.starts_with("[[") && value.ends_with("]]") {
+        "link"
+    } else {
+        "text"
+    }
+}
+
+/// `YYYY-MM-DD`, optionally followed by a time
+pub(crate) fn is_date(value: &str) -> bool {
+    value.len() >= 10
+        && value.is_char_boundary(10)
+        && chrono::NaiveDate::parse_from_str(&value[..10], "%Y-%m-%d").is_ok()
+        && (value.len() == 10 || value[10..].starts_with(['T', ' ']))
+}
+
+/// Extract Dataview inline fields outside fenced code blocks: full-line
+/// `Key:: value` (also inside list items and quotes) and bracketed
+/// `[key:: value]` / `(key:: value)` anywhere in a line.
+pub fn extract_inline_fields(content: &str) -> Vec<Field> {
+    let mut fields = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for (idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw_line.len();
+
+        let line = raw_line.trim_end();
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let body_start = line.len() - strip_line_prefix(line).len();
+        let body = &line[body_start..];
+
+        if let Some((key, value)) = parse_full_line_field(body) {
+            fields.push(Field {
+                key,
+                value,
+                source: FieldSource::Inline,
+                line: idx + 1,
+                byte_offset: line_offset + body_start,
+            });
+            continue;
+        }
+
+        for (start, key, value) in parse_bracketed_fields(line) {
+            fields.push(Field {
+                key,
+                value,
+                source: FieldSource::Inline,
+                line: idx + 1,
+                byte_offset: line_offset + start,
+            });
+        }
+    }
+
+    fields
+}
+
+/// Strip indentation, quote markers and a list marker
+fn strip_line_prefix(line: &str) -> &str {
+    let mut rest = line.trim_start();
+    while let Some(stripped) = rest.strip_prefix('>') {
+        rest = stripped.trim_start();
+    }
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(stripped) = rest.strip_prefix(marker) {
+            return stripped.trim_start();
+        }
+    }
+    rest
+}
+
+fn parse_full_line_field(body: &str) -> Option<(String, String)> {
+    let sep = body.find("::")?;
+    let key = clean_key(&body[..sep])?;
+    let value = body[sep + 2..].trim();
+    Some((key, value.to_string()))
+}
+
+fn parse_bracketed_fields(line: &str) -> Vec<(usize, String, String)> {
+    let mut fields = Vec::new();
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+        let open = match rest.chars().next() {
+            Some(c @ ('[' | '(')) => c,
+            Some(c) => {
+                i += c.len_utf8();
+                continue;
+            }
+            None => break,
+        };
+
+        let Some(len) = matching_close(rest, open) else {
+            i += 1;
+            continue;
+        };
+
+        // Wikilinks and embeds are never fields
+        if rest.starts_with("[[") {
+            i += len;
+            continue;
+        }
+
+        let inner = &rest[1..len - 1];
+        if let Some(field) = inner
+            .find("::")
+            .and_then(|sep| Some((clean_key(&inner[..sep])?, inner[sep + 2..].trim())))
+        {
+            fields.push((i, field.0, field.1.to_string()));
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+
+    fields
+}
+
+/// Byte length of the bracketed span starting at `text[0]`, including both brackets
+fn matching_close(text: &str, open: char) -> Option<usize> {
+    let close = if open == '[' { ']' } else { ')' };
+    let mut depth = 0;
+
+    for (idx, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(idx + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Validate and normalize a field key; emphasis around the key is allowed
+fn clean_key(raw: &str) -> Option<String> {
+    let key = raw.trim().trim_matches(|c| c == '*' || c == '_').trim();
+    let valid = !key.is_empty()
+        && key.chars().count() <= 64
+        && !key.contains(['[', ']', '(', ')', '`', ':', '#', '|']);
+    valid.then(|| normalize_field_key(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_full_line_fields() {
+        let content = "Rating:: 4\n- **Due Date**:: 2024-03-01\n> Mood:: calm\n```\nx:: 1\n```";
+        let fields = extract_inline_fields(content);
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].key, "rating");
+        assert_eq!(fields[0].value, "4");
+        assert_eq!(fields[0].line, 1);
+        assert_eq!(fields[1].key, "due-date");
+        assert_eq!(fields[1].byte_offset, 13);
+        assert_eq!(fields[2].key, "mood");
+        assert_eq!(fields[2].source, FieldSource::Inline);
+    }
+
+    #[test]
+    fn test_extract_bracketed_fields() {
+        let content = "Read [status:: done] and (author:: [[Cal Newport]]) but not [[Link::x]]";
+        let fields = extract_inline_fields(content);
+
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].key, "status");
+        assert_eq!(fields[0].value, "done");
+        assert_eq!(fields[0].byte_offset, 5);
+        assert_eq!(fields[1].key, "author");
+        assert_eq!(fields[1].value, "[[Cal Newport]]");
+    }
+
+    #[test]
+    fn test_field_value_type() {
+        assert_eq!(field_value_type("4"), "number");
+        assert_eq!(field_value_type("-2.5"), "number");
+        assert_eq!(field_value_type("2024-03-01"), "date");
+        assert_eq!(field_value_type("2024-03-01T10:00"), "date");
+        assert_eq!(field_value_type("True"), "bool");
+        assert_eq!(field_value_type("[[Home]]"), "link");
+        assert_eq!(field_value_type("in progress"), "text");
+        assert_eq!(field_value_type("NaN"), "text");
+    }
+}
@@ -0,0 +1,190 @@
+/// Whether a footnote entry is a `[^label]` reference or a `[^label]: text` definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FootnoteKind {
+    Reference,
+    Definition,
+}
+
+impl FootnoteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FootnoteKind::Reference => "reference",
+            FootnoteKind::Definition => "definition",
+        }
+    }
+}
+
+/// A Markdown footnote reference or definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footnote {
+    /// Label without `[^` and `]`, as written
+    pub label: String,
+    pub kind: FootnoteKind,
+    /// Definition text including indented continuation lines; empty for references
+    pub text: String,
+    /// 1-based line number
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+/// Extract footnote references and definitions outside fenced code blocks and
+/// inline code. A definition starts a line with `[^label]:`; following lines
+/// indented by a tab or four spaces continue it.
+pub fn extract_footnotes(content: &str) -> Vec<Footnote> {
+    let mut footnotes: Vec<Footnote> = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+    let mut open_definition: Option<usize> = None;
+
+    for (idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw_line.len();
+
+        let line = raw_line.trim_end();
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            open_definition = None;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let continues = line.starts_with('\t') || line.starts_with("    ");
+        match open_definition {
+            Some(def) if continues && !trimmed.is_empty() => {
+                let text = &mut footnotes[def].text;
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(trimmed);
+            }
+            // A blank line only ends the definition if the next line is not indented
+            Some(_) if trimmed.is_empty() => {}
+            _ => open_definition = None,
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        let mut search_from = 0;
+        if indent <= 3 {
+            if let Some((label, text_start)) = parse_definition_start(trimmed) {
+                footnotes.push(Footnote {
+                    label: label.to_string(),
+                    kind: FootnoteKind::Definition,
+                    text: trimmed[text_start..].trim().to_string(),
+                    line: idx + 1,
+                    byte_offset: line_offset + indent,
+                });
+                open_definition = Some(footnotes.len() - 1);
+                search_from = indent + text_start;
+            }
+        }
+
+        for (start, label) in find_references(&line[search_from..]) {
+            footnotes.push(Footnote {
+                label: label.to_string(),
+                kind: FootnoteKind::Reference,
+                text: String::new(),
+                line: idx + 1,
+                byte_offset: line_offset + search_from + start,
+            });
+        }
+    }
+
+    footnotes
+}
+
+/// `[^label]:` at the start of `text`: the label and where the definition text starts
+fn parse_definition_start(text: &str) -> Option<(&str, usize)> {
+    let (label, len) = parse_reference(text)?;
+    text[len..].starts_with(':').then_some((label, len + 1))
+}
+
+/// `[^label]` at the start of `text`: the label and the length of the reference
+fn parse_reference(text: &str) -> Option<(&str, usize)> {
+    let rest = text.strip_prefix("[^")?;
+    let close = rest.find(']')?;
+    let label = &rest[..close];
+    if label.is_empty() || label.contains(char::is_whitespace) || label.contains('[') {
+        return None;
+    }
+    Some((label, close + 3))
+}
+
+/// `[^label]` references in a line, skipping inline code; byte offsets are relative to the line
+fn find_references(line: &str) -> Vec<(usize, &str)> {
+    let mut references = Vec::new();
+    let mut in_code = false;
+    let mut idx = 0;
+
+    while idx < line.len() {
+        let rest = &line[idx..];
+        if rest.starts_with('`') {
+            in_code = !in_code;
+        } else if !in_code {
+            if let Some((label, len)) = parse_reference(rest) {
+                references.push((idx, label));
+                idx += len;
+                continue;
+            }
+        }
+        idx += rest.chars().next().map_or(1, char::len_utf8);
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_footnote_references_and_definitions() {
+        let content = "Claim one[^1] and two[^note].\n\n[^1]: First source.\n[^note]: Second\n    continued here\n\nAfter.";
+        let footnotes = extract_footnotes(content);
+
+        let summary: Vec<(&str, FootnoteKind, usize)> = footnotes
+            .iter()
+            .map(|f| (f.label.as_str(), f.kind, f.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("1", FootnoteKind::Reference, 1),
+                ("note", FootnoteKind::Reference, 1),
+                ("1", FootnoteKind::Definition, 3),
+                ("note", FootnoteKind::Definition, 4),
+            ]
+        );
+        assert_eq!(footnotes[0].byte_offset, 9);
+        assert_eq!(footnotes[2].text, "First source.");
+        assert_eq!(footnotes[3].text, "Second\ncontinued here");
+        assert!(content[footnotes[3].byte_offset..].starts_with("[^note]:"));
+    }
+
+    #[test]
+    fn test_extract_footnotes_skips_code() {
+        let content = "Use `[^1]` literally.\n```\n[^2]: in code\n```\nReal[^3]\n[^3]: See [^4]";
+        let footnotes = extract_footnotes(content);
+
+        let labels: Vec<(&str, FootnoteKind)> = footnotes
+            .iter()
+            .map(|f| (f.label.as_str(), f.kind))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("3", FootnoteKind::Reference),
+                ("3", FootnoteKind::Definition),
+                ("4", FootnoteKind::Reference),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_footnotes_ignores_non_labels() {
+        let footnotes = extract_footnotes("Not notes: [^ 1], [^], [x] and ^[inline]");
+        assert!(footnotes.is_empty());
+    }
+}
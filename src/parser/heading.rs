@@ -0,0 +1,113 @@
+/// An ATX heading (`## Text`) with its position in the note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+    /// 1-based line number
+    pub line: usize,
+    pub byte_offset: usize,
+}
+
+/// Extract headings outside fenced code blocks
+pub fn extract_headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some((level, text)) = parse_atx_heading(trimmed) {
+            headings.push(Heading {
+                level,
+                slug: slugify(&text),
+                text,
+                line: idx + 1,
+                byte_offset: line_offset,
+            });
+        }
+    }
+
+    headings
+}
+
+pub(super) fn parse_atx_heading(line: &str) -> Option<(u8, String)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if !rest.starts_with(' ') && !rest.is_empty() {
+        return None;
+    }
+
+    // Optional closing sequence: "## Title ##"
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    if text.is_empty() {
+        return None;
+    }
+
+    Some((level as u8, text.to_string()))
+}
+
+/// GitHub-style anchor slug: lowercase, spaces to `-`, punctuation dropped
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else if c.is_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_headings_positions() {
+        let content = "# Title\n\nText\n\n## Sub Section ##\n```\n# not a heading\n```\n### Deep";
+        let headings = extract_headings(content);
+
+        assert_eq!(headings.len(), 3);
+        assert_eq!(headings[0].level, 1);
+        assert_eq!(headings[0].line, 1);
+        assert_eq!(headings[1].text, "Sub Section");
+        assert_eq!(headings[1].slug, "sub-section");
+        assert_eq!(headings[1].line, 5);
+        assert_eq!(headings[1].byte_offset, 15);
+        assert_eq!(headings[2].level, 3);
+    }
+
+    #[test]
+    fn test_extract_headings_ignores_tags_and_empty() {
+        let headings = extract_headings("#tag at start\n#\n####### seven");
+        assert!(headings.is_empty());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Key Principles"), "key-principles");
+        assert_eq!(slugify("What's new? (2024)"), "whats-new-2024");
+        assert_eq!(slugify("  snake_case-name "), "snake_case-name");
+    }
+}
@@ -14,6 +14,11 @@ pub fn extract_markdown_links(content: &str) -> Vec<Link> {
             }
 
             if let Some((label_end, label)) = parse_bracket_section(&chars, i, '[', ']') {
+                // `[^1]` is a footnote reference, even when followed by parentheses
+                if label.starts_with('^') {
+                    i = label_end + 1;
+                    continue;
+                }
                 let next = label_end + 1;
                 if next < chars.len() && chars[next] == '(' {
                     if let Some((dest_end, dest_raw)) =
@@ -51,7 +56,8 @@ pub fn build_markdown_link(label: &str, dest: &str, is_embed: bool) -> Option<Li
     }
 
     let (path, heading_ref, block_ref) = split_heading_block(dest);
-    let text = normalize_note_identifier(&path);
+    let text = normalize_note_identifier(&percent_decode(&path));
+    let heading_ref = heading_ref.map(|heading| percent_decode(&heading));
     if text.is_empty() {
         return None;
     }
@@ -94,6 +100,28 @@ fn parse_bracket_section(
     None
 }
 
+/// Decode `%XX` escapes (e.g. `Deep%20Work.md`), leaving malformed sequences as-is
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_string())
+}
+
 fn clean_markdown_link_destination(dest: &str) -> String {
     let trimmed = dest.trim();
     let trimmed = trimmed.trim_start_matches('<').trim_end_matches('>').trim();
@@ -0,0 +1,75 @@
+/// Characters Obsidian accepts in a tag besides letters and digits
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Validate a tag the way Obsidian does and return its display spelling:
+/// letters, digits, `_`, `-` and `/` only, at least one non-digit character,
+/// and no empty `/` segments. A leading `#` is removed.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#');
+    if tag.is_empty() || !tag.chars().all(is_tag_char) {
+        return None;
+    }
+    if tag.split('/').any(str::is_empty) {
+        return None;
+    }
+    if tag.chars().all(|c| c.is_numeric() || c == '/') {
+        return None;
+    }
+    Some(tag.to_string())
+}
+
+/// Case-folded form used to compare and store tags; `#Project` and `#project` are one tag
+pub fn tag_key(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+/// Inline `#tags` in the text. A tag runs from `#` up to the first character
+/// Obsidian does not allow in tags, so `#todo.` yields `todo`; numeric-only
+/// tags such as `#2024` are skipped.
+pub fn extract_inline_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+
+    for word in content.split_whitespace() {
+        let Some(rest) = word.strip_prefix('#') else {
+            continue;
+        };
+        let end = rest.find(|c: char| !is_tag_char(c)).unwrap_or(rest.len());
+        if let Some(tag) = normalize_tag(rest[..end].trim_end_matches('/')) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(
+            normalize_tag("#Project/Alpha"),
+            Some("Project/Alpha".to_string())
+        );
+        assert_eq!(
+            normalize_tag("meeting-notes"),
+            Some("meeting-notes".to_string())
+        );
+        assert_eq!(normalize_tag("y2024"), Some("y2024".to_string()));
+        assert_eq!(normalize_tag("2024"), None);
+        assert_eq!(normalize_tag("2024/01"), None);
+        assert_eq!(normalize_tag("two words"), None);
+        assert_eq!(normalize_tag("a//b"), None);
+        assert_eq!(normalize_tag("#"), None);
+    }
+
+    #[test]
+    fn test_extract_inline_tags() {
+        let tags = extract_inline_tags("Done #todo. #2024 #Work/Q1, #café # heading ##");
+        assert_eq!(tags, vec!["todo", "Work/Q1", "café"]);
+        assert_eq!(tag_key("#Work/Q1"), "work/q1");
+    }
+}
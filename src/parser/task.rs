@@ -0,0 +1,224 @@
+use super::heading::parse_atx_heading;
+use super::tag::{extract_inline_tags, tag_key};
+
+const DUE_MARKER: &str = "📅";
+const SCHEDULED_MARKER: &str = "⏳";
+const DONE_MARKER: &str = "✅";
+/// Start and created dates are stripped from the text but not stored
+const OTHER_DATE_MARKERS: [&str; 2] = ["🛫", "➕"];
+const PRIORITY_MARKERS: [(&str, &str); 5] = [
+    ("🔺", "highest"),
+    ("⏫", "high"),
+    ("🔼", "medium"),
+    ("🔽", "low"),
+    ("⏬", "lowest"),
+];
+
+/// A checkbox list item (`- [ ] text`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// Character between the brackets: ' ', 'x', '/', '-', ...
+    pub status: char,
+    /// Description without date and priority markers
+    pub text: String,
+    /// 1-based line number
+    pub line: usize,
+    pub byte_offset: usize,
+    /// Text of the closest heading above the task
+    pub heading: Option<String>,
+    pub due: Option<String>,
+    pub scheduled: Option<String>,
+    pub done: Option<String>,
+    pub priority: Option<String>,
+    /// Inline tags in the text, case-folded like note tags
+    pub tags: Vec<String>,
+}
+
+impl Task {
+    /// Done (`x`) and cancelled (`-`) tasks are closed, any other status is open
+    pub fn is_open(&self) -> bool {
+        !matches!(self.status, 'x' | 'X' | '-')
+    }
+}
+
+/// Extract tasks outside fenced code blocks
+pub fn extract_tasks(content: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut in_fence = false;
+    let mut heading: Option<String> = None;
+    let mut offset = 0;
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some((_, text)) = parse_atx_heading(trimmed) {
+            heading = Some(text);
+            continue;
+        }
+
+        if let Some((status, body)) = parse_checkbox(trimmed) {
+            tasks.push(parse_task(
+                status,
+                body,
+                idx + 1,
+                line_offset,
+                heading.clone(),
+            ));
+        }
+    }
+
+    tasks
+}
+
+/// Split `- [s] body` (also `*`, `+` and ordered list markers) into status and body
+fn parse_checkbox(line: &str) -> Option<(char, &str)> {
+    let rest = if let Some(rest) = line.strip_prefix(['-', '*', '+']) {
+        rest
+    } else {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        line[digits..].strip_prefix(['.', ')'])?
+    };
+
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let rest = rest.strip_prefix('[')?;
+    let status = rest.chars().next()?;
+    let rest = rest[status.len_utf8()..].strip_prefix(']')?;
+
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((status, rest.trim()))
+}
+
+fn parse_task(
+    status: char,
+    body: &str,
+    line: usize,
+    byte_offset: usize,
+    heading: Option<String>,
+) -> Task {
+    let mut text = body.to_string();
+
+    let due = take_date(&mut text, DUE_MARKER);
+    let scheduled = take_date(&mut text, SCHEDULED_MARKER);
+    let done = take_date(&mut text, DONE_MARKER);
+    for marker in OTHER_DATE_MARKERS {
+        take_date(&mut text, marker);
+    }
+
+    let mut priority = None;
+    for (marker, name) in PRIORITY_MARKERS {
+        if text.contains(marker) {
+            text = text.replace(marker, "");
+            priority.get_or_insert_with(|| name.to_string());
+        }
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tags = extract_task_tags(&text);
+
+    Task {
+        status,
+        text,
+        line,
+        byte_offset,
+        heading,
+        due,
+        scheduled,
+        done,
+        priority,
+        tags,
+    }
+}
+
+/// Remove `<marker> YYYY-MM-DD` from the text, returning the date
+fn take_date(text: &mut String, marker: &str) -> Option<String> {
+    let start = text.find(marker)?;
+    let after = &text[start + marker.len()..];
+    let trimmed = after.trim_start();
+    let date: String = trimmed.chars().take(10).collect();
+
+    if !is_iso_date(&date) {
+        text.replace_range(start..start + marker.len(), "");
+        return None;
+    }
+
+    let end = start + marker.len() + (after.len() - trimmed.len()) + date.len();
+    text.replace_range(start..end, "");
+    Some(date)
+}
+
+fn is_iso_date(value: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+fn extract_task_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in extract_inline_tags(text) {
+        let key = tag_key(&tag);
+        if !tags.contains(&key) {
+            tags.push(key);
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_tasks_statuses_and_headings() {
+        let content =
+            "# Plan\n- [ ] open\n- [x] done\n## Later\n* [/] in progress\n1. [-] cancelled\n- [] no";
+        let tasks = extract_tasks(content);
+
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[0].status, ' ');
+        assert_eq!(tasks[0].heading.as_deref(), Some("Plan"));
+        assert_eq!(tasks[0].line, 2);
+        assert_eq!(tasks[0].byte_offset, 7);
+        assert!(!tasks[1].is_open());
+        assert_eq!(tasks[2].status, '/');
+        assert!(tasks[2].is_open());
+        assert_eq!(tasks[2].heading.as_deref(), Some("Later"));
+        assert!(!tasks[3].is_open());
+    }
+
+    #[test]
+    fn test_extract_tasks_dates_priority_and_tags() {
+        let content =
+            "  - [x] Ship release #work #v1.0 #Work #123 ⏫ 📅 2024-03-01 ⏳2024-02-20 ✅ 2024-02-28";
+        let task = &extract_tasks(content)[0];
+
+        assert_eq!(task.text, "Ship release #work #v1.0 #Work #123");
+        assert_eq!(task.due.as_deref(), Some("2024-03-01"));
+        assert_eq!(task.scheduled.as_deref(), Some("2024-02-20"));
+        assert_eq!(task.done.as_deref(), Some("2024-02-28"));
+        assert_eq!(task.priority.as_deref(), Some("high"));
+        assert_eq!(task.tags, vec!["work", "v1"]);
+    }
+
+    #[test]
+    fn test_extract_tasks_skips_code_and_invalid_dates() {
+        let content = "```\n- [ ] in code\n```\n- [ ] Call 📅 tomorrow";
+        let tasks = extract_tasks(content);
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].text, "Call tomorrow");
+        assert_eq!(tasks[0].due, None);
+    }
+}
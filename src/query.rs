@@ -1,16 +1,57 @@
 // Query module for retrieving and searching vault data
+mod blocks;
+mod callouts;
+mod dql;
+mod footnotes;
+mod fuzzy;
+mod grep;
+mod headings;
+mod hybrid;
 mod links;
+mod mentions;
+mod properties;
+mod resolver;
 mod search;
+mod semantic;
+mod tag_analysis;
 mod tags;
+mod tasks;
 
+pub use blocks::{search_blocks, BlockResult};
+pub use callouts::{search_callouts, CalloutResult};
+pub use dql::{run_dql, run_dql_at, DqlError, DqlKind, DqlResult, DqlRow, DqlValue};
+pub use footnotes::{diagnose_footnotes, FootnoteIssue};
+pub use grep::{grep_notes, GrepError, GrepLine, GrepMatch, GrepOptions};
+pub use headings::{get_headings, HeadingResult};
+pub use hybrid::{hybrid_search, HybridResult, HybridWeights, RankContribution};
 pub use links::{
     diagnose_broken_links, get_backlinks, get_dead_ends, get_forward_links, get_orphans,
     get_unresolved_links, BrokenLinkResult, DiagnoseResult, LinkResult,
 };
-pub use search::{search_chunks, SearchResult};
+pub use mentions::{find_unlinked_mentions, MentionResult};
+pub use properties::{
+    find_notes_by_properties, get_fields, list_property_keys, FieldResult, PropertyFilter,
+    PropertyKeyResult, PropertyMatch, PropertyOp,
+};
+pub use resolver::{
+    resolve_note, AmbiguousNoteError, MatchKind, NoteCandidate, NoteResolution, NoteResolver,
+};
+pub(crate) use search::highlight_ranges;
+pub use search::{
+    corrected_query, search_chunks, search_notes, suggest_terms, NoteBoostScores, NoteBoosts,
+    NoteSearchResult, SearchError, SearchOptions, SearchResult, TermSuggestion,
+    DEFAULT_SNIPPET_TOKENS, SUGGESTIONS_PER_TERM,
+};
+pub use semantic::{semantic_search, SemanticResult};
+pub use tag_analysis::{
+    analyze_tags, NoteTagSuggestions, SuggestedTag, TagAnalysis, TagAnalysisOptions,
+    TagImplication, TagPair,
+};
 pub use tags::{
-    get_notes_by_tag, get_notes_by_tags_and, get_notes_by_tags_or, list_tags, TagResult,
+    find_similar_tags, get_notes_by_tag, get_notes_by_tags_and, get_notes_by_tags_or, list_tags,
+    tag_tree, SimilarTagGroup, TagNode, TagResult, TagVariant,
 };
+pub use tasks::{search_tasks, TaskFilter, TaskResult};
 
 use rusqlite::{Connection, OptionalExtension};
 use serde::Serialize;
@@ -28,60 +69,46 @@ pub struct NoteDescribeResult {
     pub frontmatter: Option<String>,
 }
 
-/// Get note metadata by path or title
+fn map_describe_row(row: &rusqlite::Row) -> rusqlite::Result<NoteDescribeResult> {
+    Ok(NoteDescribeResult {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        title: row.get(2)?,
+        mtime: row.get(3)?,
+        hash: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        frontmatter: row.get(7)?,
+    })
+}
+
+/// Get note metadata by any identifier the note resolver accepts; `None`
+/// when it matches no note or several
 pub fn get_note_by_filename(
     conn: &Connection,
     filename: &str,
 ) -> rusqlite::Result<Option<NoteDescribeResult>> {
-    // First try to find by exact path match
-    let result = conn
-        .query_row(
-            "SELECT id, path, title, mtime, hash, created_at, updated_at, frontmatter_json
-             FROM notes
-             WHERE path = ?1 OR title = ?1",
-            [filename],
-            |row| {
-                Ok(NoteDescribeResult {
-                    id: row.get(0)?,
-                    path: row.get(1)?,
-                    title: row.get(2)?,
-                    mtime: row.get(3)?,
-                    hash: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    frontmatter: row.get(7)?,
-                })
-            },
-        )
-        .optional();
-
-    // If not found by exact match, try partial match on path or title
-    if let Ok(None) = result {
-        conn.query_row(
-            "SELECT id, path, title, mtime, hash, created_at, updated_at, frontmatter_json
-             FROM notes
-             WHERE path LIKE ?1 OR title LIKE ?1
-             LIMIT 1",
-            [format!("%{filename}%")],
-            |row| {
-                Ok(NoteDescribeResult {
-                    id: row.get(0)?,
-                    path: row.get(1)?,
-                    title: row.get(2)?,
-                    mtime: row.get(3)?,
-                    hash: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    frontmatter: row.get(7)?,
-                })
-            },
-        )
-        .optional()
-    } else {
-        result
+    match resolve_note(conn, filename)? {
+        NoteResolution::Found { note, .. } => get_note_by_id(conn, note.id),
+        NoteResolution::Ambiguous(_) | NoteResolution::NotFound => Ok(None),
     }
 }
 
+/// Get note metadata by note id
+pub fn get_note_by_id(
+    conn: &Connection,
+    note_id: i64,
+) -> rusqlite::Result<Option<NoteDescribeResult>> {
+    conn.query_row(
+        "SELECT id, path, title, mtime, hash, created_at, updated_at, frontmatter_json
+         FROM notes
+         WHERE id = ?1",
+        [note_id],
+        map_describe_row,
+    )
+    .optional()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +156,11 @@ mod tests {
             "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, hash TEXT, created_at TEXT, updated_at TEXT, frontmatter_json TEXT)",
             [],
         ).unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
 
         // Insert test data using params
         conn.execute(
@@ -151,6 +183,11 @@ mod tests {
             "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, hash TEXT, created_at TEXT, updated_at TEXT, frontmatter_json TEXT)",
             [],
         ).unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
 
         // Insert test data
         conn.execute(
@@ -173,6 +210,11 @@ mod tests {
             "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, hash TEXT, created_at TEXT, updated_at TEXT, frontmatter_json TEXT)",
             [],
         ).unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
 
         // Insert test data
         conn.execute(
@@ -194,6 +236,11 @@ mod tests {
             "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, hash TEXT, created_at TEXT, updated_at TEXT, frontmatter_json TEXT)",
             [],
         ).unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
 
         // Insert test data
         conn.execute(
@@ -205,4 +252,34 @@ mod tests {
         let result = get_note_by_filename(&conn, "nonexistent.md").unwrap();
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_get_note_by_filename_alias() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Create tables
+        conn.execute(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, hash TEXT, created_at TEXT, updated_at TEXT, frontmatter_json TEXT)",
+            [],
+        ).unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
+
+        // Insert test data
+        conn.execute(
+            "INSERT INTO notes (id, path, title, mtime, hash, created_at, updated_at) VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params!["dev/JavaScript.md", "JavaScript", 1234567890_i64, "hash123", "2024-01-01", "2024-01-02"],
+        ).unwrap();
+        conn.execute("INSERT INTO aliases (note_id, alias) VALUES (1, 'JS')", [])
+            .unwrap();
+
+        // Test alias and path-without-extension match
+        let result = get_note_by_filename(&conn, "JS").unwrap();
+        assert_eq!(result.unwrap().path, "dev/JavaScript.md");
+        let result = get_note_by_filename(&conn, "dev/JavaScript").unwrap();
+        assert_eq!(result.unwrap().title, "JavaScript");
+    }
 }
@@ -0,0 +1,135 @@
+use rusqlite::{Connection, Result};
+
+use super::fuzzy::levenshtein;
+
+/// Maximum number of block ids suggested for a broken block reference
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A `^block-id` anchor with the paragraph it marks
+#[derive(Debug, Clone)]
+pub struct BlockResult {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub block_id: String,
+    pub text: String,
+    pub line: i64,
+    pub byte_offset: i64,
+}
+
+/// Find blocks by id, optionally limited to the note with id `note_id`. A
+/// leading `^` is ignored and ids are matched case-insensitively.
+pub fn search_blocks(
+    conn: &Connection,
+    block_id: &str,
+    note_id: Option<i64>,
+) -> Result<Vec<BlockResult>> {
+    let block_id = block_id.trim().trim_start_matches('^');
+
+    let mut stmt = conn.prepare(
+        "SELECT n.id, n.path, n.title, b.block_id, b.text, b.line, b.byte_offset
+         FROM blocks b
+         JOIN notes n ON b.note_id = n.id
+         WHERE b.block_id = ?1 COLLATE NOCASE
+         AND (?2 IS NULL OR n.id = ?2)
+         ORDER BY n.path, b.byte_offset",
+    )?;
+
+    let results = stmt.query_map(rusqlite::params![block_id, note_id], |row| {
+        Ok(BlockResult {
+            note_id: row.get(0)?,
+            note_path: row.get(1)?,
+            note_title: row.get(2)?,
+            block_id: row.get(3)?,
+            text: row.get(4)?,
+            line: row.get(5)?,
+            byte_offset: row.get(6)?,
+        })
+    })?;
+
+    results.collect()
+}
+
+/// Block ids defined in a note, in document order
+pub(crate) fn get_block_ids_by_note_id(conn: &Connection, note_id: i64) -> Result<Vec<String>> {
+    let mut stmt =
+        conn.prepare("SELECT block_id FROM blocks WHERE note_id = ?1 ORDER BY byte_offset")?;
+    let results = stmt.query_map([note_id], |row| row.get(0))?;
+    results.collect()
+}
+
+/// Whether a block reference points at one of the block ids
+pub(crate) fn block_ref_matches(block_ids: &[String], block_ref: &str) -> bool {
+    let block_ref = block_ref.trim().trim_start_matches('^');
+    block_ids
+        .iter()
+        .any(|id| id.eq_ignore_ascii_case(block_ref))
+}
+
+/// Closest block ids to a block reference, nearest first
+pub(crate) fn suggest_block_ids(block_ids: &[String], block_ref: &str) -> Vec<String> {
+    let block_ref = block_ref.trim().trim_start_matches('^').to_lowercase();
+
+    let mut scored: Vec<(usize, &String)> = block_ids
+        .iter()
+        .map(|id| (levenshtein(&block_ref, &id.to_lowercase()), id))
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance);
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, id)| id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE blocks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, block_id TEXT, text TEXT,
+                line INTEGER, byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES (1, 'Deep Work.md', 'Deep Work');
+             INSERT INTO notes VALUES (2, 'Ideas.md', 'Ideas');
+             INSERT INTO blocks VALUES (1, 1, 'rule1', 'Work deeply.', 5, 40);
+             INSERT INTO blocks VALUES (2, 2, 'Rule1', 'Another rule.', 3, 10);
+             INSERT INTO blocks VALUES (3, 2, 'idea-7', 'An idea.', 9, 60);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_search_blocks() {
+        let conn = setup_db();
+
+        let results = search_blocks(&conn, "^rule1", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].note_path, "Deep Work.md");
+        assert_eq!(results[0].text, "Work deeply.");
+
+        let results = search_blocks(&conn, "rule1", Some(2)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].block_id, "Rule1");
+
+        assert!(search_blocks(&conn, "rule1", Some(3)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_block_ref_matches_and_suggestions() {
+        let conn = setup_db();
+        let ids = get_block_ids_by_note_id(&conn, 2).unwrap();
+        assert_eq!(ids, vec!["Rule1", "idea-7"]);
+
+        assert!(block_ref_matches(&ids, "rule1"));
+        assert!(!block_ref_matches(&ids, "idea-8"));
+        assert_eq!(suggest_block_ids(&ids, "idea-8")[0], "idea-7");
+    }
+}
@@ -0,0 +1,137 @@
+use crate::parser::callout_type_names;
+use rusqlite::{Connection, Result};
+
+#[derive(Debug, Clone)]
+pub struct CalloutResult {
+    pub id: i64,
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub kind: String,
+    pub title: Option<String>,
+    pub body: String,
+    pub fold: Option<String>,
+    pub depth: i64,
+    pub parent_id: Option<i64>,
+    pub line: i64,
+}
+
+/// Search callouts by type and text. `kind` also matches the type's aliases
+/// (`warning` finds `caution` and `attention`); `text` is matched
+/// case-insensitively against the title and body.
+pub fn search_callouts(
+    conn: &Connection,
+    kind: Option<&str>,
+    text: Option<&str>,
+    limit: usize,
+) -> Result<Vec<CalloutResult>> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+
+    if let Some(kind) = kind {
+        let mut placeholders = Vec::new();
+        for name in callout_type_names(kind) {
+            params.push(name);
+            placeholders.push(format!("?{}", params.len()));
+        }
+        conditions.push(format!("c.type IN ({})", placeholders.join(", ")));
+    }
+    if let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) {
+        params.push(text.to_lowercase());
+        conditions.push(format!(
+            "instr(lower(COALESCE(c.title, '') || char(10) || c.body), ?{}) > 0",
+            params.len()
+        ));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let query = format!(
+        "SELECT c.id, n.id, n.path, n.title, c.type, c.title, c.body, c.fold,
+                c.depth, c.parent_id, c.line
+         FROM callouts c
+         JOIN notes n ON c.note_id = n.id
+         {where_clause}
+         ORDER BY n.path, c.line
+         LIMIT {limit}"
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+        Ok(CalloutResult {
+            id: row.get(0)?,
+            note_id: row.get(1)?,
+            note_path: row.get(2)?,
+            note_title: row.get(3)?,
+            kind: row.get(4)?,
+            title: row.get(5)?,
+            body: row.get(6)?,
+            fold: row.get(7)?,
+            depth: row.get(8)?,
+            parent_id: row.get(9)?,
+            line: row.get(10)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE callouts (
+                id INTEGER PRIMARY KEY, note_id INTEGER, parent_id INTEGER, type TEXT,
+                title TEXT, body TEXT, fold TEXT, depth INTEGER, line INTEGER,
+                byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES (1, 'adr/0001.md', 'ADR 1');
+             INSERT INTO notes VALUES (2, 'Home.md', 'Home');
+             INSERT INTO callouts VALUES
+                (1, 1, NULL, 'decision', 'Use SQLite', 'Embedded and simple', NULL, 1, 3, 20),
+                (2, 1, 1, 'caution', NULL, 'Watch the WAL size', '-', 2, 5, 60),
+                (3, 2, NULL, 'warning', 'Backups', 'Run them weekly', 'collapsed', 1, 2, 8),
+                (4, 2, NULL, 'todo', NULL, 'Review sqlite settings', NULL, 1, 9, 90);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(results: &[CalloutResult]) -> Vec<i64> {
+        results.iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn test_search_callouts_by_type_includes_aliases() {
+        let conn = setup_db();
+
+        let warnings = search_callouts(&conn, Some("Warning"), None, 50).unwrap();
+        assert_eq!(ids(&warnings), vec![3, 2]);
+        assert_eq!(warnings[1].parent_id, Some(1));
+
+        let decisions = search_callouts(&conn, Some("decision"), None, 50).unwrap();
+        assert_eq!(ids(&decisions), vec![1]);
+        assert_eq!(decisions[0].title.as_deref(), Some("Use SQLite"));
+    }
+
+    #[test]
+    fn test_search_callouts_text_and_limit() {
+        let conn = setup_db();
+
+        let sqlite = search_callouts(&conn, None, Some("SQLITE"), 50).unwrap();
+        assert_eq!(ids(&sqlite), vec![4, 1]);
+
+        let todo = search_callouts(&conn, Some("todo"), Some("sqlite"), 50).unwrap();
+        assert_eq!(ids(&todo), vec![4]);
+
+        assert_eq!(search_callouts(&conn, None, None, 2).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,423 @@
+// Read-only subset of the Dataview Query Language
+mod eval;
+mod lexer;
+mod parser;
+mod value;
+
+pub use value::Value as DqlValue;
+
+use chrono::NaiveDateTime;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use super::{AmbiguousNoteError, NoteResolver};
+use eval::{eval, Row};
+use parser::{parse_query, Command, QueryType, Source};
+use value::Value;
+
+#[derive(Debug, Error)]
+pub enum DqlError {
+    /// The query does not parse; `column` is a 1-based character position
+    #[error("Invalid DQL query at column {column}: {message}\n  {query}\n  {}^", " ".repeat(.column - 1))]
+    Syntax {
+        message: String,
+        query: String,
+        column: usize,
+    },
+
+    /// A `FROM [[note]]` source matches several notes
+    #[error("{0}")]
+    AmbiguousNote(#[from] AmbiguousNoteError),
+
+    /// A `FROM [[note]]` source matches no note
+    #[error("Note not found: {0}")]
+    NoteNotFound(String),
+
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+}
+
+impl DqlError {
+    pub(crate) fn syntax(query: &str, byte_position: usize, message: &str) -> Self {
+        DqlError::Syntax {
+            message: message.to_string(),
+            query: query.replace(['\n', '\r', '\t'], " "),
+            column: query[..byte_position].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DqlKind {
+    List,
+    Table,
+}
+
+#[derive(Debug, Clone)]
+pub struct DqlRow {
+    pub note_path: String,
+    pub note_title: String,
+    /// One value per column
+    pub values: Vec<DqlValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DqlResult {
+    pub kind: DqlKind,
+    /// `WITHOUT ID`: the note itself is not shown next to the values
+    pub without_id: bool,
+    /// Column headers, excluding the note column
+    pub columns: Vec<String>,
+    pub rows: Vec<DqlRow>,
+}
+
+/// Run a `LIST` or `TABLE` query against the index. `date(today)` and
+/// `date(now)` use the local clock.
+pub fn run_dql(conn: &Connection, query: &str) -> Result<DqlResult, DqlError> {
+    run_dql_at(conn, query, chrono::Local::now().naive_local())
+}
+
+/// Run a query with a fixed current time
+pub fn run_dql_at(
+    conn: &Connection,
+    query: &str,
+    now: NaiveDateTime,
+) -> Result<DqlResult, DqlError> {
+    let query = parse_query(query)?;
+
+    let mut link_ids = HashMap::new();
+    if let Some(source) = &query.source {
+        let resolver = NoteResolver::load(conn)?;
+        resolve_source_links(&resolver, source, &mut link_ids)?;
+    }
+
+    let mut rows: Vec<Row> = load_rows(conn)?
+        .into_iter()
+        .filter(|row| {
+            query
+                .source
+                .as_ref()
+                .map_or(true, |source| row.matches_source(source, &link_ids))
+        })
+        .collect();
+
+    for command in &query.commands {
+        match command {
+            Command::Where(expr) => rows.retain(|row| eval(expr, row, now).is_truthy()),
+            Command::Sort(keys) => {
+                let mut keyed: Vec<(Vec<Value>, Row)> = rows
+                    .into_iter()
+                    .map(|row| {
+                        let values = keys.iter().map(|(expr, _)| eval(expr, &row, now));
+                        (values.collect(), row)
+                    })
+                    .collect();
+                keyed.sort_by(|(a, _), (b, _)| {
+                    a.iter()
+                        .zip(b)
+                        .zip(keys)
+                        .map(|((x, y), (_, descending))| {
+                            let order = x.sort_cmp(y);
+                            if *descending {
+                                order.reverse()
+                            } else {
+                                order
+                            }
+                        })
+                        .find(|order| order.is_ne())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                rows = keyed.into_iter().map(|(_, row)| row).collect();
+            }
+            Command::Limit(limit) => rows.truncate(*limit),
+        }
+    }
+
+    let (kind, columns) = match query.query_type {
+        QueryType::List(column) => (DqlKind::List, column.into_iter().collect()),
+        QueryType::Table(columns) => (DqlKind::Table, columns),
+    };
+
+    let rows = rows
+        .iter()
+        .map(|row| DqlRow {
+            note_path: row.path.clone(),
+            note_title: row.title.clone(),
+            values: columns
+                .iter()
+                .map(|column| eval(&column.expr, row, now))
+                .collect(),
+        })
+        .collect();
+
+    Ok(DqlResult {
+        kind,
+        without_id: query.without_id,
+        columns: columns.into_iter().map(|column| column.name).collect(),
+        rows,
+    })
+}
+
+/// Resolve the notes named by `FROM [[note]]` sources like links, without
+/// fuzzy matching
+fn resolve_source_links(
+    resolver: &NoteResolver,
+    source: &Source,
+    link_ids: &mut HashMap<String, i64>,
+) -> Result<(), DqlError> {
+    match source {
+        Source::Link(note) => match resolver.resolve_exact(note).into_result(note)? {
+            Some(found) => {
+                link_ids.insert(note.clone(), found.id);
+            }
+            None => return Err(DqlError::NoteNotFound(note.clone())),
+        },
+        Source::Not(inner) => resolve_source_links(resolver, inner, link_ids)?,
+        Source::And(a, b) | Source::Or(a, b) => {
+            resolve_source_links(resolver, a, link_ids)?;
+            resolve_source_links(resolver, b, link_ids)?;
+        }
+        Source::Tag(_) | Source::Folder(_) => {}
+    }
+    Ok(())
+}
+
+/// Load every note with its metadata, ordered by path
+fn load_rows(conn: &Connection) -> rusqlite::Result<Vec<Row>> {
+    let mut stmt = conn.prepare("SELECT id, path, title, mtime, size FROM notes ORDER BY path")?;
+    let notes = stmt.query_map([], |row| {
+        Ok(Row {
+            note_id: row.get(0)?,
+            path: row.get(1)?,
+            title: row.get(2)?,
+            mtime: row.get(3)?,
+            size: row.get(4)?,
+            ..Default::default()
+        })
+    })?;
+    let mut rows = notes.collect::<rusqlite::Result<Vec<_>>>()?;
+    let index: HashMap<i64, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| (row.note_id, idx))
+        .collect();
+
+    let mut stmt = conn.prepare("SELECT note_id, display FROM tags ORDER BY tag")?;
+    for entry in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (note_id, tag) = entry?;
+        if let Some(&idx) = index.get(&note_id) {
+            rows[idx].tags.push(tag);
+        }
+    }
+
+    let mut stmt = conn.prepare("SELECT note_id, alias FROM aliases ORDER BY id")?;
+    for entry in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (note_id, alias) = entry?;
+        if let Some(&idx) = index.get(&note_id) {
+            rows[idx].aliases.push(alias);
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT l.src_note_id, l.dst_note_id, l.dst_text, d.path
+         FROM links l
+         LEFT JOIN notes d ON l.dst_note_id = d.id
+         ORDER BY l.id",
+    )?;
+    let links = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<i64>>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+    for link in links {
+        let (src_id, dst_id, dst_text, dst_path) = link?;
+        let Some(&src_idx) = index.get(&src_id) else {
+            continue;
+        };
+        let target = match &dst_path {
+            Some(path) => path.strip_suffix(".md").unwrap_or(path).to_string(),
+            None => dst_text,
+        };
+        if !rows[src_idx].outlinks.contains(&target) {
+            rows[src_idx].outlinks.push(target);
+        }
+
+        if let Some(dst_idx) = dst_id.and_then(|id| index.get(&id).copied()) {
+            let dst_id = rows[dst_idx].note_id;
+            if !rows[src_idx].outlink_ids.contains(&dst_id) {
+                rows[src_idx].outlink_ids.push(dst_id);
+            }
+            let source = rows[src_idx].path.clone();
+            let source = source.strip_suffix(".md").unwrap_or(&source).to_string();
+            if !rows[dst_idx].inlinks.contains(&source) {
+                rows[dst_idx].inlinks.push(source);
+            }
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT note_id, key, value, value_type FROM fields ORDER BY note_id, byte_offset",
+    )?;
+    let fields = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    for field in fields {
+        let (note_id, key, value, value_type) = field?;
+        let Some(&idx) = index.get(&note_id) else {
+            continue;
+        };
+        let value = Value::from_field(&value, &value_type);
+        match rows[idx].fields.entry(key) {
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                let existing = entry.get_mut();
+                match existing {
+                    Value::List(items) => items.push(value),
+                    _ => *existing = Value::List(vec![existing.clone(), value]),
+                }
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (
+                id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER, size INTEGER
+             );
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE links (
+                id INTEGER PRIMARY KEY, src_note_id INTEGER, dst_note_id INTEGER, dst_text TEXT
+             );
+             CREATE TABLE fields (
+                id INTEGER PRIMARY KEY, note_id INTEGER, key TEXT, value TEXT,
+                value_type TEXT, byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES
+                (1, 'Books/Deep Work.md', 'Deep Work', 1704067200, 3000),
+                (2, 'Books/Atomic Habits.md', 'Atomic Habits', 1706745600, 1200),
+                (3, 'Home.md', 'Home', 1709251200, 500);
+             INSERT INTO tags VALUES
+                (1, 1, 'books', 'books'), (2, 2, 'books/habits', 'Books/Habits'), (3, 3, 'hub', 'hub');
+             INSERT INTO links VALUES
+                (1, 3, 1, 'Deep Work'), (2, 3, 2, 'Atomic Habits'), (3, 1, NULL, 'Flow');
+             INSERT INTO fields VALUES
+                (1, 1, 'rating', '5', 'number', 10),
+                (2, 2, 'rating', '3', 'number', 10),
+                (3, 1, 'status', 'done', 'text', 20),
+                (4, 2, 'status', 'reading', 'text', 20),
+                (5, 1, 'genre', 'focus', 'text', 30),
+                (6, 1, 'genre', 'work', 'text', 40);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn now() -> NaiveDateTime {
+        value::parse_date("2024-03-10T12:00").unwrap()
+    }
+
+    fn paths(result: &DqlResult) -> Vec<&str> {
+        result.rows.iter().map(|r| r.note_path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_run_table_query() {
+        let conn = setup_db();
+        let result = run_dql_at(
+            &conn,
+            r#"TABLE rating, status AS "State" FROM #books WHERE rating >= 3 SORT rating DESC"#,
+            now(),
+        )
+        .unwrap();
+
+        assert_eq!(result.kind, DqlKind::Table);
+        assert_eq!(result.columns, vec!["rating", "State"]);
+        assert_eq!(
+            paths(&result),
+            vec!["Books/Deep Work.md", "Books/Atomic Habits.md"]
+        );
+        assert_eq!(
+            result.rows[0].values[1],
+            DqlValue::String("done".to_string())
+        );
+    }
+
+    #[test]
+    fn test_run_list_query_sources_and_limit() {
+        let conn = setup_db();
+        let run = |query: &str| run_dql_at(&conn, query, now()).unwrap();
+
+        assert_eq!(
+            paths(&run(r#"LIST FROM "Books" LIMIT 1"#)),
+            vec!["Books/Atomic Habits.md"]
+        );
+        assert_eq!(paths(&run("LIST FROM [[Deep Work]]")), vec!["Home.md"]);
+        assert_eq!(paths(&run("LIST FROM -#books")), vec!["Home.md"]);
+        assert_eq!(
+            paths(&run("LIST WHERE contains(genre, \"work\")")),
+            vec!["Books/Deep Work.md"]
+        );
+        assert_eq!(
+            paths(&run("LIST WHERE file.mtime >= date(today) - dur(40 days)")),
+            vec!["Books/Atomic Habits.md", "Home.md"]
+        );
+        assert_eq!(
+            paths(&run(
+                "LIST WHERE contains(file.outlinks, [[Flow]]) OR file.size < 1000"
+            )),
+            vec!["Books/Deep Work.md", "Home.md"]
+        );
+
+        let result = run("LIST length(file.inlinks) FROM #books SORT file.name");
+        assert_eq!(result.kind, DqlKind::List);
+        assert_eq!(result.rows[0].values, vec![DqlValue::Number(1.0)]);
+    }
+
+    #[test]
+    fn test_run_dql_link_source_resolution() {
+        let conn = setup_db();
+        conn.execute_batch("INSERT INTO aliases VALUES (1, 1, 'Reading'), (2, 2, 'Reading');")
+            .unwrap();
+        let error = |query: &str| run_dql_at(&conn, query, now()).unwrap_err();
+
+        // No fuzzy matching: a typo is not some other note
+        assert!(matches!(
+            error("LIST FROM [[Hom]]"),
+            DqlError::NoteNotFound(note) if note == "Hom"
+        ));
+        assert!(matches!(
+            error("LIST FROM [[Reading]]"),
+            DqlError::AmbiguousNote(e) if e.candidates.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_run_dql_syntax_error() {
+        let conn = setup_db();
+        let err = run_dql_at(&conn, "LIST WHERE rating >", now()).unwrap_err();
+        let message = err.to_string();
+
+        assert!(message.starts_with("Invalid DQL query at column 20: expected an expression"));
+        assert!(message.ends_with(&format!("  {}^", " ".repeat(19))));
+    }
+}
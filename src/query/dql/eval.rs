@@ -0,0 +1,354 @@
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashMap;
+
+use super::parser::{BinaryOp, Expr, Source};
+use super::value::{parse_date, parse_duration, Value};
+use crate::parser::normalize_field_key;
+
+/// Everything a query can read about one note
+#[derive(Debug, Clone, Default)]
+pub(super) struct Row {
+    pub note_id: i64,
+    pub path: String,
+    pub title: String,
+    /// Seconds since the Unix epoch
+    pub mtime: i64,
+    pub size: i64,
+    /// Values by normalized key; repeated keys become a list
+    pub fields: HashMap<String, Value>,
+    /// Tags without the leading `#`
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    /// Link targets: the note path without `.md` when resolved, the link text otherwise
+    pub outlinks: Vec<String>,
+    pub outlink_ids: Vec<i64>,
+    pub inlinks: Vec<String>,
+}
+
+impl Row {
+    fn stem(&self) -> &str {
+        self.path.strip_suffix(".md").unwrap_or(&self.path)
+    }
+
+    /// Value of a dotted field path such as `file.mtime` or `rating`
+    pub(super) fn get(&self, name: &str) -> Value {
+        if let Some(file_field) = name.strip_prefix("file.") {
+            return self.file_value(&file_field.to_lowercase());
+        }
+        self.fields
+            .get(&normalize_field_key(name))
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    fn file_value(&self, name: &str) -> Value {
+        let strings = |items: &[String]| {
+            Value::List(items.iter().map(|s| Value::String(s.clone())).collect())
+        };
+        let links =
+            |items: &[String]| Value::List(items.iter().cloned().map(Value::Link).collect());
+
+        match name {
+            "name" => Value::String(self.stem().rsplit('/').next().unwrap_or_default().into()),
+            "path" => Value::String(self.path.clone()),
+            "folder" => Value::String(
+                self.path
+                    .rsplit_once('/')
+                    .map(|(folder, _)| folder.to_string())
+                    .unwrap_or_default(),
+            ),
+            "ext" => Value::String("md".to_string()),
+            "link" => Value::Link(self.stem().to_string()),
+            "mtime" => chrono::DateTime::from_timestamp(self.mtime, 0)
+                .map(|dt| Value::Date(dt.with_timezone(&chrono::Local).naive_local()))
+                .unwrap_or(Value::Null),
+            "size" => Value::Number(self.size as f64),
+            "tags" => strings(&expand_tags(&self.tags)),
+            "etags" => strings(
+                &self
+                    .tags
+                    .iter()
+                    .map(|tag| format!("#{tag}"))
+                    .collect::<Vec<_>>(),
+            ),
+            "aliases" => strings(&self.aliases),
+            "outlinks" => links(&self.outlinks),
+            "inlinks" => links(&self.inlinks),
+            _ => Value::Null,
+        }
+    }
+
+    pub(super) fn matches_source(&self, source: &Source, link_ids: &HashMap<String, i64>) -> bool {
+        match source {
+            Source::Tag(tag) => self.tags.iter().any(|t| {
+                t.eq_ignore_ascii_case(tag)
+                    || t.to_lowercase()
+                        .starts_with(&format!("{}/", tag.to_lowercase()))
+            }),
+            Source::Folder(folder) => {
+                let folder = folder.trim_matches('/');
+                folder.is_empty()
+                    || self.path == folder
+                    || self.stem() == folder
+                    || self.path.starts_with(&format!("{folder}/"))
+            }
+            Source::Link(note) => link_ids
+                .get(note)
+                .is_some_and(|id| self.outlink_ids.contains(id)),
+            Source::Not(inner) => !self.matches_source(inner, link_ids),
+            Source::And(a, b) => {
+                self.matches_source(a, link_ids) && self.matches_source(b, link_ids)
+            }
+            Source::Or(a, b) => {
+                self.matches_source(a, link_ids) || self.matches_source(b, link_ids)
+            }
+        }
+    }
+}
+
+/// `#a/b` also counts as `#a`, as in Dataview's `file.tags`
+fn expand_tags(tags: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for tag in tags {
+        let mut prefix = String::new();
+        for part in tag.split('/') {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(part);
+            let tag = format!("#{prefix}");
+            if !expanded.contains(&tag) {
+                expanded.push(tag);
+            }
+        }
+    }
+    expanded
+}
+
+/// Evaluate an expression for a row; `now` anchors `date(today)` and friends.
+/// Type mismatches evaluate to null rather than failing the query.
+pub(super) fn eval(expr: &Expr, row: &Row, now: NaiveDateTime) -> Value {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Field(name) => row.get(name),
+        Expr::Not(inner) => Value::Bool(!eval(inner, row, now).is_truthy()),
+        Expr::Neg(inner) => match eval(inner, row, now) {
+            Value::Number(n) => Value::Number(-n),
+            Value::Duration(d) => Value::Duration(-d),
+            _ => Value::Null,
+        },
+        Expr::Binary(BinaryOp::And, a, b) => {
+            Value::Bool(eval(a, row, now).is_truthy() && eval(b, row, now).is_truthy())
+        }
+        Expr::Binary(BinaryOp::Or, a, b) => {
+            Value::Bool(eval(a, row, now).is_truthy() || eval(b, row, now).is_truthy())
+        }
+        Expr::Binary(op, a, b) => binary(*op, eval(a, row, now), eval(b, row, now)),
+        Expr::Call(name, args) => {
+            let args: Vec<Value> = args.iter().map(|arg| eval(arg, row, now)).collect();
+            call(name, &args, now)
+        }
+    }
+}
+
+fn binary(op: BinaryOp, a: Value, b: Value) -> Value {
+    use std::cmp::Ordering::*;
+
+    let compared = || a.compare(&b);
+    match op {
+        BinaryOp::Eq => Value::Bool(a.loose_eq(&b)),
+        BinaryOp::Ne => Value::Bool(!a.loose_eq(&b)),
+        BinaryOp::Lt => Value::Bool(!is_null(&a, &b) && compared() == Some(Less)),
+        BinaryOp::Le => Value::Bool(!is_null(&a, &b) && matches!(compared(), Some(Less | Equal))),
+        BinaryOp::Gt => Value::Bool(!is_null(&a, &b) && compared() == Some(Greater)),
+        BinaryOp::Ge => {
+            Value::Bool(!is_null(&a, &b) && matches!(compared(), Some(Greater | Equal)))
+        }
+        BinaryOp::Add => match (a, b) {
+            (Value::Number(x), Value::Number(y)) => Value::Number(x + y),
+            (Value::Date(d), Value::Duration(dur)) | (Value::Duration(dur), Value::Date(d)) => {
+                Value::Date(d + dur)
+            }
+            (Value::Duration(x), Value::Duration(y)) => Value::Duration(x + y),
+            (Value::String(s), other) => Value::String(format!("{s}{other}")),
+            (other, Value::String(s)) if other != Value::Null => {
+                Value::String(format!("{other}{s}"))
+            }
+            _ => Value::Null,
+        },
+        BinaryOp::Sub => match (a, b) {
+            (Value::Number(x), Value::Number(y)) => Value::Number(x - y),
+            (Value::Date(d), Value::Duration(dur)) => Value::Date(d - dur),
+            (Value::Date(x), Value::Date(y)) => Value::Duration(x - y),
+            (Value::Duration(x), Value::Duration(y)) => Value::Duration(x - y),
+            _ => Value::Null,
+        },
+        BinaryOp::Mul => match (a, b) {
+            (Value::Number(x), Value::Number(y)) => Value::Number(x * y),
+            _ => Value::Null,
+        },
+        BinaryOp::Div => match (a, b) {
+            (Value::Number(x), Value::Number(y)) if y != 0.0 => Value::Number(x / y),
+            _ => Value::Null,
+        },
+        BinaryOp::And | BinaryOp::Or => unreachable!("logical operators short-circuit in eval"),
+    }
+}
+
+/// Ordering comparisons with null are always false
+fn is_null(a: &Value, b: &Value) -> bool {
+    matches!(a, Value::Null) || matches!(b, Value::Null)
+}
+
+fn call(name: &str, args: &[Value], now: NaiveDateTime) -> Value {
+    match (name, args) {
+        ("contains", [container, item]) => Value::Bool(contains(container, item, false)),
+        ("icontains", [container, item]) => Value::Bool(contains(container, item, true)),
+        ("startswith", [Value::String(s), Value::String(prefix)]) => {
+            Value::Bool(s.starts_with(prefix.as_str()))
+        }
+        ("endswith", [Value::String(s), Value::String(suffix)]) => {
+            Value::Bool(s.ends_with(suffix.as_str()))
+        }
+        ("length", [Value::List(items)]) => Value::Number(items.len() as f64),
+        ("length", [Value::String(s)]) => Value::Number(s.chars().count() as f64),
+        ("length", [Value::Null]) => Value::Number(0.0),
+        ("lower", [Value::String(s)]) => Value::String(s.to_lowercase()),
+        ("upper", [Value::String(s)]) => Value::String(s.to_uppercase()),
+        ("lower" | "upper", [other]) => other.clone(),
+        ("default", [Value::Null, fallback]) => fallback.clone(),
+        ("default", [value, _]) => value.clone(),
+        ("date", [Value::Date(d)]) => Value::Date(*d),
+        ("date", [Value::String(s)]) => {
+            let today = now.date().and_time(chrono::NaiveTime::MIN);
+            match s.to_lowercase().as_str() {
+                "now" => Value::Date(now),
+                "today" => Value::Date(today),
+                "tomorrow" => Value::Date(today + Duration::days(1)),
+                "yesterday" => Value::Date(today - Duration::days(1)),
+                _ => parse_date(s).map_or(Value::Null, Value::Date),
+            }
+        }
+        ("dur", [Value::String(s)]) => parse_duration(s).map_or(Value::Null, Value::Duration),
+        ("dur", [Value::Duration(d)]) => Value::Duration(*d),
+        _ => Value::Null,
+    }
+}
+
+/// Dataview `contains`: list membership (recursing into items) and substring
+/// match for text
+fn contains(container: &Value, item: &Value, ignore_case: bool) -> bool {
+    match (container, item) {
+        (Value::List(items), _) => items.iter().any(|v| contains(v, item, ignore_case)),
+        (Value::String(s), Value::String(needle)) if ignore_case => {
+            s.to_lowercase().contains(&needle.to_lowercase())
+        }
+        (Value::String(s), Value::String(needle)) => s.contains(needle.as_str()),
+        _ => container.loose_eq(item),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::parse_query;
+    use super::super::parser::Command;
+    use super::*;
+
+    fn row() -> Row {
+        let mut fields = HashMap::new();
+        fields.insert("rating".to_string(), Value::Number(4.0));
+        fields.insert("status".to_string(), Value::String("reading".to_string()));
+        fields.insert(
+            "finished".to_string(),
+            Value::Date(parse_date("2024-03-01").unwrap()),
+        );
+        Row {
+            note_id: 1,
+            path: "Books/Deep Work.md".to_string(),
+            title: "Deep Work".to_string(),
+            mtime: 1_700_000_000,
+            size: 2048,
+            fields,
+            tags: vec!["books/nonfiction".to_string()],
+            outlinks: vec!["Stoicism".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn eval_where(expr: &str) -> Value {
+        let query = parse_query(&format!("LIST WHERE {expr}")).unwrap();
+        let Command::Where(expr) = &query.commands[0] else {
+            panic!("expected WHERE");
+        };
+        let now = parse_date("2024-03-10T12:00").unwrap();
+        eval(expr, &row(), now)
+    }
+
+    #[test]
+    fn test_eval_fields_and_comparisons() {
+        assert_eq!(
+            eval_where("rating >= 4 AND status = \"reading\""),
+            Value::Bool(true)
+        );
+        assert_eq!(eval_where("rating * 2 + 1"), Value::Number(9.0));
+        assert_eq!(eval_where("missing > 1"), Value::Bool(false));
+        assert_eq!(eval_where("!missing"), Value::Bool(true));
+        assert_eq!(
+            eval_where("file.name"),
+            Value::String("Deep Work".to_string())
+        );
+        assert_eq!(
+            eval_where("file.folder"),
+            Value::String("Books".to_string())
+        );
+        assert_eq!(eval_where("file.size > 1000"), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_eval_dates_and_functions() {
+        assert_eq!(
+            eval_where("finished >= date(today) - dur(2 weeks)"),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval_where("finished < date(\"2024-01-01\")"),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            eval_where("contains(file.tags, \"#books\")"),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval_where("contains(file.tags, \"#novels\")"),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            eval_where("contains(file.outlinks, [[stoicism]])"),
+            Value::Bool(true)
+        );
+        assert_eq!(eval_where("icontains(status, \"READ\")"), Value::Bool(true));
+        assert_eq!(eval_where("length(file.tags)"), Value::Number(2.0));
+        assert_eq!(
+            eval_where("default(missing, \"n/a\")"),
+            Value::String("n/a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_matches_source() {
+        let row = row();
+        let links = HashMap::from([("Stoicism".to_string(), 7)]);
+        let source = |query: &str| parse_query(query).unwrap().source.unwrap();
+
+        assert!(row.matches_source(&source("LIST FROM #books"), &links));
+        assert!(!row.matches_source(&source("LIST FROM #book"), &links));
+        assert!(row.matches_source(&source("LIST FROM \"Books\""), &links));
+        assert!(!row.matches_source(&source("LIST FROM \"Book\""), &links));
+        assert!(row.matches_source(&source("LIST FROM #books AND -\"Archive\""), &links));
+
+        let mut linked = row.clone();
+        linked.outlink_ids.push(7);
+        assert!(linked.matches_source(&source("LIST FROM [[Stoicism]]"), &links));
+        assert!(!row.matches_source(&source("LIST FROM [[Stoicism]]"), &links));
+    }
+}
@@ -0,0 +1,204 @@
+use super::DqlError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum TokenKind {
+    /// Identifier or keyword; dots and inner hyphens are part of the name (`file.mtime`, `due-date`)
+    Ident(String),
+    Number(f64),
+    Str(String),
+    /// `#tag` without the leading `#`
+    Tag(String),
+    /// `[[target]]` without brackets, alias or heading
+    Link(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the query
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Operators, longest first
+const OPERATORS: [&str; 11] = ["!=", "<=", ">=", "=", "<", ">", "+", "-", "*", "/", "!"];
+
+pub(super) fn tokenize(query: &str) -> Result<Vec<Token>, DqlError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let rest = &query[start..];
+        let (kind, len) = if c == '"' {
+            lex_string(query, start)?
+        } else if let Some(inner) = rest.strip_prefix("[[") {
+            let Some(close) = inner.find("]]") else {
+                return Err(DqlError::syntax(
+                    query,
+                    start,
+                    "unclosed link, expected ']]'",
+                ));
+            };
+            let target = inner[..close]
+                .split('|')
+                .next()
+                .unwrap_or_default()
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .trim();
+            (TokenKind::Link(target.to_string()), close + 4)
+        } else if c == '#' {
+            let len = rest[1..]
+                .find(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '/' | '_' | '-')))
+                .unwrap_or(rest.len() - 1);
+            if len == 0 {
+                return Err(DqlError::syntax(
+                    query,
+                    start,
+                    "expected a tag name after '#'",
+                ));
+            }
+            (TokenKind::Tag(rest[1..=len].to_string()), len + 1)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse::<f64>()
+                .map_err(|_| DqlError::syntax(query, start, "invalid number"))?;
+            (TokenKind::Number(number), len)
+        } else if c.is_alphabetic() || c == '_' {
+            (
+                TokenKind::Ident(lex_ident(rest).to_string()),
+                lex_ident(rest).len(),
+            )
+        } else if c == '(' {
+            (TokenKind::LParen, 1)
+        } else if c == ')' {
+            (TokenKind::RParen, 1)
+        } else if c == ',' {
+            (TokenKind::Comma, 1)
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            (TokenKind::Op(op), op.len())
+        } else {
+            return Err(DqlError::syntax(
+                query,
+                start,
+                &format!("unexpected character '{c}'"),
+            ));
+        };
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: start + len,
+        });
+        while chars.peek().is_some_and(|&(idx, _)| idx < start + len) {
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Identifier characters: letters, digits, `_`, `.` and `-` when followed by a letter or digit
+fn lex_ident(text: &str) -> &str {
+    let mut end = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        let next_is_word = chars
+            .peek()
+            .is_some_and(|&(_, next)| next.is_alphanumeric() || next == '_');
+        if c.is_alphanumeric() || c == '_' || (matches!(c, '-' | '.') && next_is_word) {
+            end = idx + c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    &text[..end]
+}
+
+fn lex_string(query: &str, start: usize) -> Result<(TokenKind, usize), DqlError> {
+    let mut value = String::new();
+    let mut escaped = false;
+
+    for (idx, c) in query[start + 1..].char_indices() {
+        if escaped {
+            value.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            return Ok((TokenKind::Str(value), idx + 2));
+        } else {
+            value.push(c);
+        }
+    }
+
+    Err(DqlError::syntax(
+        query,
+        start,
+        "unclosed string, expected '\"'",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(query: &str) -> Vec<TokenKind> {
+        tokenize(query)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_query() {
+        assert_eq!(
+            kinds(r#"FROM #books/read AND "Projects" WHERE due-date <= 2.5"#),
+            vec![
+                TokenKind::Ident("FROM".to_string()),
+                TokenKind::Tag("books/read".to_string()),
+                TokenKind::Ident("AND".to_string()),
+                TokenKind::Str("Projects".to_string()),
+                TokenKind::Ident("WHERE".to_string()),
+                TokenKind::Ident("due-date".to_string()),
+                TokenKind::Op("<="),
+                TokenKind::Number(2.5),
+            ]
+        );
+        assert_eq!(
+            kinds("contains(file.outlinks, [[Deep Work#Rules|DW]]) - 1"),
+            vec![
+                TokenKind::Ident("contains".to_string()),
+                TokenKind::LParen,
+                TokenKind::Ident("file.outlinks".to_string()),
+                TokenKind::Comma,
+                TokenKind::Link("Deep Work".to_string()),
+                TokenKind::RParen,
+                TokenKind::Op("-"),
+                TokenKind::Number(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        let err = tokenize(r#"WHERE title = "open"#).unwrap_err();
+        assert!(err.to_string().contains("unclosed string"));
+        assert!(tokenize("WHERE a ; b").is_err());
+    }
+}
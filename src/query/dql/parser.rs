@@ -0,0 +1,590 @@
+use super::lexer::{tokenize, Token, TokenKind};
+use super::value::Value;
+use super::DqlError;
+
+/// Clause keywords, which can never be read as field names
+const CLAUSES: [&str; 6] = ["list", "table", "from", "where", "sort", "limit"];
+
+/// Supported functions with their accepted argument counts
+const FUNCTIONS: [(&str, usize, usize); 10] = [
+    ("contains", 2, 2),
+    ("icontains", 2, 2),
+    ("startswith", 2, 2),
+    ("endswith", 2, 2),
+    ("length", 1, 1),
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("default", 2, 2),
+    ("date", 1, 1),
+    ("dur", 1, 1),
+];
+
+/// Words `date()` accepts unquoted
+const DATE_KEYWORDS: [&str; 4] = ["today", "now", "tomorrow", "yesterday"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+    Literal(Value),
+    /// Dotted field path such as `rating` or `file.mtime`
+    Field(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// A `FROM` source
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Source {
+    /// Notes carrying the tag or one of its nested tags
+    Tag(String),
+    /// Notes under the folder, or the note at that path
+    Folder(String),
+    /// Notes linking to the note
+    Link(String),
+    Not(Box<Source>),
+    And(Box<Source>, Box<Source>),
+    Or(Box<Source>, Box<Source>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Column {
+    pub expr: Expr,
+    /// Header text: the `AS` name or the expression as written
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum QueryType {
+    List(Option<Column>),
+    Table(Vec<Column>),
+}
+
+/// Data commands, applied in the order they are written
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Command {
+    Where(Expr),
+    /// Sort keys with `true` for descending
+    Sort(Vec<(Expr, bool)>),
+    Limit(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Query {
+    pub query_type: QueryType,
+    pub without_id: bool,
+    pub source: Option<Source>,
+    pub commands: Vec<Command>,
+}
+
+pub(super) fn parse_query(query: &str) -> Result<Query, DqlError> {
+    let tokens = tokenize(query)?;
+    let mut parser = Parser {
+        query,
+        tokens,
+        pos: 0,
+    };
+    parser.parse()
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self) -> Result<Query, DqlError> {
+        let is_table = if self.eat_keyword("table") {
+            true
+        } else if self.eat_keyword("list") {
+            false
+        } else {
+            return Err(self.error("expected LIST or TABLE at the start of the query"));
+        };
+
+        let without_id = if self.eat_keyword("without") {
+            if !self.eat_keyword("id") {
+                return Err(self.error("expected ID after WITHOUT"));
+            }
+            true
+        } else {
+            false
+        };
+
+        let query_type = if is_table {
+            let mut columns = Vec::new();
+            if !self.at_clause() {
+                loop {
+                    columns.push(self.parse_column()?);
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+            }
+            QueryType::Table(columns)
+        } else if self.at_clause() {
+            QueryType::List(None)
+        } else {
+            QueryType::List(Some(self.parse_column()?))
+        };
+
+        let source = if self.eat_keyword("from") {
+            Some(self.parse_source_or()?)
+        } else {
+            None
+        };
+
+        let mut commands = Vec::new();
+        while self.peek().is_some() {
+            if self.eat_keyword("where") {
+                commands.push(Command::Where(self.parse_expr()?));
+            } else if self.eat_keyword("sort") {
+                let mut keys = Vec::new();
+                loop {
+                    let expr = self.parse_expr()?;
+                    let descending = self.eat_keyword("desc") || self.eat_keyword("descending");
+                    if !descending && !self.eat_keyword("asc") {
+                        self.eat_keyword("ascending");
+                    }
+                    keys.push((expr, descending));
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+                commands.push(Command::Sort(keys));
+            } else if self.eat_keyword("limit") {
+                match self.next_kind() {
+                    Some(TokenKind::Number(n)) if n.fract() == 0.0 && n >= 0.0 => {
+                        commands.push(Command::Limit(n as usize));
+                    }
+                    _ => return Err(self.error_at_previous("LIMIT expects a whole number")),
+                }
+            } else if self.peek_keyword("from") {
+                return Err(self.error("FROM must come before WHERE, SORT and LIMIT"));
+            } else {
+                return Err(self.error("expected WHERE, SORT or LIMIT"));
+            }
+        }
+
+        Ok(Query {
+            query_type,
+            without_id,
+            source,
+            commands,
+        })
+    }
+
+    fn parse_column(&mut self) -> Result<Column, DqlError> {
+        let start = self.current_start();
+        let expr = self.parse_expr()?;
+        let end = self.tokens[self.pos - 1].end;
+
+        let name = if self.eat_keyword("as") {
+            match self.next_kind() {
+                Some(TokenKind::Str(name) | TokenKind::Ident(name)) => name,
+                _ => return Err(self.error_at_previous("expected a column name after AS")),
+            }
+        } else {
+            self.query[start..end].trim().to_string()
+        };
+
+        Ok(Column { expr, name })
+    }
+
+    fn parse_source_or(&mut self) -> Result<Source, DqlError> {
+        let mut source = self.parse_source_and()?;
+        while self.eat_keyword("or") {
+            source = Source::Or(Box::new(source), Box::new(self.parse_source_and()?));
+        }
+        Ok(source)
+    }
+
+    fn parse_source_and(&mut self) -> Result<Source, DqlError> {
+        let mut source = self.parse_source_atom()?;
+        while self.eat_keyword("and") {
+            source = Source::And(Box::new(source), Box::new(self.parse_source_atom()?));
+        }
+        Ok(source)
+    }
+
+    fn parse_source_atom(&mut self) -> Result<Source, DqlError> {
+        match self.next_kind() {
+            Some(TokenKind::Tag(tag)) => Ok(Source::Tag(tag)),
+            Some(TokenKind::Str(folder)) => Ok(Source::Folder(folder)),
+            Some(TokenKind::Link(note)) => Ok(Source::Link(note)),
+            Some(TokenKind::Op("-" | "!")) => Ok(Source::Not(Box::new(self.parse_source_atom()?))),
+            Some(TokenKind::LParen) => {
+                let source = self.parse_source_or()?;
+                self.expect(&TokenKind::RParen, "expected ')'")?;
+                Ok(source)
+            }
+            _ => Err(self.error_at_previous(
+                "expected a source: #tag, \"folder\" or [[note]], optionally combined with AND, OR and -",
+            )),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, DqlError> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Binary(BinaryOp::Or, Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, DqlError> {
+        let mut expr = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            expr = Expr::Binary(
+                BinaryOp::And,
+                Box::new(expr),
+                Box::new(self.parse_comparison()?),
+            );
+        }
+        Ok(expr)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, DqlError> {
+        let expr = self.parse_additive()?;
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Op("=")) => BinaryOp::Eq,
+            Some(TokenKind::Op("!=")) => BinaryOp::Ne,
+            Some(TokenKind::Op("<")) => BinaryOp::Lt,
+            Some(TokenKind::Op("<=")) => BinaryOp::Le,
+            Some(TokenKind::Op(">")) => BinaryOp::Gt,
+            Some(TokenKind::Op(">=")) => BinaryOp::Ge,
+            _ => return Ok(expr),
+        };
+        self.pos += 1;
+        Ok(Expr::Binary(
+            op,
+            Box::new(expr),
+            Box::new(self.parse_additive()?),
+        ))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, DqlError> {
+        let mut expr = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Op("+")) => BinaryOp::Add,
+                Some(TokenKind::Op("-")) => BinaryOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_multiplicative()?));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, DqlError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Op("*")) => BinaryOp::Mul,
+                Some(TokenKind::Op("/")) => BinaryOp::Div,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, DqlError> {
+        if self.eat(&TokenKind::Op("!")) {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat(&TokenKind::Op("-")) {
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, DqlError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("expected an expression"));
+        };
+        self.pos += 1;
+
+        match token.kind {
+            TokenKind::Number(n) => Ok(Expr::Literal(Value::Number(n))),
+            TokenKind::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            TokenKind::Link(target) => Ok(Expr::Literal(Value::Link(target))),
+            TokenKind::Tag(tag) => Ok(Expr::Literal(Value::String(format!("#{tag}")))),
+            TokenKind::LParen => {
+                let expr = self.parse_expr()?;
+                self.expect(&TokenKind::RParen, "expected ')'")?;
+                Ok(expr)
+            }
+            TokenKind::Ident(name) => {
+                let lower = name.to_lowercase();
+                if CLAUSES.contains(&lower.as_str()) || matches!(lower.as_str(), "and" | "or") {
+                    self.pos -= 1;
+                    return Err(self.error(&format!("expected an expression, found '{name}'")));
+                }
+                match lower.as_str() {
+                    "true" => return Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => return Ok(Expr::Literal(Value::Bool(false))),
+                    "null" => return Ok(Expr::Literal(Value::Null)),
+                    _ => {}
+                }
+                if self.eat(&TokenKind::LParen) {
+                    self.parse_call(&lower, token.start)
+                } else {
+                    Ok(Expr::Field(name))
+                }
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected an expression"))
+            }
+        }
+    }
+
+    /// Parse the arguments of a call; the opening parenthesis is consumed
+    fn parse_call(&mut self, name: &str, start: usize) -> Result<Expr, DqlError> {
+        let Some(&(_, min, max)) = FUNCTIONS.iter().find(|(f, _, _)| *f == name) else {
+            let known: Vec<&str> = FUNCTIONS.iter().map(|(f, _, _)| *f).collect();
+            return Err(DqlError::syntax(
+                self.query,
+                start,
+                &format!(
+                    "unknown function '{name}', supported functions: {}",
+                    known.join(", ")
+                ),
+            ));
+        };
+
+        // `dur(7 days)` and `date(today)` take bare words
+        if name == "dur" || name == "date" {
+            let close = self.tokens[self.pos..]
+                .iter()
+                .position(|t| t.kind == TokenKind::RParen);
+            if let Some(close) = close.map(|offset| self.pos + offset) {
+                let raw = &self.tokens[self.pos..close];
+                let bare = match raw {
+                    [Token {
+                        kind: TokenKind::Ident(word),
+                        ..
+                    }] if name == "date" => DATE_KEYWORDS.contains(&word.to_lowercase().as_str()),
+                    [Token {
+                        kind: TokenKind::Number(_),
+                        ..
+                    }, ..] => name == "dur",
+                    _ => false,
+                };
+                if bare {
+                    let text = self.query[raw[0].start..raw[raw.len() - 1].end].to_string();
+                    self.pos = close + 1;
+                    return Ok(Expr::Call(
+                        name.to_string(),
+                        vec![Expr::Literal(Value::String(text))],
+                    ));
+                }
+            }
+        }
+
+        let mut args = Vec::new();
+        if !self.eat(&TokenKind::RParen) {
+            loop {
+                args.push(self.parse_expr()?);
+                if self.eat(&TokenKind::Comma) {
+                    continue;
+                }
+                self.expect(&TokenKind::RParen, "expected ',' or ')' in function call")?;
+                break;
+            }
+        }
+
+        if args.len() < min || args.len() > max {
+            return Err(DqlError::syntax(
+                self.query,
+                start,
+                &format!(
+                    "{name}() takes {min} argument{}, got {}",
+                    if min == 1 { "" } else { "s" },
+                    args.len()
+                ),
+            ));
+        }
+        Ok(Expr::Call(name.to_string(), args))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_kind(&mut self) -> Option<TokenKind> {
+        let kind = self.tokens.get(self.pos).map(|t| t.kind.clone());
+        if kind.is_some() {
+            self.pos += 1;
+        }
+        kind
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Ident(word), .. })
+            if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        let found = self.peek().is_some_and(|t| &t.kind == kind);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, kind: &TokenKind, message: &str) -> Result<(), DqlError> {
+        if self.eat(kind) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    /// Whether the next token starts a clause (or the query ends)
+    fn at_clause(&self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(Token {
+                kind: TokenKind::Ident(word),
+                ..
+            }) => CLAUSES.contains(&word.to_lowercase().as_str()),
+            _ => false,
+        }
+    }
+
+    fn current_start(&self) -> usize {
+        self.peek().map_or(self.query.len(), |t| t.start)
+    }
+
+    fn error(&self, message: &str) -> DqlError {
+        DqlError::syntax(self.query, self.current_start(), message)
+    }
+
+    fn error_at_previous(&self, message: &str) -> DqlError {
+        let position = self
+            .pos
+            .checked_sub(1)
+            .and_then(|idx| self.tokens.get(idx))
+            .map_or(self.query.len(), |t| t.start);
+        DqlError::syntax(self.query, position, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Box<Expr> {
+        Box::new(Expr::Field(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse_table_query() {
+        let query = parse_query(
+            r#"TABLE rating AS "Score", file.mtime FROM #books AND -"Archive" WHERE rating >= 4 AND !done SORT rating DESC, file.name LIMIT 5"#,
+        )
+        .unwrap();
+
+        let QueryType::Table(columns) = &query.query_type else {
+            panic!("expected a table");
+        };
+        assert_eq!(columns[0].name, "Score");
+        assert_eq!(columns[1].name, "file.mtime");
+        assert_eq!(
+            query.source,
+            Some(Source::And(
+                Box::new(Source::Tag("books".to_string())),
+                Box::new(Source::Not(Box::new(Source::Folder("Archive".to_string()))))
+            ))
+        );
+        assert_eq!(
+            query.commands[0],
+            Command::Where(Expr::Binary(
+                BinaryOp::And,
+                Box::new(Expr::Binary(
+                    BinaryOp::Ge,
+                    field("rating"),
+                    Box::new(Expr::Literal(Value::Number(4.0)))
+                )),
+                Box::new(Expr::Not(field("done")))
+            ))
+        );
+        assert_eq!(
+            query.commands[1],
+            Command::Sort(vec![
+                (Expr::Field("rating".to_string()), true),
+                (Expr::Field("file.name".to_string()), false)
+            ])
+        );
+        assert_eq!(query.commands[2], Command::Limit(5));
+    }
+
+    #[test]
+    fn test_parse_list_and_bare_function_arguments() {
+        let query = parse_query(
+            "list without id file.link from [[Home]] where file.mtime >= date(today) - dur(7 days)",
+        )
+        .unwrap();
+
+        assert!(query.without_id);
+        assert!(matches!(query.query_type, QueryType::List(Some(_))));
+        assert_eq!(query.source, Some(Source::Link("Home".to_string())));
+        let Command::Where(Expr::Binary(BinaryOp::Ge, _, rhs)) = &query.commands[0] else {
+            panic!("expected a comparison");
+        };
+        assert_eq!(
+            **rhs,
+            Expr::Binary(
+                BinaryOp::Sub,
+                Box::new(Expr::Call(
+                    "date".to_string(),
+                    vec![Expr::Literal(Value::String("today".to_string()))]
+                )),
+                Box::new(Expr::Call(
+                    "dur".to_string(),
+                    vec![Expr::Literal(Value::String("7 days".to_string()))]
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |query: &str| parse_query(query).unwrap_err().to_string();
+
+        assert!(message("SELECT x").contains("expected LIST or TABLE"));
+        assert!(message("LIST WHERE").contains("expected an expression"));
+        assert!(message("LIST WHERE foo(x)").contains("unknown function 'foo'"));
+        assert!(message("LIST WHERE contains(x)").contains("takes 2 arguments"));
+        assert!(message("LIST LIMIT many").contains("whole number"));
+        assert!(message("LIST WHERE x = 1 FROM #a").contains("FROM must come before"));
+        assert!(message("LIST FROM rating").contains("expected a source"));
+    }
+}
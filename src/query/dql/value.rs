@@ -0,0 +1,260 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A DQL value. Field values keep the type detected at index time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Date(NaiveDateTime),
+    Duration(Duration),
+    /// Link target as written, or the note path without `.md` once resolved
+    Link(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Build a value from an indexed field using its detected type
+    pub(crate) fn from_field(value: &str, value_type: &str) -> Value {
+        match value_type {
+            "number" => value.trim().parse().map_or(Value::Null, Value::Number),
+            "bool" => Value::Bool(value.trim().eq_ignore_ascii_case("true")),
+            "date" => parse_date(value).map_or_else(|| Value::String(value.into()), Value::Date),
+            "link" => Value::Link(link_target(value)),
+            _ => match value
+                .trim()
+                .strip_prefix('[')
+                .and_then(|v| v.strip_suffix(']'))
+            {
+                // Frontmatter flow lists such as `[a, b]`
+                Some(items) if !value.trim().starts_with("[[") => Value::List(
+                    items
+                        .split(',')
+                        .map(|item| item.trim().trim_matches('"').trim_matches('\''))
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Value::from_field(item, crate::parser::field_value_type(item)))
+                        .collect(),
+                ),
+                _ => Value::String(value.to_string()),
+            },
+        }
+    }
+
+    pub(crate) fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::List(items) => !items.is_empty(),
+            Value::Date(_) | Value::Duration(_) | Value::Link(_) => true,
+        }
+    }
+
+    /// Equality as used by `=`: links match by note name, dates match
+    /// date strings and numbers compare numerically
+    pub(crate) fn loose_eq(&self, other: &Value) -> bool {
+        self.compare(other) == Some(Ordering::Equal)
+    }
+
+    /// Compare two values of compatible types; `None` when they cannot be compared
+    pub(crate) fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, Value::Null) => Some(Ordering::Equal),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::String(s)) => parse_date(s).map(|b| a.cmp(&b)),
+            (Value::String(s), Value::Date(b)) => parse_date(s).map(|a| a.cmp(b)),
+            (Value::Duration(a), Value::Duration(b)) => Some(a.cmp(b)),
+            (Value::Link(a), Value::Link(b)) => Some(link_key(a).cmp(&link_key(b))),
+            (Value::Link(a), Value::String(b)) | (Value::String(b), Value::Link(a))
+                if link_key(a) == link_key(b) =>
+            {
+                Some(Ordering::Equal)
+            }
+            (Value::List(a), Value::List(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.compare(y)? {
+                        Ordering::Equal => continue,
+                        order => return Some(order),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Total order used by SORT: nulls first, then by type, then by value
+    pub(crate) fn sort_cmp(&self, other: &Value) -> Ordering {
+        self.compare(other)
+            .unwrap_or_else(|| self.type_rank().cmp(&other.type_rank()))
+    }
+
+    fn type_rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::Duration(_) => 3,
+            Value::Date(_) => 4,
+            Value::String(_) => 5,
+            Value::Link(_) => 6,
+            Value::List(_) => 7,
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::json!(b),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => serde_json::json!(*n as i64),
+            Value::Number(n) => serde_json::json!(n),
+            Value::List(items) => items.iter().map(Value::to_json).collect(),
+            other => serde_json::json!(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "-"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "{s}"),
+            Value::Date(d) if d.time() == chrono::NaiveTime::MIN => {
+                write!(f, "{}", d.format("%Y-%m-%d"))
+            }
+            Value::Date(d) => write!(f, "{}", d.format("%Y-%m-%d %H:%M")),
+            Value::Duration(d) => format_duration(f, d),
+            Value::Link(target) => write!(f, "[[{target}]]"),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+                write!(f, "{}", items.join(", "))
+            }
+        }
+    }
+}
+
+fn format_duration(f: &mut fmt::Formatter<'_>, duration: &Duration) -> fmt::Result {
+    let days = duration.num_days();
+    if duration.num_seconds() == days * 86_400 {
+        write!(f, "{days} days")
+    } else {
+        write!(f, "{} minutes", duration.num_minutes())
+    }
+}
+
+/// `YYYY-MM-DD` with an optional `THH:MM[:SS]` or ` HH:MM[:SS]` time
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if !crate::parser::is_date(value) {
+        return None;
+    }
+    let date = NaiveDate::parse_from_str(&value[..10], "%Y-%m-%d").ok()?;
+    let time = value[10..].trim_start_matches(['T', ' ']);
+
+    if time.is_empty() {
+        return date.and_hms_opt(0, 0, 0);
+    }
+    ["%H:%M:%S", "%H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveTime::parse_from_str(time, format).ok())
+        .map(|time| date.and_time(time))
+}
+
+/// Parse a duration such as `7 days`, `2 weeks`, `3 hours` or `1 month`
+pub(crate) fn parse_duration(value: &str) -> Option<Duration> {
+    let mut words = value.split_whitespace();
+    let amount: i64 = words.next()?.parse().ok()?;
+    let unit = words.next().unwrap_or("days").to_lowercase();
+    if words.next().is_some() {
+        return None;
+    }
+
+    let unit = unit.trim_end_matches('s');
+    match unit {
+        "minute" | "min" | "m" => Some(Duration::minutes(amount)),
+        "hour" | "hr" | "h" => Some(Duration::hours(amount)),
+        "day" | "d" => Some(Duration::days(amount)),
+        "week" | "wk" | "w" => Some(Duration::weeks(amount)),
+        "month" | "mo" => Some(Duration::days(amount * 30)),
+        "year" | "yr" | "y" => Some(Duration::days(amount * 365)),
+        _ => None,
+    }
+}
+
+/// Target of `[[target|alias]]`, or the text itself
+fn link_target(value: &str) -> String {
+    let inner = value.trim().trim_start_matches("[[").trim_end_matches("]]");
+    inner
+        .split(['|', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Lowercase file name without folders or `.md`, the way Obsidian matches links
+fn link_key(target: &str) -> String {
+    let name = target.rsplit('/').next().unwrap_or(target);
+    name.strip_suffix(".md").unwrap_or(name).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_from_field() {
+        assert_eq!(Value::from_field("4", "number"), Value::Number(4.0));
+        assert_eq!(
+            Value::from_field("[[Deep Work|DW]]", "link"),
+            Value::Link("Deep Work".to_string())
+        );
+        assert_eq!(
+            Value::from_field("[a, \"b\", 3]", "text"),
+            Value::List(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::Number(3.0)
+            ])
+        );
+        assert!(matches!(
+            Value::from_field("2024-03-01T10:30", "date"),
+            Value::Date(d) if d.format("%H:%M").to_string() == "10:30"
+        ));
+    }
+
+    #[test]
+    fn test_value_compare() {
+        let date = Value::Date(parse_date("2024-03-01").unwrap());
+        assert!(date.loose_eq(&Value::String("2024-03-01".to_string())));
+        assert_eq!(
+            date.compare(&Value::Date(parse_date("2024-01-01").unwrap())),
+            Some(Ordering::Greater)
+        );
+        assert!(Value::Link("Books/Deep Work".to_string())
+            .loose_eq(&Value::Link("deep work".to_string())));
+        assert_eq!(
+            Value::Number(1.0).compare(&Value::String("1".to_string())),
+            None
+        );
+        assert_eq!(Value::Null.sort_cmp(&Value::Number(-5.0)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("7 days"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("2 weeks"), Some(Duration::days(14)));
+        assert_eq!(parse_duration("3 h"), Some(Duration::hours(3)));
+        assert_eq!(parse_duration("soon"), None);
+    }
+}
@@ -0,0 +1,144 @@
+use rusqlite::{Connection, Result};
+use std::collections::BTreeMap;
+
+/// A footnote problem in one note
+#[derive(Debug, Clone)]
+pub struct FootnoteIssue {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub label: String,
+    pub issue: String, // "missing_definition", "unused_definition" or "duplicate_label"
+    /// Lines of the references (missing definition) or definitions involved
+    pub lines: Vec<i64>,
+}
+
+#[derive(Default)]
+struct LabelUsage {
+    label: String,
+    first_line: i64,
+    references: Vec<i64>,
+    definitions: Vec<i64>,
+}
+
+/// Check footnotes note by note: references without a definition, definitions
+/// never referenced and labels defined more than once. Labels match
+/// case-insensitively. Issues are ordered by note path and first occurrence.
+pub fn diagnose_footnotes(conn: &Connection) -> Result<Vec<FootnoteIssue>> {
+    let mut stmt = conn.prepare(
+        "SELECT n.id, n.path, n.title, f.label, f.kind, f.line
+         FROM footnotes f
+         JOIN notes n ON f.note_id = n.id
+         ORDER BY n.path, f.line, f.byte_offset",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, i64>(5)?,
+        ))
+    })?;
+
+    // (path, id, title) -> lowercased label -> usage
+    let mut notes: BTreeMap<(String, i64, String), BTreeMap<String, LabelUsage>> = BTreeMap::new();
+    for row in rows {
+        let (note_id, path, title, label, kind, line) = row?;
+        let usage = notes
+            .entry((path, note_id, title))
+            .or_default()
+            .entry(label.to_lowercase())
+            .or_insert_with(|| LabelUsage {
+                label,
+                first_line: line,
+                ..Default::default()
+            });
+        if kind == "definition" {
+            usage.definitions.push(line);
+        } else {
+            usage.references.push(line);
+        }
+    }
+
+    let mut issues = Vec::new();
+    for ((note_path, note_id, note_title), labels) in notes {
+        let mut labels: Vec<LabelUsage> = labels.into_values().collect();
+        labels.sort_by_key(|usage| usage.first_line);
+
+        for usage in labels {
+            let mut push = |issue: &str, lines: Vec<i64>| {
+                issues.push(FootnoteIssue {
+                    note_id,
+                    note_path: note_path.clone(),
+                    note_title: note_title.clone(),
+                    label: usage.label.clone(),
+                    issue: issue.to_string(),
+                    lines,
+                });
+            };
+
+            if usage.definitions.is_empty() {
+                push("missing_definition", usage.references.clone());
+            } else if usage.references.is_empty() {
+                push("unused_definition", usage.definitions.clone());
+            }
+            if usage.definitions.len() > 1 {
+                push("duplicate_label", usage.definitions.clone());
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_footnotes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE footnotes (
+                id INTEGER PRIMARY KEY, note_id INTEGER, label TEXT, kind TEXT,
+                text TEXT, line INTEGER, byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES (1, 'b.md', 'B'), (2, 'a.md', 'A');
+             INSERT INTO footnotes (note_id, label, kind, text, line, byte_offset) VALUES
+                (1, '1', 'reference', '', 1, 5),
+                (1, 'Note', 'reference', '', 2, 20),
+                (1, '1', 'definition', 'One', 5, 50),
+                (1, 'note', 'definition', 'Two', 6, 60),
+                (1, 'note', 'definition', 'Again', 7, 70),
+                (1, 'extra', 'definition', 'Unused', 8, 80),
+                (2, 'x', 'reference', '', 3, 30),
+                (2, 'x', 'reference', '', 4, 40);",
+        )
+        .unwrap();
+
+        let issues = diagnose_footnotes(&conn).unwrap();
+        let summary: Vec<(&str, &str, &str, Vec<i64>)> = issues
+            .iter()
+            .map(|i| {
+                (
+                    i.note_path.as_str(),
+                    i.label.as_str(),
+                    i.issue.as_str(),
+                    i.lines.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("a.md", "x", "missing_definition", vec![3, 4]),
+                ("b.md", "Note", "duplicate_label", vec![6, 7]),
+                ("b.md", "extra", "unused_definition", vec![8]),
+            ]
+        );
+    }
+}
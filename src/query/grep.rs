@@ -0,0 +1,286 @@
+// Regular expression search over note source files
+use super::SearchOptions;
+use regex::{Regex, RegexBuilder};
+use rusqlite::Connection;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GrepError {
+    /// Not a source, so the parse error with its caret is printed once
+    #[error("Invalid regular expression: {0}")]
+    Pattern(regex::Error),
+
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+}
+
+/// Result size, context and the note filters of `search grep`
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    /// Maximum number of matching lines
+    pub limit: usize,
+    /// Lines of context before and after each matching line
+    pub context: usize,
+    pub ignore_case: bool,
+    /// Path globs (`*` also matches `/`); with `folders`, a note must match one
+    pub paths: Vec<String>,
+    /// Folders, including their subfolders
+    pub folders: Vec<String>,
+    /// Tags, matching nested tags too; a note needs one unless `all_tags`
+    pub tags: Vec<String>,
+    pub all_tags: bool,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            limit: 100,
+            context: 0,
+            ignore_case: false,
+            paths: Vec::new(),
+            folders: Vec::new(),
+            tags: Vec::new(),
+            all_tags: false,
+        }
+    }
+}
+
+/// A line of a note, numbered from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepLine {
+    pub line: usize,
+    pub text: String,
+}
+
+/// A line matching the pattern, with its context
+#[derive(Debug, Clone)]
+pub struct GrepMatch {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based character column of the first match
+    pub column: usize,
+    pub text: String,
+    /// Byte ranges of the matches in `text`
+    pub matches: Vec<(usize, usize)>,
+    pub before: Vec<GrepLine>,
+    pub after: Vec<GrepLine>,
+}
+
+/// Match `pattern` line by line against the source files of the indexed notes
+/// passing the filters, in path order. Notes whose file can no longer be read,
+/// such as ones deleted since the last index, are skipped.
+pub fn grep_notes(
+    conn: &Connection,
+    vault_path: &Path,
+    pattern: &str,
+    options: &GrepOptions,
+) -> Result<Vec<GrepMatch>, GrepError> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(GrepError::Pattern)?;
+
+    let filters = SearchOptions {
+        paths: options.paths.clone(),
+        folders: options.folders.clone(),
+        tags: options.tags.clone(),
+        all_tags: options.all_tags,
+        ..Default::default()
+    };
+    let mut params = Vec::new();
+    let conditions = filters.note_conditions("n", &mut params);
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT n.id, n.path, n.title FROM notes n {where_clause}
+         ORDER BY n.path COLLATE NOCASE"
+    ))?;
+    let notes = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut results = Vec::new();
+    for (note_id, note_path, note_title) in notes {
+        let Ok(content) = std::fs::read_to_string(vault_path.join(&note_path)) else {
+            continue;
+        };
+
+        for found in grep_text(&content, &regex, options.context) {
+            if results.len() >= options.limit {
+                return Ok(results);
+            }
+            results.push(GrepMatch {
+                note_id,
+                note_path: note_path.clone(),
+                note_title: note_title.clone(),
+                ..found
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Lines of `content` matching `regex`, without note details
+fn grep_text(content: &str, regex: &Regex, context: usize) -> Vec<GrepMatch> {
+    let lines: Vec<&str> = content.lines().collect();
+    let numbered = |from: usize, to: usize| -> Vec<GrepLine> {
+        (from..to)
+            .map(|idx| GrepLine {
+                line: idx + 1,
+                text: lines[idx].to_string(),
+            })
+            .collect()
+    };
+
+    let mut found = Vec::new();
+    for (idx, text) in lines.iter().enumerate() {
+        // Zero-length matches such as `^`, `$` or `\b` select the line but have
+        // nothing to highlight
+        let Some(first) = regex.find(text).map(|m| m.start()) else {
+            continue;
+        };
+        let matches: Vec<(usize, usize)> = regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect();
+
+        found.push(GrepMatch {
+            note_id: 0,
+            note_path: String::new(),
+            note_title: String::new(),
+            line: idx + 1,
+            column: text[..first].chars().count() + 1,
+            text: text.to_string(),
+            matches,
+            before: numbered(idx.saturating_sub(context), idx),
+            after: numbered(idx + 1, (idx + 1 + context).min(lines.len())),
+        });
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grep_text_lines_columns_and_context() {
+        let regex = Regex::new(r"TODO\(\w+\)").unwrap();
+        let content = "# Plan\nFirst TODO(ana) and TODO(bo)\nmiddle\ncafé TODO(cy)\n";
+
+        let found = grep_text(content, &regex, 1);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].line, found[0].column), (2, 7));
+        assert_eq!(found[0].matches, vec![(6, 15), (20, 28)]);
+        assert_eq!(
+            found[0].before,
+            vec![GrepLine {
+                line: 1,
+                text: "# Plan".to_string()
+            }]
+        );
+        assert_eq!(found[0].after[0].text, "middle");
+        // Columns count characters, not bytes
+        assert_eq!((found[1].line, found[1].column), (4, 6));
+        assert!(found[1].after.is_empty());
+    }
+
+    #[test]
+    fn test_grep_text_zero_length_matches() {
+        // Lines match like grep, but only non-empty matches are highlighted
+        let regex = Regex::new("x*").unwrap();
+        let found = grep_text("abc\nxx", &regex, 0);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].line, found[0].column), (1, 1));
+        assert!(found[0].matches.is_empty());
+        assert_eq!(found[1].matches, vec![(0, 2)]);
+
+        let regex = Regex::new("(?m)^$").unwrap();
+        let found = grep_text("one\n\nthree", &regex, 0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line, 2);
+
+        let regex = Regex::new("é$").unwrap();
+        assert_eq!(grep_text("café", &regex, 0)[0].column, 4);
+        let regex = Regex::new(r"$").unwrap();
+        assert_eq!(grep_text("café", &regex, 0)[0].column, 5);
+    }
+
+    #[test]
+    fn test_grep_notes_filters_and_limit() {
+        let vault = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(vault.path().join("work")).unwrap();
+        std::fs::write(
+            vault.path().join("work/a.md"),
+            "see https://internal.corp/x\n",
+        )
+        .unwrap();
+        std::fs::write(
+            vault.path().join("b.md"),
+            "HTTP://internal.corp/y\nhttp://internal.corp/z",
+        )
+        .unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             INSERT INTO notes VALUES (1, 'work/a.md', 'A'), (2, 'b.md', 'B'), (3, 'gone.md', 'Gone');
+             INSERT INTO tags (note_id, tag, display) VALUES (2, 'links', 'links');",
+        )
+        .unwrap();
+
+        let grep = |pattern: &str, options: &GrepOptions| -> Vec<(String, usize)> {
+            grep_notes(&conn, vault.path(), pattern, options)
+                .unwrap()
+                .into_iter()
+                .map(|m| (m.note_path, m.line))
+                .collect()
+        };
+        let pattern = r"https?://internal\.corp/\S+";
+
+        assert_eq!(
+            grep(pattern, &GrepOptions::default()),
+            vec![("b.md".to_string(), 2), ("work/a.md".to_string(), 1)]
+        );
+        let ignore_case = GrepOptions {
+            ignore_case: true,
+            limit: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep(pattern, &ignore_case),
+            vec![("b.md".to_string(), 1), ("b.md".to_string(), 2)]
+        );
+        let folder = GrepOptions {
+            folders: vec!["work".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(grep(pattern, &folder), vec![("work/a.md".to_string(), 1)]);
+        let tagged = GrepOptions {
+            tags: vec!["links".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(grep(pattern, &tagged), vec![("b.md".to_string(), 2)]);
+
+        let err = grep_notes(&conn, vault.path(), "TODO(", &GrepOptions::default()).unwrap_err();
+        assert!(matches!(err, GrepError::Pattern(_)));
+    }
+}
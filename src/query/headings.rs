@@ -0,0 +1,142 @@
+use rusqlite::{Connection, Result};
+
+use super::fuzzy::levenshtein;
+use crate::parser::slugify;
+
+/// Maximum number of headings suggested for a broken heading link
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A heading stored for a note
+#[derive(Debug, Clone)]
+pub struct HeadingResult {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+    pub line: i64,
+    pub byte_offset: i64,
+}
+
+/// Get the headings of a note in document order
+pub fn get_headings(conn: &Connection, note_id: i64) -> Result<Vec<HeadingResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT level, text, slug, line, byte_offset
+         FROM headings
+         WHERE note_id = ?1
+         ORDER BY byte_offset",
+    )?;
+
+    let results = stmt.query_map([note_id], |row| {
+        Ok(HeadingResult {
+            level: row.get(0)?,
+            text: row.get(1)?,
+            slug: row.get(2)?,
+            line: row.get(3)?,
+            byte_offset: row.get(4)?,
+        })
+    })?;
+
+    results.collect()
+}
+
+/// Whether a link's heading reference points at one of the headings.
+/// Nested references (`Note#Parent#Child`) are matched on their last segment,
+/// by text (case-insensitive) or by slug.
+pub(crate) fn heading_ref_matches(headings: &[HeadingResult], heading_ref: &str) -> bool {
+    let target = last_segment(heading_ref);
+    let slug = slugify(target);
+
+    headings
+        .iter()
+        .any(|h| h.text.eq_ignore_ascii_case(target) || (!slug.is_empty() && h.slug == slug))
+}
+
+/// Closest heading texts to a heading reference, nearest first
+pub(crate) fn suggest_headings(headings: &[HeadingResult], heading_ref: &str) -> Vec<String> {
+    let target = last_segment(heading_ref).to_lowercase();
+
+    let mut scored: Vec<(usize, &HeadingResult)> = headings
+        .iter()
+        .map(|h| (levenshtein(&target, &h.text.to_lowercase()), h))
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.byte_offset.cmp(&b.1.byte_offset)));
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for (_, heading) in scored {
+        if !suggestions.contains(&heading.text) {
+            suggestions.push(heading.text.clone());
+        }
+        if suggestions.len() == MAX_SUGGESTIONS {
+            break;
+        }
+    }
+    suggestions
+}
+
+fn last_segment(heading_ref: &str) -> &str {
+    heading_ref.rsplit('#').next().unwrap_or(heading_ref).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(level: u8, text: &str, byte_offset: i64) -> HeadingResult {
+        HeadingResult {
+            level,
+            text: text.to_string(),
+            slug: slugify(text),
+            line: byte_offset + 1,
+            byte_offset,
+        }
+    }
+
+    #[test]
+    fn test_get_headings_in_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE headings (
+                id INTEGER PRIMARY KEY, note_id INTEGER, level INTEGER, text TEXT,
+                slug TEXT, line INTEGER, byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES (1, 'Deep Work.md', 'Deep Work');
+             INSERT INTO headings VALUES (1, 1, 2, 'Rules', 'rules', 5, 40);
+             INSERT INTO headings VALUES (2, 1, 1, 'Deep Work', 'deep-work', 1, 0);",
+        )
+        .unwrap();
+
+        let headings = get_headings(&conn, 1).unwrap();
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].text, "Deep Work");
+        assert_eq!(headings[1].level, 2);
+
+        assert!(get_headings(&conn, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_heading_ref_matches() {
+        let headings = vec![heading(1, "Key Principles", 0), heading(2, "Notes", 10)];
+
+        assert!(heading_ref_matches(&headings, "Key Principles"));
+        assert!(heading_ref_matches(&headings, "key principles"));
+        assert!(heading_ref_matches(&headings, "key-principles"));
+        assert!(heading_ref_matches(&headings, "Key Principles#Notes"));
+        assert!(!heading_ref_matches(&headings, "Principles"));
+    }
+
+    #[test]
+    fn test_suggest_headings() {
+        let headings = vec![
+            heading(1, "Overview", 0),
+            heading(2, "Key Principles", 10),
+            heading(2, "Key Practices", 20),
+            heading(2, "Sources", 30),
+        ];
+
+        let suggestions = suggest_headings(&headings, "Key Principle");
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0], "Key Principles");
+        assert_eq!(suggestions[1], "Key Practices");
+    }
+}
@@ -0,0 +1,333 @@
+// Keyword and vector results fused by reciprocal rank
+use super::search::highlight_ranges;
+use super::{search_chunks, semantic_search, SearchError, SearchOptions};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Weights of the two rankings in reciprocal-rank fusion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HybridWeights {
+    /// Weight of the BM25 full-text ranking
+    pub keyword: f64,
+    /// Weight of the embedding similarity ranking
+    pub semantic: f64,
+    /// Rank offset `k` in `weight / (k + rank)`; larger values flatten the
+    /// advantage of the top ranks
+    pub rrf_k: f64,
+}
+
+impl Default for HybridWeights {
+    fn default() -> Self {
+        Self {
+            keyword: 1.0,
+            semantic: 1.0,
+            rrf_k: 60.0,
+        }
+    }
+}
+
+impl HybridWeights {
+    /// Reject weights that are negative or not finite, both weights being 0,
+    /// and a negative or non-finite `rrf_k`
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, weight) in [("keyword", self.keyword), ("semantic", self.semantic)] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!(
+                    "Invalid {name} weight {weight}: weights must be finite and not negative"
+                ));
+            }
+        }
+        if self.keyword == 0.0 && self.semantic == 0.0 {
+            return Err(
+                "The keyword and semantic weights are both 0; at least one must be positive"
+                    .to_string(),
+            );
+        }
+        if !self.rrf_k.is_finite() || self.rrf_k < 0.0 {
+            return Err(format!(
+                "Invalid hybrid_rrf_k {}: it must be finite and not negative",
+                self.rrf_k
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A chunk's place in one ranking and what it adds to the fused score
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankContribution {
+    /// 1-based position in the ranking
+    pub rank: usize,
+    /// BM25 rank (lower is better) or cosine similarity
+    pub value: f64,
+    pub weight: f64,
+    /// `weight / (rrf_k + rank)`
+    pub score: f64,
+}
+
+/// A chunk found by either ranking
+#[derive(Debug, Clone)]
+pub struct HybridResult {
+    pub chunk_id: i64,
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub heading_path: Option<String>,
+    pub chunk_text: String,
+    /// Sum of the contributions
+    pub score: f64,
+    pub keyword: Option<RankContribution>,
+    pub semantic: Option<RankContribution>,
+    /// Full-text snippet; empty for chunks only found by similarity
+    pub snippet: String,
+    /// Byte ranges of matched terms in `snippet`
+    pub snippet_matches: Vec<(usize, usize)>,
+}
+
+impl HybridResult {
+    /// Snippet with each match wrapped in `start` and `end`
+    pub fn highlighted_snippet(&self, start: &str, end: &str) -> String {
+        highlight_ranges(&self.snippet, &self.snippet_matches, start, end)
+    }
+}
+
+/// Chunks ranked in each list before fusion, so that a chunk just outside
+/// `limit` in both lists can still make the fused top
+fn candidate_count(limit: usize) -> usize {
+    limit.saturating_mul(5).max(50)
+}
+
+/// Fuse the BM25 ranking of `query` with the similarity ranking of
+/// `query_vector` among `model` embeddings. A chunk scores
+/// `weight / (rrf_k + rank)` for each list it appears in, so chunks found by
+/// both rise to the top. Chunks with no positive similarity are not counted
+/// as semantic matches. `options` filters and limits the fused results; a
+/// zero weight leaves its list out.
+pub fn hybrid_search(
+    conn: &Connection,
+    query: &str,
+    model: &str,
+    query_vector: &[f32],
+    options: &SearchOptions,
+    weights: &HybridWeights,
+) -> Result<Vec<HybridResult>, SearchError> {
+    let candidates = SearchOptions {
+        limit: candidate_count(options.limit),
+        ..options.clone()
+    };
+    let contribution = |weight: f64, idx: usize, value: f64| RankContribution {
+        rank: idx + 1,
+        value,
+        weight,
+        score: weight / (weights.rrf_k + (idx + 1) as f64),
+    };
+
+    let mut fused: HashMap<i64, HybridResult> = HashMap::new();
+    if weights.keyword > 0.0 {
+        for (idx, hit) in search_chunks(conn, query, &candidates)?
+            .into_iter()
+            .enumerate()
+        {
+            let keyword = contribution(weights.keyword, idx, f64::from(hit.rank));
+            fused.insert(
+                hit.chunk_id,
+                HybridResult {
+                    chunk_id: hit.chunk_id,
+                    note_id: hit.note_id,
+                    note_path: hit.note_path,
+                    note_title: hit.note_title,
+                    heading_path: hit.heading_path,
+                    chunk_text: hit.chunk_text,
+                    score: keyword.score,
+                    keyword: Some(keyword),
+                    semantic: None,
+                    snippet: hit.snippet,
+                    snippet_matches: hit.snippet_matches,
+                },
+            );
+        }
+    }
+    if weights.semantic > 0.0 {
+        for (idx, hit) in semantic_search(conn, model, query_vector, &candidates)?
+            .into_iter()
+            .filter(|hit| hit.score > 0.0)
+            .enumerate()
+        {
+            let semantic = contribution(weights.semantic, idx, hit.score);
+            let entry = fused.entry(hit.chunk_id).or_insert_with(|| HybridResult {
+                chunk_id: hit.chunk_id,
+                note_id: hit.note_id,
+                note_path: hit.note_path,
+                note_title: hit.note_title,
+                heading_path: hit.heading_path,
+                chunk_text: hit.chunk_text,
+                score: 0.0,
+                keyword: None,
+                semantic: None,
+                snippet: String::new(),
+                snippet_matches: Vec::new(),
+            });
+            entry.score += semantic.score;
+            entry.semantic = Some(semantic);
+        }
+    }
+
+    let mut results: Vec<HybridResult> = fused.into_values().collect();
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.chunk_id.cmp(&b.chunk_id))
+    });
+    results.truncate(options.limit);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             CREATE TABLE chunks (id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT, byte_offset INTEGER);
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id);
+             CREATE TABLE embeddings (chunk_id INTEGER PRIMARY KEY, model TEXT, hash TEXT, dimensions INTEGER, vector BLOB);
+             INSERT INTO notes VALUES (1, 'tickets.md', 'Tickets', 0), (2, 'focus.md', 'Focus', 0), (3, 'work/deep.md', 'Deep', 0);
+             INSERT INTO tags (note_id, tag, display) VALUES (3, 'work', 'work');
+             INSERT INTO chunks VALUES
+                (1, 1, NULL, 'Fixed OPS-1234 in the deploy script', 0),
+                (2, 2, NULL, 'Staying focused takes practice', 0),
+                (3, 3, NULL, 'OPS-1234 follow-up: OPS-1234 needs concentration', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+        let vectors: [(i64, [f32; 2]); 3] = [(1, [0.0, 1.0]), (2, [1.0, 0.0]), (3, [0.8, 0.6])];
+        for (chunk_id, vector) in vectors {
+            let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+            conn.execute(
+                "INSERT INTO embeddings VALUES (?1, 'm', 'h', 2, ?2)",
+                rusqlite::params![chunk_id, blob],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn test_hybrid_search_fuses_rankings() {
+        let conn = setup_db();
+        let options = SearchOptions::default();
+
+        let results = hybrid_search(
+            &conn,
+            "OPS-1234",
+            "m",
+            &[1.0, 0.0],
+            &options,
+            &HybridWeights::default(),
+        )
+        .unwrap();
+        let ids: Vec<i64> = results.iter().map(|r| r.chunk_id).collect();
+        // Chunk 3 is in both lists, chunk 2 only similar and chunk 1 only matches
+        assert_eq!(ids, vec![3, 2, 1]);
+        let top = &results[0];
+        let keyword = top.keyword.as_ref().unwrap();
+        let semantic = top.semantic.as_ref().unwrap();
+        assert_eq!(semantic.rank, 2);
+        assert!((top.score - (keyword.score + semantic.score)).abs() < 1e-12);
+        assert!((semantic.score - 1.0 / 62.0).abs() < 1e-12);
+        assert!(!top.snippet.is_empty());
+
+        let similar = &results[1];
+        assert!(similar.keyword.is_none());
+        assert!(similar.snippet.is_empty());
+        assert_eq!(similar.semantic.as_ref().unwrap().rank, 1);
+        assert!(results[2].semantic.is_none());
+    }
+
+    #[test]
+    fn test_hybrid_weights_validate() {
+        assert!(HybridWeights::default().validate().is_ok());
+        let keyword_only = HybridWeights {
+            semantic: 0.0,
+            rrf_k: 0.0,
+            ..Default::default()
+        };
+        assert!(keyword_only.validate().is_ok());
+
+        let invalid = [
+            (-1.0, 1.0, 60.0, "keyword weight"),
+            (1.0, f64::NAN, 60.0, "semantic weight"),
+            (f64::INFINITY, 1.0, 60.0, "keyword weight"),
+            (0.0, 0.0, 60.0, "both 0"),
+            (1.0, 1.0, -1.0, "hybrid_rrf_k"),
+            (1.0, 1.0, f64::INFINITY, "hybrid_rrf_k"),
+        ];
+        for (keyword, semantic, rrf_k, message) in invalid {
+            let weights = HybridWeights {
+                keyword,
+                semantic,
+                rrf_k,
+            };
+            let err = weights.validate().unwrap_err();
+            assert!(err.contains(message), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_hybrid_search_weights_and_filters() {
+        let conn = setup_db();
+
+        let keyword_only = HybridWeights {
+            semantic: 0.0,
+            ..Default::default()
+        };
+        let results = hybrid_search(
+            &conn,
+            "OPS-1234",
+            "m",
+            &[1.0, 0.0],
+            &SearchOptions::default(),
+            &keyword_only,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.semantic.is_none()));
+
+        let keyword_heavy = HybridWeights {
+            keyword: 3.0,
+            ..Default::default()
+        };
+        let results = hybrid_search(
+            &conn,
+            "OPS-1234",
+            "m",
+            &[1.0, 0.0],
+            &SearchOptions::default(),
+            &keyword_heavy,
+        )
+        .unwrap();
+        let ids: Vec<i64> = results.iter().map(|r| r.chunk_id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+
+        let tagged = SearchOptions {
+            tags: vec!["work".to_string()],
+            limit: 5,
+            ..Default::default()
+        };
+        let results = hybrid_search(
+            &conn,
+            "OPS-1234",
+            "m",
+            &[1.0, 0.0],
+            &tagged,
+            &HybridWeights::default(),
+        )
+        .unwrap();
+        let ids: Vec<i64> = results.iter().map(|r| r.chunk_id).collect();
+        assert_eq!(ids, vec![3]);
+        assert_eq!(results[0].semantic.as_ref().unwrap().rank, 1);
+    }
+}
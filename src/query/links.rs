@@ -1,4 +1,9 @@
-use rusqlite::{Connection, OptionalExtension, Result};
+use rusqlite::{Connection, Result};
+use std::collections::hash_map::{Entry, HashMap};
+
+use super::blocks::{block_ref_matches, get_block_ids_by_note_id, suggest_block_ids};
+use super::headings::{get_headings, heading_ref_matches, suggest_headings};
+use super::{NoteResolution, NoteResolver};
 
 #[derive(Debug, Clone)]
 pub struct LinkResult {
@@ -28,13 +33,15 @@ pub struct BrokenLinkResult {
     pub src_title: String,
     pub raw_link: String,
     pub target: String,
-    pub status: String,          // "unresolved" or "ambiguous"
-    pub candidates: Vec<String>, // list of candidate note paths
+    pub status: String, // "unresolved", "ambiguous", "missing_heading" or "missing_block"
+    pub candidates: Vec<String>, // candidate note paths, or suggested headings / block ids
 }
 
-/// Get all broken links (unresolved and ambiguous)
+/// Get all broken links (unresolved, ambiguous, missing headings and blocks)
 /// Unresolved: links where dst_note_id is NULL (target doesn't exist)
 /// Ambiguous: links where dst_text could match multiple notes
+/// Missing heading: links whose heading_ref matches no heading in the target note
+/// Missing block: links whose block_ref matches no `^id` in the target note
 pub fn diagnose_broken_links(conn: &Connection) -> Result<Vec<BrokenLinkResult>> {
     let mut results = Vec::new();
 
@@ -51,6 +58,7 @@ pub fn diagnose_broken_links(conn: &Connection) -> Result<Vec<BrokenLinkResult>>
          ORDER BY src.path, l.dst_text",
     )?;
 
+    let resolver = NoteResolver::load(conn)?;
     let unresolved_iter = stmt.query_map([], |row| {
         Ok(BrokenLinkResult {
             src_path: row.get(0)?,
@@ -63,7 +71,13 @@ pub fn diagnose_broken_links(conn: &Connection) -> Result<Vec<BrokenLinkResult>>
     })?;
 
     for result in unresolved_iter {
-        results.push(result?);
+        let mut result = result?;
+        // Links matching several notes are left unresolved at index time
+        if let NoteResolution::Ambiguous(candidates) = resolver.resolve_exact(&result.target) {
+            result.status = "ambiguous".to_string();
+            result.candidates = candidates.into_iter().map(|c| c.path).collect();
+        }
+        results.push(result);
     }
 
     // Now find ambiguous links - links where dst_text could match multiple notes
@@ -74,10 +88,14 @@ pub fn diagnose_broken_links(conn: &Connection) -> Result<Vec<BrokenLinkResult>>
             src.path as src_path,
             src.title as src_title,
             l.alias as raw_link,
-            l.dst_text as target
+            l.dst_text as target,
+            l.dst_note_id,
+            l.heading_ref,
+            l.block_ref
          FROM links l
          JOIN notes src ON l.src_note_id = src.id
-         WHERE l.dst_note_id IS NOT NULL",
+         WHERE l.dst_note_id IS NOT NULL
+         ORDER BY src.path, l.dst_text",
     )?;
 
     let links_iter = stmt.query_map([], |row| {
@@ -87,31 +105,69 @@ pub fn diagnose_broken_links(conn: &Connection) -> Result<Vec<BrokenLinkResult>>
             row.get::<_, String>(2)?,
             row.get::<_, Option<String>>(3)?.unwrap_or_default(),
             row.get::<_, String>(4)?,
+            row.get::<_, i64>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
         ))
     })?;
 
     // For each resolved link, check if the target is ambiguous
-    let mut stmt_candidates =
-        conn.prepare("SELECT path FROM notes WHERE path = ?1 OR title = ?1 LIMIT 2")?;
+    let mut headings_by_note = HashMap::new();
+    let mut blocks_by_note = HashMap::new();
 
     for link_result in links_iter {
-        let (_link_id, src_path, src_title, raw_link, target) = link_result?;
+        let (_link_id, src_path, src_title, raw_link, target, dst_note_id, heading_ref, block_ref) =
+            link_result?;
 
-        // Check how many notes match this target
-        let candidates: Vec<String> = stmt_candidates
-            .query_map([&target], |row| row.get(0))?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        // If there are multiple matches, mark as ambiguous
-        if candidates.len() > 1 {
+        // If several notes match at the same resolution step, mark as ambiguous
+        if let NoteResolution::Ambiguous(candidates) = resolver.resolve_exact(&target) {
             results.push(BrokenLinkResult {
                 src_path,
                 src_title,
                 raw_link,
                 target,
                 status: "ambiguous".to_string(),
-                candidates,
+                candidates: candidates.into_iter().map(|c| c.path).collect(),
+            });
+            continue;
+        }
+
+        if let Some(block_ref) = block_ref.filter(|b| !b.trim().is_empty()) {
+            let block_ids = match blocks_by_note.entry(dst_note_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(get_block_ids_by_note_id(conn, dst_note_id)?),
+            };
+
+            if !block_ref_matches(block_ids, &block_ref) {
+                results.push(BrokenLinkResult {
+                    src_path,
+                    src_title,
+                    raw_link,
+                    target: format!("{target}#^{block_ref}"),
+                    status: "missing_block".to_string(),
+                    candidates: suggest_block_ids(block_ids, &block_ref),
+                });
+            }
+            continue;
+        }
+
+        let Some(heading_ref) = heading_ref.filter(|h| !h.trim().is_empty()) else {
+            continue;
+        };
+
+        let headings = match headings_by_note.entry(dst_note_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(get_headings(conn, dst_note_id)?),
+        };
+
+        if !heading_ref_matches(headings, &heading_ref) {
+            results.push(BrokenLinkResult {
+                src_path,
+                src_title,
+                raw_link,
+                target: format!("{target}#{heading_ref}"),
+                status: "missing_heading".to_string(),
+                candidates: suggest_headings(headings, &heading_ref),
             });
         }
     }
@@ -216,20 +272,7 @@ pub fn get_dead_ends(
 }
 
 /// Get all notes that link to a given note (backlinks)
-pub fn get_backlinks(conn: &Connection, note_path: &str) -> Result<Vec<LinkResult>> {
-    // First find the target note
-    let target_note_id: Option<i64> = conn
-        .query_row("SELECT id FROM notes WHERE path = ?1", [note_path], |row| {
-            row.get(0)
-        })
-        .optional()?;
-
-    if target_note_id.is_none() {
-        return Ok(Vec::new());
-    }
-
-    let target_note_id = target_note_id.unwrap();
-
+pub fn get_backlinks(conn: &Connection, target_note_id: i64) -> Result<Vec<LinkResult>> {
     // Get all links pointing to this note
     let mut stmt = conn.prepare(
         "SELECT 
@@ -267,20 +310,7 @@ pub fn get_backlinks(conn: &Connection, note_path: &str) -> Result<Vec<LinkResul
 }
 
 /// Get all notes that a given note links to (forward links)
-pub fn get_forward_links(conn: &Connection, note_path: &str) -> Result<Vec<LinkResult>> {
-    // First find the source note
-    let src_note_id: Option<i64> = conn
-        .query_row("SELECT id FROM notes WHERE path = ?1", [note_path], |row| {
-            row.get(0)
-        })
-        .optional()?;
-
-    if src_note_id.is_none() {
-        return Ok(Vec::new());
-    }
-
-    let src_note_id = src_note_id.unwrap();
-
+pub fn get_forward_links(conn: &Connection, src_note_id: i64) -> Result<Vec<LinkResult>> {
     // Get all links from this note
     let mut stmt = conn.prepare(
         "SELECT 
@@ -358,18 +388,39 @@ pub fn get_unresolved_links(conn: &Connection) -> Result<Vec<LinkResult>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::resolve_note;
     use rusqlite::Connection;
 
+    fn note_id(conn: &Connection, note: &str) -> i64 {
+        match resolve_note(conn, note).unwrap() {
+            NoteResolution::Found { note, .. } => note.id,
+            other => panic!("{note} did not resolve: {other:?}"),
+        }
+    }
+
     fn setup_test_db(conn: &Connection) {
         conn.execute(
             "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT)",
             [],
         )
         .unwrap();
+        conn.execute(
+            "CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT)",
+            [],
+        )
+        .unwrap();
         conn.execute(
             "CREATE TABLE links (id INTEGER PRIMARY KEY, src_note_id INTEGER, dst_note_id INTEGER, dst_text TEXT, is_embed INTEGER, alias TEXT, heading_ref TEXT, block_ref TEXT)",
             [],
         ).unwrap();
+        conn.execute(
+            "CREATE TABLE headings (id INTEGER PRIMARY KEY, note_id INTEGER, level INTEGER, text TEXT, slug TEXT, line INTEGER, byte_offset INTEGER)",
+            [],
+        ).unwrap();
+        conn.execute(
+            "CREATE TABLE blocks (id INTEGER PRIMARY KEY, note_id INTEGER, block_id TEXT, text TEXT, line INTEGER, byte_offset INTEGER)",
+            [],
+        ).unwrap();
 
         // Insert test notes
         conn.execute(
@@ -433,17 +484,38 @@ mod tests {
             [],
         ).unwrap();
 
-        let backlinks = get_backlinks(&conn, "test2.md").unwrap();
+        let backlinks = get_backlinks(&conn, 2).unwrap();
         assert_eq!(backlinks.len(), 1);
         assert_eq!(backlinks[0].note_path, "test1.md");
     }
 
+    #[test]
+    fn test_get_backlinks_by_title_and_alias() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_db(&conn);
+        conn.execute(
+            "INSERT INTO aliases (note_id, alias) VALUES (2, 'Second')",
+            [],
+        )
+        .unwrap();
+
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed) VALUES (1, 2, 'Second', 0)",
+            [],
+        ).unwrap();
+
+        for note in ["Test 2", "Second", "test2"] {
+            let backlinks = get_backlinks(&conn, note_id(&conn, note)).unwrap();
+            assert_eq!(backlinks.len(), 1);
+        }
+    }
+
     #[test]
     fn test_get_backlinks_no_results() {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let backlinks = get_backlinks(&conn, "test2.md").unwrap();
+        let backlinks = get_backlinks(&conn, 2).unwrap();
         assert!(backlinks.is_empty());
     }
 
@@ -458,7 +530,7 @@ mod tests {
             [],
         ).unwrap();
 
-        let forward_links = get_forward_links(&conn, "test1.md").unwrap();
+        let forward_links = get_forward_links(&conn, note_id(&conn, "test1.md")).unwrap();
         assert_eq!(forward_links.len(), 1);
     }
 
@@ -473,7 +545,7 @@ mod tests {
             [],
         ).unwrap();
 
-        let forward_links = get_forward_links(&conn, "test1.md").unwrap();
+        let forward_links = get_forward_links(&conn, 1).unwrap();
         assert_eq!(forward_links.len(), 1);
         assert_eq!(forward_links[0].note_id, -1); // Unresolved
     }
@@ -559,6 +631,101 @@ mod tests {
         assert!(broken.is_empty());
     }
 
+    #[test]
+    fn test_diagnose_broken_links_alias_target() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_db(&conn);
+        conn.execute(
+            "INSERT INTO aliases (note_id, alias) VALUES (2, 'Second')",
+            [],
+        )
+        .unwrap();
+
+        // A link resolved through an alias is not broken
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed) VALUES (1, 2, 'Second', 0)",
+            [],
+        ).unwrap();
+        assert!(diagnose_broken_links(&conn).unwrap().is_empty());
+
+        // An alias shared by two notes makes the link ambiguous
+        conn.execute(
+            "INSERT INTO aliases (note_id, alias) VALUES (2, 'Shared'), (3, 'Shared')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed) VALUES (1, 2, 'Shared', 0)",
+            [],
+        ).unwrap();
+        let broken = diagnose_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].status, "ambiguous");
+        assert_eq!(broken[0].candidates, vec!["test2.md", "test3.md"]);
+
+        // Indexing leaves ambiguous links unresolved; they are still reported as such
+        conn.execute("DELETE FROM links", []).unwrap();
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed) VALUES (1, NULL, 'Shared', 0)",
+            [],
+        ).unwrap();
+        let broken = diagnose_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].status, "ambiguous");
+        assert_eq!(broken[0].candidates, vec!["test2.md", "test3.md"]);
+    }
+
+    #[test]
+    fn test_diagnose_broken_links_missing_heading() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_db(&conn);
+        conn.execute(
+            "INSERT INTO headings (note_id, level, text, slug, line, byte_offset) VALUES
+             (2, 1, 'Overview', 'overview', 1, 0),
+             (2, 2, 'Key Principles', 'key-principles', 5, 30)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed, heading_ref) VALUES
+             (1, 2, 'test2', 0, 'key-principles'),
+             (1, 2, 'test2', 0, 'Key Principle')",
+            [],
+        )
+        .unwrap();
+
+        let broken = diagnose_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].status, "missing_heading");
+        assert_eq!(broken[0].target, "test2#Key Principle");
+        assert_eq!(broken[0].candidates[0], "Key Principles");
+    }
+
+    #[test]
+    fn test_diagnose_broken_links_missing_block() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_test_db(&conn);
+        conn.execute(
+            "INSERT INTO blocks (note_id, block_id, text, line, byte_offset) VALUES
+             (2, 'quote-1', 'A quote', 3, 20)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO links (src_note_id, dst_note_id, dst_text, is_embed, block_ref) VALUES
+             (1, 2, 'test2', 1, 'quote-1'),
+             (1, 2, 'test2', 1, 'quote-2')",
+            [],
+        )
+        .unwrap();
+
+        let broken = diagnose_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].status, "missing_block");
+        assert_eq!(broken[0].target, "test2#^quote-2");
+        assert_eq!(broken[0].candidates, vec!["quote-1"]);
+    }
+
     #[test]
     fn test_diagnose_broken_links_empty() {
         let conn = Connection::open_in_memory().unwrap();
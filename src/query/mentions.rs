@@ -0,0 +1,327 @@
+use rusqlite::{Connection, OptionalExtension, Result};
+use std::collections::HashSet;
+
+use crate::parser::{extract_markdown_links, extract_wikilinks};
+
+/// A plain-text reference to a note that is not linked
+#[derive(Debug, Clone)]
+pub struct MentionResult {
+    pub chunk_id: i64,
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub heading_path: Option<String>,
+    pub matched_term: String,
+    pub context: String,
+}
+
+struct TargetNote {
+    id: i64,
+    path: String,
+    title: String,
+    aliases: Vec<String>,
+}
+
+/// Find unlinked mentions of a note's title or aliases in other notes.
+/// Chunks that already link to the note, code blocks, inline code and link
+/// text are ignored.
+pub fn find_unlinked_mentions(
+    conn: &Connection,
+    note_id: i64,
+    limit: usize,
+) -> Result<Vec<MentionResult>> {
+    let target = match find_target_note(conn, note_id)? {
+        Some(target) => target,
+        None => return Ok(Vec::new()),
+    };
+
+    let terms = mention_terms(&target);
+    let link_keys = link_keys(&target);
+
+    let mut stmt = conn.prepare(
+        "SELECT c.id, n.id, n.path, n.title, c.heading_path, c.text
+         FROM fts_chunks fc
+         JOIN chunks c ON fc.rowid = c.id
+         JOIN notes n ON c.note_id = n.id
+         WHERE fts_chunks MATCH ?1 AND n.id != ?2
+         ORDER BY n.path COLLATE NOCASE, c.byte_offset, c.id",
+    )?;
+
+    let mut seen_chunks = HashSet::new();
+    let mut results = Vec::new();
+
+    for term in &terms {
+        let fts_query = format!("text : \"{}\"", term.replace('"', "\"\""));
+        let rows = stmt.query_map(rusqlite::params![fts_query, target.id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (chunk_id, note_id, note_path, note_title, heading_path, text) = row?;
+            if seen_chunks.contains(&chunk_id) || links_to_target(&text, &link_keys) {
+                continue;
+            }
+
+            if let Some(context) = find_mention(&strip_non_prose(&text), term) {
+                seen_chunks.insert(chunk_id);
+                results.push(MentionResult {
+                    chunk_id,
+                    note_id,
+                    note_path,
+                    note_title,
+                    heading_path,
+                    matched_term: term.clone(),
+                    context,
+                });
+            }
+        }
+    }
+
+    results.sort_by(|a, b| {
+        a.note_path
+            .to_lowercase()
+            .cmp(&b.note_path.to_lowercase())
+            .then(a.chunk_id.cmp(&b.chunk_id))
+    });
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+fn find_target_note(conn: &Connection, id: i64) -> Result<Option<TargetNote>> {
+    let found = conn
+        .query_row("SELECT path, title FROM notes WHERE id = ?1", [id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+    let Some((path, title)) = found else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare("SELECT alias FROM aliases WHERE note_id = ?1 ORDER BY alias")?;
+    let aliases = stmt
+        .query_map([id], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+
+    Ok(Some(TargetNote {
+        id,
+        path,
+        title,
+        aliases,
+    }))
+}
+
+fn note_stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.strip_suffix(".md").unwrap_or(name)
+}
+
+/// Title, file name and aliases, deduplicated case-insensitively
+fn mention_terms(target: &TargetNote) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let candidates = std::iter::once(target.title.as_str())
+        .chain(std::iter::once(note_stem(&target.path)))
+        .chain(target.aliases.iter().map(String::as_str));
+
+    for candidate in candidates {
+        let candidate = candidate.trim();
+        if !candidate.is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(candidate)) {
+            terms.push(candidate.to_string());
+        }
+    }
+
+    terms
+}
+
+/// Lowercased identifiers a link may use to point at the target
+fn link_keys(target: &TargetNote) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    keys.insert(target.path.trim_end_matches(".md").to_lowercase());
+    keys.insert(note_stem(&target.path).to_lowercase());
+    keys.insert(target.title.to_lowercase());
+    for alias in &target.aliases {
+        keys.insert(alias.to_lowercase());
+    }
+    keys
+}
+
+fn links_to_target(text: &str, link_keys: &HashSet<String>) -> bool {
+    extract_wikilinks(text)
+        .into_iter()
+        .chain(extract_markdown_links(text))
+        .any(|link| {
+            let key = link.text.to_lowercase();
+            let stem = key.rsplit('/').next().unwrap_or(&key).to_string();
+            link_keys.contains(&key) || link_keys.contains(&stem)
+        })
+}
+
+/// Blank out frontmatter, fenced code, inline code and link syntax so only
+/// prose is searched for mentions.
+fn strip_non_prose(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    let mut in_frontmatter = text.starts_with("---");
+
+    for (idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        if in_frontmatter {
+            if idx > 0 && line.trim() == "---" {
+                in_frontmatter = false;
+            }
+            out.push('\n');
+            continue;
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            out.push('\n');
+            continue;
+        }
+
+        if !in_fence {
+            out.push_str(&strip_inline_spans(line));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+fn strip_inline_spans(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+
+        let skip = if let Some(code) = rest.strip_prefix('`') {
+            code.find('`').map(|end| end + 2)
+        } else if rest.starts_with("[[") {
+            rest.find("]]").map(|end| end + 2)
+        } else if rest.starts_with('[') {
+            rest.find(']')
+                .filter(|&close| rest[close..].starts_with("]("))
+                .and_then(|close| rest[close..].find(')').map(|end| close + end + 1))
+        } else {
+            None
+        };
+
+        if let Some(len) = skip {
+            out.push(' ');
+            i += len;
+            continue;
+        }
+
+        let ch = rest.chars().next().unwrap_or(' ');
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+
+    out
+}
+
+/// Return the trimmed line containing a whole-word, case-insensitive match
+fn find_mention(text: &str, term: &str) -> Option<String> {
+    let term_lower = term.to_lowercase();
+
+    text.lines()
+        .find(|line| contains_word(&line.to_lowercase(), &term_lower))
+        .map(|line| line.trim().to_string())
+}
+
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    haystack.match_indices(needle).any(|(pos, _)| {
+        let before = haystack[..pos].chars().next_back();
+        let after = haystack[pos + needle.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES (1, 'Deep Work.md', 'Deep Work');
+             INSERT INTO notes VALUES (2, 'Projects.md', 'Projects');
+             INSERT INTO notes VALUES (3, 'Ideas.md', 'Ideas');
+             INSERT INTO aliases VALUES (1, 1, 'Flow State');
+             INSERT INTO chunks VALUES (1, 1, NULL, 'Deep work is focus.', 0);
+             INSERT INTO chunks VALUES (2, 2, '# Projects', 'I plan deep work blocks daily.', 0);
+             INSERT INTO chunks VALUES (3, 2, '# Later', 'Already linked: [[Deep Work]] here.', 40);
+             INSERT INTO chunks VALUES (4, 3, NULL, 'Reaching a flow state takes time.', 0);
+             INSERT INTO chunks VALUES (5, 3, NULL, '```\nDeep Work in code\n```\nUse `deep work`.', 50);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_find_unlinked_mentions() {
+        let conn = setup_db();
+        let results = find_unlinked_mentions(&conn, 1, 10).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].note_path, "Ideas.md");
+        assert_eq!(results[0].matched_term, "Flow State");
+        assert_eq!(results[0].context, "Reaching a flow state takes time.");
+        assert_eq!(results[1].note_path, "Projects.md");
+        assert_eq!(results[1].chunk_id, 2);
+    }
+
+    #[test]
+    fn test_find_unlinked_mentions_limit() {
+        let conn = setup_db();
+        let results = find_unlinked_mentions(&conn, 1, 1).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_find_unlinked_mentions_unknown_note() {
+        let conn = setup_db();
+        let results = find_unlinked_mentions(&conn, 99, 10).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_strip_non_prose() {
+        let text = "---\naliases: [X]\n---\nkeep `code` [[Link]] [md](a.md)\n```\nhidden\n```";
+        let stripped = strip_non_prose(text);
+        assert!(stripped.contains("keep"));
+        assert!(!stripped.contains("aliases"));
+        assert!(!stripped.contains("code"));
+        assert!(!stripped.contains("Link"));
+        assert!(!stripped.contains("md"));
+        assert!(!stripped.contains("hidden"));
+    }
+
+    #[test]
+    fn test_contains_word_boundaries() {
+        assert!(contains_word("a deep work day", "deep work"));
+        assert!(!contains_word("deep workers", "deep work"));
+        assert!(!contains_word("undeep work", "deep work"));
+    }
+}
@@ -0,0 +1,432 @@
+use rusqlite::{Connection, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parser::{field_value_type, normalize_field_key};
+
+/// Filter operators; the leftmost match wins and two-character operators win ties
+const OPERATORS: [(&str, PropertyOp); 7] = [
+    ("!=", PropertyOp::Ne),
+    (">=", PropertyOp::Ge),
+    ("<=", PropertyOp::Le),
+    ("=", PropertyOp::Eq),
+    (">", PropertyOp::Gt),
+    ("<", PropertyOp::Lt),
+    ("~", PropertyOp::Contains),
+];
+
+/// Comparison applied to a property value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyOp {
+    /// The note has the key, whatever its value
+    Exists,
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// Case-insensitive substring match
+    Contains,
+}
+
+impl PropertyOp {
+    fn sql(&self) -> &'static str {
+        match self {
+            PropertyOp::Gt => ">",
+            PropertyOp::Ge => ">=",
+            PropertyOp::Lt => "<",
+            PropertyOp::Le => "<=",
+            _ => "=",
+        }
+    }
+}
+
+/// A typed filter over frontmatter properties and inline fields, written
+/// `key`, `key=value`, `key!=value`, `key>value`, `key>=value`, `key<value`,
+/// `key<=value` or `key~text`.
+///
+/// The value decides the comparison: numbers compare numerically, dates
+/// (`YYYY-MM-DD`) chronologically, `true`/`false` as booleans and anything
+/// else as case-insensitive text, where `=` also matches one item of a list
+/// value such as `[a, b]`. A note matches when any of its values for the key
+/// matches; `!=` matches notes without a matching value, including notes
+/// without the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyFilter {
+    pub key: String,
+    pub op: PropertyOp,
+    pub value: String,
+}
+
+impl PropertyFilter {
+    /// Parse a filter expression such as `rating>=4` or `status=done`
+    pub fn parse(expr: &str) -> std::result::Result<Self, String> {
+        let expr = expr.trim();
+        let found = OPERATORS
+            .iter()
+            .filter_map(|(token, op)| expr.find(token).map(|pos| (pos, *token, *op)))
+            .min_by_key(|(pos, token, _)| (*pos, std::cmp::Reverse(token.len())));
+
+        let (key, op, value) = match found {
+            Some((pos, token, op)) => (&expr[..pos], op, expr[pos + token.len()..].trim()),
+            None => (expr, PropertyOp::Exists, ""),
+        };
+
+        let key = normalize_field_key(key);
+        if key.is_empty() {
+            return Err(format!(
+                "invalid property filter '{expr}': expected KEY, KEY=VALUE or KEY<op>VALUE \
+                 with one of = != > >= < <= ~"
+            ));
+        }
+        if op != PropertyOp::Exists && value.is_empty() {
+            return Err(format!("invalid property filter '{expr}': missing value"));
+        }
+        if matches!(
+            op,
+            PropertyOp::Gt | PropertyOp::Ge | PropertyOp::Lt | PropertyOp::Le
+        ) && !matches!(field_value_type(value), "number" | "date" | "text")
+        {
+            return Err(format!(
+                "invalid property filter '{expr}': '{value}' cannot be ordered"
+            ));
+        }
+
+        Ok(PropertyFilter {
+            key,
+            op,
+            value: value.trim_matches('"').to_string(),
+        })
+    }
+
+    /// SQL condition on the note aliased `note_alias`, binding values as
+    /// numbered parameters appended to `params`
+    pub(crate) fn to_sql(&self, note_alias: &str, params: &mut Vec<String>) -> String {
+        params.push(self.key.clone());
+        let key_param = params.len();
+        let exists = |condition: &str| {
+            format!(
+                "EXISTS (SELECT 1 FROM fields f WHERE f.note_id = {note_alias}.id \
+                 AND f.key = ?{key_param}{condition})"
+            )
+        };
+
+        if self.op == PropertyOp::Exists {
+            return exists("");
+        }
+
+        params.push(self.value.clone());
+        let value_param = params.len();
+        let condition = match (self.op, field_value_type(&self.value)) {
+            (PropertyOp::Contains, _) => {
+                format!("instr(lower(f.value), lower(?{value_param})) > 0")
+            }
+            (_, "number") => format!(
+                "f.number IS NOT NULL AND f.number {} CAST(?{value_param} AS REAL)",
+                self.op.sql()
+            ),
+            (_, "date") => format!(
+                "f.value_type = 'date' AND substr(f.value, 1, 10) {} substr(?{value_param}, 1, 10)",
+                self.op.sql()
+            ),
+            (PropertyOp::Eq | PropertyOp::Ne, "bool") => {
+                format!("lower(f.value) = lower(?{value_param})")
+            }
+            (PropertyOp::Eq | PropertyOp::Ne, _) => format!(
+                "(f.value = ?{value_param} COLLATE NOCASE \
+                 OR instr(',' || replace(replace(replace(replace(lower(f.value), \
+                 '[', ''), ']', ''), '\"', ''), ', ', ',') || ',', \
+                 ',' || lower(?{value_param}) || ',') > 0)"
+            ),
+            _ => format!("f.value {} ?{value_param} COLLATE NOCASE", self.op.sql()),
+        };
+
+        let sql = exists(&format!(" AND {condition}"));
+        if self.op == PropertyOp::Ne {
+            format!("NOT {sql}")
+        } else {
+            sql
+        }
+    }
+}
+
+impl FromStr for PropertyFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for PropertyFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            PropertyOp::Exists => return write!(f, "{}", self.key),
+            PropertyOp::Eq => "=",
+            PropertyOp::Ne => "!=",
+            PropertyOp::Gt => ">",
+            PropertyOp::Ge => ">=",
+            PropertyOp::Lt => "<",
+            PropertyOp::Le => "<=",
+            PropertyOp::Contains => "~",
+        };
+        write!(f, "{}{}{}", self.key, op, self.value)
+    }
+}
+
+/// A frontmatter property or inline field of a note
+#[derive(Debug, Clone)]
+pub struct FieldResult {
+    pub key: String,
+    pub value: String,
+    pub value_type: String,
+    pub source: String,
+    pub line: i64,
+}
+
+/// A note matching every property filter, with the values of the filtered keys
+#[derive(Debug, Clone)]
+pub struct PropertyMatch {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub fields: Vec<FieldResult>,
+}
+
+/// A property key in use across the vault
+#[derive(Debug, Clone)]
+pub struct PropertyKeyResult {
+    pub key: String,
+    pub note_count: i64,
+    pub value_types: Vec<String>,
+}
+
+/// Find notes matching all filters, ordered by path
+pub fn find_notes_by_properties(
+    conn: &Connection,
+    filters: &[PropertyFilter],
+    limit: usize,
+) -> Result<Vec<PropertyMatch>> {
+    let mut params: Vec<String> = Vec::new();
+    let conditions: Vec<String> = filters.iter().map(|f| f.to_sql("n", &mut params)).collect();
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let query = format!(
+        "SELECT n.id, n.path, n.title
+         FROM notes n
+         {where_clause}
+         ORDER BY n.path
+         LIMIT {limit}"
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+        Ok(PropertyMatch {
+            note_id: row.get(0)?,
+            note_path: row.get(1)?,
+            note_title: row.get(2)?,
+            fields: Vec::new(),
+        })
+    })?;
+    let mut matches = rows.collect::<Result<Vec<_>>>()?;
+
+    let keys: Vec<&str> = filters.iter().map(|f| f.key.as_str()).collect();
+    for note in &mut matches {
+        note.fields = get_fields(conn, note.note_id)?
+            .into_iter()
+            .filter(|field| keys.contains(&field.key.as_str()))
+            .collect();
+    }
+
+    Ok(matches)
+}
+
+/// All properties of a note, frontmatter first, in document order
+pub fn get_fields(conn: &Connection, note_id: i64) -> Result<Vec<FieldResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT key, value, value_type, source, line
+         FROM fields
+         WHERE note_id = ?1
+         ORDER BY byte_offset",
+    )?;
+    let results = stmt.query_map([note_id], |row| {
+        Ok(FieldResult {
+            key: row.get(0)?,
+            value: row.get(1)?,
+            value_type: row.get(2)?,
+            source: row.get(3)?,
+            line: row.get(4)?,
+        })
+    })?;
+    results.collect()
+}
+
+/// Property keys with the number of notes using them, most used first
+pub fn list_property_keys(conn: &Connection) -> Result<Vec<PropertyKeyResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT key, COUNT(DISTINCT note_id), value_type
+         FROM fields
+         GROUP BY key, value_type",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut types: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (key, _, value_type) = row?;
+        types.entry(key).or_default().push(value_type);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT key, COUNT(DISTINCT note_id) AS note_count
+         FROM fields
+         GROUP BY key
+         ORDER BY note_count DESC, key",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?;
+
+    let mut keys = Vec::new();
+    for row in rows {
+        let (key, note_count) = row?;
+        let mut value_types = types.remove(&key).unwrap_or_default();
+        value_types.sort();
+        keys.push(PropertyKeyResult {
+            key,
+            note_count,
+            value_types,
+        });
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE fields (
+                id INTEGER PRIMARY KEY, note_id INTEGER, key TEXT, value TEXT,
+                value_type TEXT, number REAL, source TEXT, line INTEGER, byte_offset INTEGER
+             );
+             INSERT INTO notes VALUES (1, 'Books/Deep Work.md', 'Deep Work');
+             INSERT INTO notes VALUES (2, 'Books/Atomic Habits.md', 'Atomic Habits');
+             INSERT INTO notes VALUES (3, 'Home.md', 'Home');
+             INSERT INTO fields VALUES
+                (1, 1, 'rating', '5', 'number', 5, 'frontmatter', 2, 4),
+                (2, 1, 'status', 'done', 'text', NULL, 'inline', 8, 90),
+                (3, 1, 'finished', '2024-02-01', 'date', NULL, 'inline', 9, 110),
+                (4, 2, 'rating', '3.5', 'number', 3.5, 'inline', 5, 40),
+                (5, 2, 'genre', '[self-help, habits]', 'text', NULL, 'frontmatter', 3, 20),
+                (6, 2, 'status', 'Reading', 'text', NULL, 'frontmatter', 4, 30),
+                (7, 3, 'pinned', 'true', 'bool', NULL, 'frontmatter', 2, 4);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn paths(conn: &Connection, filters: &[&str]) -> Vec<String> {
+        let filters: Vec<PropertyFilter> = filters
+            .iter()
+            .map(|f| PropertyFilter::parse(f).unwrap())
+            .collect();
+        find_notes_by_properties(conn, &filters, 100)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.note_path)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_property_filter() {
+        let filter = PropertyFilter::parse("Due Date >= 2024-01-01").unwrap();
+        assert_eq!(filter.key, "due-date");
+        assert_eq!(filter.op, PropertyOp::Ge);
+        assert_eq!(filter.value, "2024-01-01");
+        assert_eq!(filter.to_string(), "due-date>=2024-01-01");
+
+        assert_eq!(
+            PropertyFilter::parse("status!=done").unwrap().op,
+            PropertyOp::Ne
+        );
+        assert_eq!(
+            PropertyFilter::parse("rating").unwrap().op,
+            PropertyOp::Exists
+        );
+        assert!(PropertyFilter::parse("=done").is_err());
+        assert!(PropertyFilter::parse("rating>").is_err());
+        assert!(PropertyFilter::parse("pinned>true").is_err());
+    }
+
+    #[test]
+    fn test_find_notes_by_typed_properties() {
+        let conn = setup_db();
+
+        assert_eq!(paths(&conn, &["rating>=4"]), vec!["Books/Deep Work.md"]);
+        assert_eq!(
+            paths(&conn, &["rating<10", "status=reading"]),
+            vec!["Books/Atomic Habits.md"]
+        );
+        assert_eq!(
+            paths(&conn, &["finished>2024-01-15"]),
+            vec!["Books/Deep Work.md"]
+        );
+        assert_eq!(
+            paths(&conn, &["genre=habits"]),
+            vec!["Books/Atomic Habits.md"]
+        );
+        assert_eq!(
+            paths(&conn, &["status~read"]),
+            vec!["Books/Atomic Habits.md"]
+        );
+        assert_eq!(paths(&conn, &["pinned=TRUE"]), vec!["Home.md"]);
+        assert_eq!(
+            paths(&conn, &["status!=done"]),
+            vec!["Books/Atomic Habits.md", "Home.md"]
+        );
+        assert_eq!(
+            paths(&conn, &["rating"]),
+            vec!["Books/Atomic Habits.md", "Books/Deep Work.md"]
+        );
+    }
+
+    #[test]
+    fn test_matches_include_filtered_values() {
+        let conn = setup_db();
+        let filters = vec![PropertyFilter::parse("rating>1").unwrap()];
+        let matches = find_notes_by_properties(&conn, &filters, 100).unwrap();
+
+        assert_eq!(matches[0].fields.len(), 1);
+        assert_eq!(matches[0].fields[0].value, "3.5");
+        assert_eq!(matches[0].fields[0].source, "inline");
+    }
+
+    #[test]
+    fn test_list_property_keys_and_get_fields() {
+        let conn = setup_db();
+
+        let keys = list_property_keys(&conn).unwrap();
+        assert_eq!(keys[0].key, "rating");
+        assert_eq!(keys[0].note_count, 2);
+        assert_eq!(keys[0].value_types, vec!["number"]);
+
+        let fields = get_fields(&conn, 1).unwrap();
+        let keys: Vec<&str> = fields.iter().map(|f| f.key.as_str()).collect();
+        assert_eq!(keys, vec!["rating", "status", "finished"]);
+    }
+}
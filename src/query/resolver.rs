@@ -0,0 +1,402 @@
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use super::fuzzy::{levenshtein, max_typo_distance};
+
+/// Maximum number of candidates reported for an ambiguous identifier
+const MAX_CANDIDATES: usize = 10;
+
+/// A note that an identifier may refer to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NoteCandidate {
+    pub id: i64,
+    pub path: String,
+    pub title: String,
+}
+
+/// Which resolution step matched, in the order they are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    ExactPath,
+    PathWithoutExtension,
+    Basename,
+    Title,
+    Alias,
+    CaseInsensitive,
+    Fuzzy,
+}
+
+impl MatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::ExactPath => "exact_path",
+            MatchKind::PathWithoutExtension => "path_without_extension",
+            MatchKind::Basename => "basename",
+            MatchKind::Title => "title",
+            MatchKind::Alias => "alias",
+            MatchKind::CaseInsensitive => "case_insensitive",
+            MatchKind::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteResolution {
+    Found {
+        note: NoteCandidate,
+        kind: MatchKind,
+    },
+    Ambiguous(Vec<NoteCandidate>),
+    NotFound,
+}
+
+impl NoteResolution {
+    /// Turn an ambiguous resolution into an error, keeping found and not-found
+    /// outcomes for the caller to handle.
+    pub fn into_result(
+        self,
+        identifier: &str,
+    ) -> std::result::Result<Option<NoteCandidate>, AmbiguousNoteError> {
+        match self {
+            NoteResolution::Found { note, .. } => Ok(Some(note)),
+            NoteResolution::NotFound => Ok(None),
+            NoteResolution::Ambiguous(candidates) => Err(AmbiguousNoteError {
+                identifier: identifier.to_string(),
+                candidates,
+            }),
+        }
+    }
+}
+
+/// Raised when a note argument matches several notes at the same resolution step
+#[derive(Debug, Clone, Error)]
+#[error(
+    "Note '{identifier}' is ambiguous, it matches {} notes:\n{}",
+    .candidates.len(),
+    format_candidates(.candidates)
+)]
+pub struct AmbiguousNoteError {
+    pub identifier: String,
+    pub candidates: Vec<NoteCandidate>,
+}
+
+fn format_candidates(candidates: &[NoteCandidate]) -> String {
+    candidates
+        .iter()
+        .map(|c| format!("  - {} ({})", c.path, c.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct ResolverEntry {
+    note: NoteCandidate,
+    aliases: Vec<String>,
+}
+
+impl ResolverEntry {
+    fn file_name(&self) -> &str {
+        self.note.path.rsplit('/').next().unwrap_or(&self.note.path)
+    }
+
+    fn stem(&self) -> &str {
+        let name = self.file_name();
+        name.strip_suffix(".md").unwrap_or(name)
+    }
+
+    fn path_without_extension(&self) -> &str {
+        self.note
+            .path
+            .strip_suffix(".md")
+            .unwrap_or(&self.note.path)
+    }
+
+    /// Whether this note matches `identifier` at a given (non-fuzzy) step
+    fn matches(&self, kind: MatchKind, identifier: &str, lower: &str) -> bool {
+        match kind {
+            MatchKind::ExactPath => self.note.path == identifier,
+            MatchKind::PathWithoutExtension => self.path_without_extension() == identifier,
+            MatchKind::Basename => self.file_name() == identifier || self.stem() == identifier,
+            MatchKind::Title => self.note.title == identifier,
+            MatchKind::Alias => self.aliases.iter().any(|a| a == identifier),
+            MatchKind::CaseInsensitive => self.names().any(|name| name.to_lowercase() == lower),
+            MatchKind::Fuzzy => false,
+        }
+    }
+
+    /// Every name the note can be referred to by
+    fn names(&self) -> impl Iterator<Item = &str> {
+        [
+            self.note.path.as_str(),
+            self.path_without_extension(),
+            self.stem(),
+            self.note.title.as_str(),
+        ]
+        .into_iter()
+        .chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Resolves note identifiers given on the command line. Steps are tried in
+/// order: exact path, path without `.md`, basename, title, alias,
+/// case-insensitive, fuzzy. The first step with any match decides the outcome.
+pub struct NoteResolver {
+    entries: Vec<ResolverEntry>,
+}
+
+impl NoteResolver {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT note_id, alias FROM aliases ORDER BY alias")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (note_id, alias) = row?;
+            aliases.entry(note_id).or_default().push(alias);
+        }
+
+        let mut stmt = conn.prepare("SELECT id, path, title FROM notes ORDER BY path")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(NoteCandidate {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                title: row.get(2)?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let note = row?;
+            let aliases = aliases.remove(&note.id).unwrap_or_default();
+            entries.push(ResolverEntry { note, aliases });
+        }
+
+        Ok(NoteResolver { entries })
+    }
+
+    /// Resolve an identifier, falling back to fuzzy matching
+    pub fn resolve(&self, identifier: &str) -> NoteResolution {
+        let identifier = normalize(identifier);
+        if identifier.is_empty() {
+            return NoteResolution::NotFound;
+        }
+
+        match self.resolve_exact(&identifier) {
+            NoteResolution::NotFound => self.resolve_fuzzy(&identifier),
+            resolution => resolution,
+        }
+    }
+
+    /// Resolve an identifier without fuzzy matching
+    pub fn resolve_exact(&self, identifier: &str) -> NoteResolution {
+        let identifier = normalize(identifier);
+        let lower = identifier.to_lowercase();
+
+        let steps = [
+            MatchKind::ExactPath,
+            MatchKind::PathWithoutExtension,
+            MatchKind::Basename,
+            MatchKind::Title,
+            MatchKind::Alias,
+            MatchKind::CaseInsensitive,
+        ];
+
+        for kind in steps {
+            let found: Vec<&ResolverEntry> = self
+                .entries
+                .iter()
+                .filter(|e| e.matches(kind, &identifier, &lower))
+                .collect();
+            if !found.is_empty() {
+                return Self::outcome(found, kind);
+            }
+        }
+
+        NoteResolution::NotFound
+    }
+
+    /// Names within typo distance, or containing the identifier, ranked by distance
+    fn resolve_fuzzy(&self, identifier: &str) -> NoteResolution {
+        let lower = identifier.to_lowercase();
+        let max_distance = max_typo_distance(&lower);
+
+        let mut scored: Vec<(usize, &ResolverEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .names()
+                    .filter_map(|name| {
+                        let name = name.to_lowercase();
+                        let distance = levenshtein(&lower, &name);
+                        if distance <= max_distance {
+                            Some(distance)
+                        } else if name.contains(&lower) {
+                            Some(max_distance + 1)
+                        } else {
+                            None
+                        }
+                    })
+                    .min()
+                    .map(|distance| (distance, entry))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.note.path.cmp(&b.1.note.path)));
+        Self::outcome(
+            scored.into_iter().map(|(_, entry)| entry).collect(),
+            MatchKind::Fuzzy,
+        )
+    }
+
+    fn outcome(found: Vec<&ResolverEntry>, kind: MatchKind) -> NoteResolution {
+        match found.as_slice() {
+            [] => NoteResolution::NotFound,
+            [entry] => NoteResolution::Found {
+                note: entry.note.clone(),
+                kind,
+            },
+            _ => NoteResolution::Ambiguous(
+                found
+                    .iter()
+                    .take(MAX_CANDIDATES)
+                    .map(|entry| entry.note.clone())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn normalize(identifier: &str) -> String {
+    let value = identifier.trim().replace('\\', "/");
+    value.trim_start_matches("./").to_string()
+}
+
+/// Resolve a single note identifier against the index
+pub fn resolve_note(conn: &Connection, identifier: &str) -> Result<NoteResolution> {
+    Ok(NoteResolver::load(conn)?.resolve(identifier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             INSERT INTO notes VALUES (1, 'Home.md', 'Home');
+             INSERT INTO notes VALUES (2, 'work/Index.md', 'Work Index');
+             INSERT INTO notes VALUES (3, 'life/Index.md', 'Life Index');
+             INSERT INTO notes VALUES (4, 'dev/JavaScript.md', 'JavaScript');
+             INSERT INTO notes VALUES (5, 'Projects.md', 'Projects');
+             INSERT INTO aliases VALUES (1, 4, 'JS');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn found(resolution: NoteResolution) -> (String, MatchKind) {
+        match resolution {
+            NoteResolution::Found { note, kind } => (note.path, kind),
+            other => panic!("expected a match, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_steps_in_order() {
+        let conn = setup_db();
+        let resolver = NoteResolver::load(&conn).unwrap();
+
+        let cases = [
+            ("Home.md", "Home.md", MatchKind::ExactPath),
+            (
+                "dev/JavaScript",
+                "dev/JavaScript.md",
+                MatchKind::PathWithoutExtension,
+            ),
+            ("JavaScript.md", "dev/JavaScript.md", MatchKind::Basename),
+            ("Work Index", "work/Index.md", MatchKind::Title),
+            ("JS", "dev/JavaScript.md", MatchKind::Alias),
+            ("js", "dev/JavaScript.md", MatchKind::CaseInsensitive),
+            ("./home", "Home.md", MatchKind::CaseInsensitive),
+            ("Projcts", "Projects.md", MatchKind::Fuzzy),
+        ];
+
+        for (identifier, path, kind) in cases {
+            assert_eq!(
+                found(resolver.resolve(identifier)),
+                (path.to_string(), kind),
+                "{identifier}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_ambiguous_basename() {
+        let conn = setup_db();
+        let resolution = resolve_note(&conn, "Index").unwrap();
+
+        let NoteResolution::Ambiguous(candidates) = resolution else {
+            panic!("expected ambiguity");
+        };
+        let paths: Vec<&str> = candidates.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["life/Index.md", "work/Index.md"]);
+    }
+
+    #[test]
+    fn test_resolve_not_found() {
+        let conn = setup_db();
+        assert_eq!(
+            resolve_note(&conn, "Nothing Like It").unwrap(),
+            NoteResolution::NotFound
+        );
+        assert_eq!(resolve_note(&conn, "  ").unwrap(), NoteResolution::NotFound);
+    }
+
+    #[test]
+    fn test_resolve_exact_skips_fuzzy() {
+        let conn = setup_db();
+        let resolver = NoteResolver::load(&conn).unwrap();
+        assert_eq!(resolver.resolve_exact("Projcts"), NoteResolution::NotFound);
+    }
+
+    #[test]
+    fn test_resolve_exact_title_before_case_insensitive_path() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "INSERT INTO notes VALUES (6, 'foo.md', 'foo');
+             INSERT INTO notes VALUES (7, 'bar.md', 'Foo');",
+        )
+        .unwrap();
+        let resolver = NoteResolver::load(&conn).unwrap();
+
+        // The title match "Foo" comes before the case-insensitive basename match
+        assert_eq!(
+            found(resolver.resolve_exact("Foo")),
+            ("bar.md".into(), MatchKind::Title)
+        );
+        assert_eq!(
+            found(resolver.resolve_exact("foo")),
+            ("foo.md".into(), MatchKind::PathWithoutExtension)
+        );
+        assert!(matches!(
+            resolver.resolve_exact("Index"),
+            NoteResolution::Ambiguous(_)
+        ));
+    }
+
+    #[test]
+    fn test_into_result_reports_ambiguity() {
+        let conn = setup_db();
+        let err = resolve_note(&conn, "Index")
+            .unwrap()
+            .into_result("Index")
+            .unwrap_err();
+
+        assert_eq!(err.candidates.len(), 2);
+        assert!(err.to_string().contains("life/Index.md"));
+        assert!(err.to_string().starts_with("Note 'Index' is ambiguous"));
+    }
+}
@@ -1,4 +1,156 @@
-use rusqlite::{Connection, Result};
+// Full-text search over chunks
+mod suggest;
+mod syntax;
+
+use super::tags::{normalize_tag_query, tag_condition};
+use super::PropertyFilter;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+pub use suggest::{corrected_query, suggest_terms, TermSuggestion, SUGGESTIONS_PER_TERM};
+use syntax::{parse_search_query, FilterField, ParsedQuery, ScanTerm};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    /// The query does not parse; `column` is a 1-based character position
+    #[error("Invalid search query at column {column}: {message}\n  {query}\n  {}^", " ".repeat(.column - 1))]
+    Syntax {
+        message: String,
+        query: String,
+        column: usize,
+    },
+
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+}
+
+impl SearchError {
+    pub(crate) fn syntax(query: &str, byte_position: usize, message: &str) -> Self {
+        SearchError::Syntax {
+            message: message.to_string(),
+            query: query.replace(['\n', '\r', '\t'], " "),
+            column: query[..byte_position].chars().count() + 1,
+        }
+    }
+}
+
+/// Markers `snippet()`/`highlight()` wrap matches in; stripped again before
+/// results are returned
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+/// Largest window FTS5 `snippet()` accepts
+const MAX_SNIPPET_TOKENS: usize = 64;
+pub const DEFAULT_SNIPPET_TOKENS: usize = 16;
+
+/// Result size, snippet window and the note filters applied before `limit`
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub limit: usize,
+    /// Tokens in each snippet window (1-64)
+    pub snippet_tokens: usize,
+    /// Path globs (`*` also matches `/`); with `folders`, a note must match one
+    pub paths: Vec<String>,
+    /// Folders, including their subfolders
+    pub folders: Vec<String>,
+    /// Tags, matching nested tags too; a note needs one unless `all_tags`
+    pub tags: Vec<String>,
+    pub all_tags: bool,
+    /// `YYYY-MM-DD`: notes modified on or after this local date
+    pub modified_after: Option<String>,
+    /// `YYYY-MM-DD`: notes modified before this local date
+    pub modified_before: Option<String>,
+    /// Property filters, all of which must match
+    pub properties: Vec<PropertyFilter>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            snippet_tokens: DEFAULT_SNIPPET_TOKENS,
+            paths: Vec::new(),
+            folders: Vec::new(),
+            tags: Vec::new(),
+            all_tags: false,
+            modified_after: None,
+            modified_before: None,
+            properties: Vec::new(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// SQL conditions on the note aliased `note_alias` for every filter,
+    /// binding values as numbered parameters appended to `params`
+    pub(crate) fn note_conditions(
+        &self,
+        note_alias: &str,
+        params: &mut Vec<String>,
+    ) -> Vec<String> {
+        let mut conditions = Vec::new();
+
+        let mut locations = Vec::new();
+        for glob in &self.paths {
+            params.push(glob.trim_start_matches("./").to_string());
+            locations.push(format!("{note_alias}.path GLOB ?{}", params.len()));
+        }
+        for folder in &self.folders {
+            params.push(format!(
+                "{}/",
+                folder.trim_start_matches("./").trim_matches('/')
+            ));
+            locations.push(format!(
+                "substr({note_alias}.path, 1, length(?{0})) = ?{0}",
+                params.len()
+            ));
+        }
+        if !locations.is_empty() {
+            conditions.push(format!("({})", locations.join(" OR ")));
+        }
+
+        let tag_conditions: Vec<String> = self
+            .tags
+            .iter()
+            .map(|tag| {
+                params.push(normalize_tag_query(tag));
+                format!(
+                    "EXISTS (SELECT 1 FROM tags t WHERE t.note_id = {note_alias}.id AND {})",
+                    tag_condition("t.tag", params.len())
+                )
+            })
+            .collect();
+        if !tag_conditions.is_empty() {
+            let joiner = if self.all_tags { " AND " } else { " OR " };
+            conditions.push(format!("({})", tag_conditions.join(joiner)));
+        }
+
+        for (date, op) in [(&self.modified_after, ">="), (&self.modified_before, "<")] {
+            if let Some(timestamp) = date.as_deref().and_then(local_midnight) {
+                params.push(timestamp.to_string());
+                conditions.push(format!(
+                    "{note_alias}.mtime {op} CAST(?{} AS INTEGER)",
+                    params.len()
+                ));
+            }
+        }
+
+        for property in &self.properties {
+            conditions.push(property.to_sql(note_alias, params));
+        }
+
+        conditions
+    }
+}
+
+/// Unix time of the start of a `YYYY-MM-DD` day in the local time zone
+fn local_midnight(date: &str) -> Option<i64> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
 
 #[derive(Debug, Clone)]
 pub struct SearchResult {
@@ -9,28 +161,231 @@ pub struct SearchResult {
     pub heading_path: Option<String>,
     pub chunk_text: String,
     pub rank: f32,
+    /// Excerpt around the best matches, with `...` where text was cut
+    pub snippet: String,
+    /// Byte ranges of matched terms in `snippet`
+    pub snippet_matches: Vec<(usize, usize)>,
+    /// Byte ranges of matched terms in `chunk_text`
+    pub matches: Vec<(usize, usize)>,
 }
 
-/// Search chunks using FTS5 full-text search with BM25 ranking
-pub fn search_chunks(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-    let mut stmt = conn.prepare(
-        "SELECT 
-            c.id,
-            n.id,
-            n.path,
-            n.title,
-            c.heading_path,
-            c.text,
-            rank
-         FROM fts_chunks fc
-         JOIN chunks c ON fc.rowid = c.id
-         JOIN notes n ON c.note_id = n.id
-         WHERE fts_chunks MATCH ?1
-            ORDER BY rank, n.path COLLATE NOCASE, c.byte_offset, c.id
-         LIMIT ?2",
-    )?;
+impl SearchResult {
+    /// Snippet with each match wrapped in `start` and `end`
+    pub fn highlighted_snippet(&self, start: &str, end: &str) -> String {
+        highlight_ranges(&self.snippet, &self.snippet_matches, start, end)
+    }
+}
+
+/// `text` with each byte range in `ranges` wrapped in `start` and `end`
+pub(crate) fn highlight_ranges(
+    text: &str,
+    ranges: &[(usize, usize)],
+    start: &str,
+    end: &str,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for &(from, to) in ranges {
+        out.push_str(&text[last..from]);
+        out.push_str(start);
+        out.push_str(&text[from..to]);
+        out.push_str(end);
+        last = to;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Search chunks using FTS5 full-text search with BM25 ranking. The query
+/// uses the syntax described in `syntax::parse_search_query` and is escaped
+/// before it reaches FTS5. With the `trigram` tokenizer, terms shorter than
+/// 3 characters are found by scanning chunk text instead.
+pub fn search_chunks(
+    conn: &Connection,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>, SearchError> {
+    let parsed = parse_search_query(query, uses_trigram(conn)?)?;
+    query_chunks(conn, &parsed, options, Some(options.limit))
+}
+
+/// Whether `fts_chunks` uses the `trigram` tokenizer
+fn uses_trigram(conn: &Connection) -> rusqlite::Result<bool> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'fts_chunks'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(sql.is_some_and(|sql| sql.contains("trigram")))
+}
+
+/// SQL condition for `term` appearing in a chunk, ignoring ASCII case
+fn scan_condition(term: &ScanTerm, params: &mut Vec<String>) -> String {
+    params.push(term.text.clone());
+    let column = if term.heading {
+        "coalesce(c.heading_path, '')"
+    } else {
+        "c.text"
+    };
+    format!("instr(lower({column}), ?{}) > 0", params.len())
+}
+
+/// Byte ranges of the scanned terms in chunk `text`, sorted and merged
+fn scan_matches(text: &str, terms: &[&ScanTerm]) -> Vec<(usize, usize)> {
+    let lower = text.to_ascii_lowercase();
+    let mut ranges: Vec<(usize, usize)> = terms
+        .iter()
+        .filter(|term| !term.heading)
+        .flat_map(|term| {
+            lower
+                .match_indices(term.text.as_str())
+                .map(|(start, found)| (start, start + found.len()))
+        })
+        .collect();
+    ranges.sort_unstable();
+    merge_ranges(ranges)
+}
+
+/// Sorted `ranges` with overlapping ones joined
+fn merge_ranges(ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// About `chars` characters of `text` around its first match, with `...` where
+/// text was cut, for chunks found without FTS5 `snippet()`
+fn scan_snippet(
+    text: &str,
+    matches: &[(usize, usize)],
+    chars: usize,
+) -> (String, Vec<(usize, usize)>) {
+    let (first_start, first_end) = matches.first().copied().unwrap_or((0, 0));
+    let before = chars / 2;
+    let start = text[..first_start]
+        .char_indices()
+        .rev()
+        .take(before)
+        .last()
+        .map_or(first_start, |(i, _)| i);
+    let end = text[first_end..]
+        .char_indices()
+        .nth(chars - before)
+        .map_or(text.len(), |(i, _)| first_end + i);
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push_str("...");
+    }
+    let offset = snippet.len();
+    snippet.push_str(&text[start..end]);
+    if end < text.len() {
+        snippet.push_str("...");
+    }
+    let ranges = matches
+        .iter()
+        .filter(|&&(from, to)| from >= start && to <= end)
+        .map(|&(from, to)| (from - start + offset, to - start + offset))
+        .collect();
+    (snippet, ranges)
+}
 
-    let results = stmt.query_map([query, &limit.to_string()], |row| {
+/// Matching chunks best first; every match when `limit` is `None`
+fn query_chunks(
+    conn: &Connection,
+    parsed: &ParsedQuery,
+    options: &SearchOptions,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, SearchError> {
+    let snippet_tokens = options.snippet_tokens.clamp(1, MAX_SNIPPET_TOKENS);
+
+    // SQLite treats a negative LIMIT as no limit
+    let limit = limit.map_or("-1".to_string(), |limit| limit.to_string());
+    let mut params: Vec<String> = vec![parsed.fts.clone(), limit];
+    let mut conditions = Vec::new();
+    for filter in &parsed.filters {
+        let condition = match filter.field {
+            FilterField::Title | FilterField::Path => {
+                params.push(filter.value.to_lowercase());
+                let column = if filter.field == FilterField::Title {
+                    "n.title"
+                } else {
+                    "n.path"
+                };
+                format!("instr(lower({column}), ?{}) > 0", params.len())
+            }
+            FilterField::Tag => {
+                params.push(normalize_tag_query(&filter.value));
+                format!(
+                    "EXISTS (SELECT 1 FROM tags t WHERE t.note_id = n.id AND {})",
+                    tag_condition("t.tag", params.len())
+                )
+            }
+        };
+        if filter.negated {
+            conditions.push(format!(" AND NOT {condition}"));
+        } else {
+            conditions.push(format!(" AND {condition}"));
+        }
+    }
+    for condition in options.note_conditions("n", &mut params) {
+        conditions.push(format!(" AND {condition}"));
+    }
+    for group in &parsed.scans {
+        let alternatives: Vec<String> = group
+            .iter()
+            .map(|term| scan_condition(term, &mut params))
+            .collect();
+        conditions.push(format!(" AND ({})", alternatives.join(" OR ")));
+    }
+    for term in &parsed.excluded_scans {
+        let condition = scan_condition(term, &mut params);
+        conditions.push(format!(" AND NOT {condition}"));
+    }
+    let conditions = conditions.concat();
+
+    let sql = if parsed.fts.is_empty() {
+        // Only scanned terms: every matching chunk ranks the same
+        format!(
+            "SELECT c.id, n.id, n.path, n.title, c.heading_path, c.text, -1.0, '', ''
+             FROM chunks c
+             JOIN notes n ON c.note_id = n.id
+             WHERE TRUE{conditions}
+             ORDER BY n.path COLLATE NOCASE, c.byte_offset, c.id
+             LIMIT ?2"
+        )
+    } else {
+        format!(
+            "SELECT 
+                c.id,
+                n.id,
+                n.path,
+                n.title,
+                c.heading_path,
+                c.text,
+                rank,
+                snippet(fts_chunks, 2, char(2), char(3), '...', {snippet_tokens}),
+                highlight(fts_chunks, 2, char(2), char(3))
+             FROM fts_chunks fc
+             JOIN chunks c ON fc.rowid = c.id
+             JOIN notes n ON c.note_id = n.id
+             WHERE fts_chunks MATCH ?1{conditions}
+                ORDER BY rank, n.path COLLATE NOCASE, c.byte_offset, c.id
+             LIMIT ?2"
+        )
+    };
+    let mut stmt = conn.prepare(&sql)?;
+
+    let results = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+        let (snippet, snippet_matches) = split_matches(&row.get::<_, String>(7)?);
+        let (_, matches) = split_matches(&row.get::<_, String>(8)?);
         Ok(SearchResult {
             chunk_id: row.get(0)?,
             note_id: row.get(1)?,
@@ -39,17 +394,245 @@ pub fn search_chunks(conn: &Connection, query: &str, limit: usize) -> Result<Vec
             heading_path: row.get(4)?,
             chunk_text: row.get(5)?,
             rank: row.get(6)?,
+            snippet,
+            snippet_matches,
+            matches,
         })
     })?;
 
+    let scanned: Vec<&ScanTerm> = parsed.scans.iter().flatten().collect();
     let mut search_results = Vec::new();
     for result in results {
-        search_results.push(result?);
+        let mut result = result?;
+        if !scanned.is_empty() {
+            let mut matches = scan_matches(&result.chunk_text, &scanned);
+            matches.append(&mut result.matches);
+            matches.sort_unstable();
+            result.matches = merge_ranges(matches);
+            if parsed.fts.is_empty() {
+                (result.snippet, result.snippet_matches) =
+                    scan_snippet(&result.chunk_text, &result.matches, snippet_tokens);
+            }
+        }
+        search_results.push(result);
     }
 
     Ok(search_results)
 }
 
+/// Weights of the boosts `search_notes` applies on top of the text relevance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteBoosts {
+    /// Query terms found in the note title or an alias
+    pub title: f64,
+    /// Query terms found in the heading paths of matching chunks
+    pub heading: f64,
+    /// Recently modified notes, relative to the newest note in the vault
+    pub recency: f64,
+    /// Age in days at which the recency boost is halved
+    pub recency_half_life_days: f64,
+    /// Incoming links, relative to the most linked note in the vault
+    pub inlinks: f64,
+}
+
+impl Default for NoteBoosts {
+    fn default() -> Self {
+        Self {
+            title: 1.0,
+            heading: 0.5,
+            recency: 0.2,
+            recency_half_life_days: 180.0,
+            inlinks: 0.5,
+        }
+    }
+}
+
+/// Weighted boosts one note received; its score is `relevance * (1 + sum)`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NoteBoostScores {
+    pub title: f64,
+    pub heading: f64,
+    pub recency: f64,
+    pub inlinks: f64,
+}
+
+impl NoteBoostScores {
+    pub fn total(&self) -> f64 {
+        self.title + self.heading + self.recency + self.inlinks
+    }
+}
+
+/// A note and the chunks in it that matched
+#[derive(Debug, Clone)]
+pub struct NoteSearchResult {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub score: f64,
+    /// Text relevance before boosts: the best chunk plus a fraction of the others
+    pub relevance: f64,
+    pub boosts: NoteBoostScores,
+    pub chunk_count: usize,
+    /// Best matching chunk, whose snippet represents the note
+    pub best: SearchResult,
+    /// Distinct heading paths of matching chunks, best first
+    pub heading_paths: Vec<String>,
+}
+
+/// Weight of each matching chunk after the best one in a note's relevance
+const OTHER_CHUNK_WEIGHT: f64 = 0.1;
+
+/// Search like `search_chunks`, then group the matches by note. Notes are
+/// ranked by their chunks' relevance scaled up by `boosts`; `options.limit`
+/// counts notes.
+pub fn search_notes(
+    conn: &Connection,
+    query: &str,
+    options: &SearchOptions,
+    boosts: &NoteBoosts,
+) -> Result<Vec<NoteSearchResult>, SearchError> {
+    let parsed = parse_search_query(query, uses_trigram(conn)?)?;
+    let chunks = query_chunks(conn, &parsed, options, None)?;
+
+    let mut order: Vec<i64> = Vec::new();
+    let mut by_note: HashMap<i64, Vec<SearchResult>> = HashMap::new();
+    for chunk in chunks {
+        if !by_note.contains_key(&chunk.note_id) {
+            order.push(chunk.note_id);
+        }
+        by_note.entry(chunk.note_id).or_default().push(chunk);
+    }
+
+    let (newest_mtime, max_inlinks): (Option<i64>, Option<i64>) = conn.query_row(
+        "SELECT
+            (SELECT MAX(mtime) FROM notes),
+            (SELECT MAX(c) FROM (
+                SELECT COUNT(DISTINCT src_note_id) AS c FROM links
+                WHERE dst_note_id IS NOT NULL AND dst_note_id != src_note_id
+                GROUP BY dst_note_id
+            ))",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let mut note_stmt = conn.prepare(
+        "SELECT n.mtime,
+            (SELECT COUNT(DISTINCT l.src_note_id) FROM links l
+             WHERE l.dst_note_id = n.id AND l.src_note_id != n.id)
+         FROM notes n WHERE n.id = ?1",
+    )?;
+    let mut alias_stmt = conn.prepare("SELECT alias FROM aliases WHERE note_id = ?1")?;
+
+    let mut results = Vec::new();
+    for note_id in order {
+        let Some(chunks) = by_note.remove(&note_id) else {
+            continue;
+        };
+
+        // FTS5 ranks are negated BM25 scores, lower is better
+        let relevance = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let score = -f64::from(chunk.rank);
+                if i == 0 {
+                    score
+                } else {
+                    score * OTHER_CHUNK_WEIGHT
+                }
+            })
+            .sum::<f64>();
+
+        let mut heading_paths: Vec<String> = Vec::new();
+        for heading in chunks.iter().filter_map(|c| c.heading_path.as_ref()) {
+            if !heading_paths.contains(heading) {
+                heading_paths.push(heading.clone());
+            }
+        }
+
+        let (mtime, inlinks): (i64, i64) = note_stmt
+            .query_row([note_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?
+            .unwrap_or_default();
+        let mut names = vec![chunks[0].note_title.to_lowercase()];
+        let aliases = alias_stmt.query_map([note_id], |row| row.get::<_, String>(0))?;
+        for alias in aliases {
+            names.push(alias?.to_lowercase());
+        }
+        let headings: Vec<String> = heading_paths.iter().map(|h| h.to_lowercase()).collect();
+
+        let age_days = newest_mtime.map_or(0, |newest| (newest - mtime).max(0)) as f64 / 86_400.0;
+        let recency = if boosts.recency_half_life_days > 0.0 {
+            0.5f64.powf(age_days / boosts.recency_half_life_days)
+        } else {
+            0.0
+        };
+        let popularity = match max_inlinks {
+            Some(max) if max > 0 => (1.0 + inlinks as f64).ln() / (1.0 + max as f64).ln(),
+            _ => 0.0,
+        };
+        let note_boosts = NoteBoostScores {
+            title: boosts.title * term_coverage(&parsed.terms, &names),
+            heading: boosts.heading * term_coverage(&parsed.terms, &headings),
+            recency: boosts.recency * recency,
+            inlinks: boosts.inlinks * popularity,
+        };
+
+        let best = chunks[0].clone();
+        results.push(NoteSearchResult {
+            note_id,
+            note_path: best.note_path.clone(),
+            note_title: best.note_title.clone(),
+            score: relevance * (1.0 + note_boosts.total()),
+            relevance,
+            boosts: note_boosts,
+            chunk_count: chunks.len(),
+            best,
+            heading_paths,
+        });
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.note_path.to_lowercase().cmp(&b.note_path.to_lowercase()))
+    });
+    results.truncate(options.limit);
+    Ok(results)
+}
+
+/// Fraction of `terms` contained in at least one of `texts`
+fn term_coverage(terms: &[String], texts: &[String]) -> f64 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let found = terms
+        .iter()
+        .filter(|term| texts.iter().any(|text| text.contains(term.as_str())))
+        .count();
+    found as f64 / terms.len() as f64
+}
+
+/// Remove match markers, returning the plain text and the byte ranges they enclosed
+fn split_matches(marked: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut ranges = Vec::new();
+    let mut open = None;
+
+    for c in marked.chars() {
+        match c {
+            MATCH_START => open = Some(text.len()),
+            MATCH_END => {
+                if let Some(start) = open.take() {
+                    ranges.push((start, text.len()));
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+
+    (text, ranges)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +647,9 @@ mod tests {
             heading_path: Some("Heading".to_string()),
             chunk_text: "Test content".to_string(),
             rank: 1.0,
+            snippet: "Test content".to_string(),
+            snippet_matches: vec![(0, 4)],
+            matches: vec![(0, 4)],
         };
 
         assert_eq!(result.chunk_id, 1);
@@ -80,8 +666,319 @@ mod tests {
             heading_path: None,
             chunk_text: "Test content".to_string(),
             rank: 1.0,
+            snippet: "Test content".to_string(),
+            snippet_matches: vec![(0, 4)],
+            matches: vec![(0, 4)],
         };
 
         assert!(result.heading_path.is_none());
     }
+
+    #[test]
+    fn test_split_matches() {
+        let (text, ranges) = split_matches("...the \u{2}deep\u{3} work of \u{2}café\u{3}s");
+        assert_eq!(text, "...the deep work of cafés");
+        assert_eq!(ranges, vec![(7, 11), (20, 25)]);
+        assert_eq!(&text[20..25], "café");
+    }
+
+    #[test]
+    fn test_search_chunks_snippets_and_matches() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES (1, 'focus.md', 'Focus');
+             INSERT INTO chunks VALUES (1, 1, NULL,
+                'One two three four five six seven eight nine ten deep work eleven twelve', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+
+        let options = SearchOptions {
+            limit: 10,
+            snippet_tokens: 4,
+            ..Default::default()
+        };
+        let results = search_chunks(&conn, "deep", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.snippet, "...ten deep work eleven...");
+        assert_eq!(result.snippet_matches, vec![(7, 11)]);
+        assert_eq!(
+            result.highlighted_snippet("[", "]"),
+            "...ten [deep] work eleven..."
+        );
+        let (start, end) = result.matches[0];
+        assert_eq!(&result.chunk_text[start..end], "deep");
+    }
+
+    #[test]
+    fn test_search_chunks_short_terms_with_trigram() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id,
+                tokenize = 'trigram'
+             );
+             INSERT INTO notes VALUES (1, 'tokyo.md', 'Tokyo'), (2, 'kyoto.md', 'Kyoto');
+             INSERT INTO chunks VALUES
+                (1, 1, NULL, '東京タワーは東京の名所です', 0),
+                (2, 2, NULL, '京都タワーは京都駅の前にあります', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+        let options = SearchOptions {
+            snippet_tokens: 4,
+            ..Default::default()
+        };
+        let paths = |query: &str| -> Vec<String> {
+            search_chunks(&conn, query, &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.note_path)
+                .collect()
+        };
+
+        let results = search_chunks(&conn, "東京", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.note_path, "tokyo.md");
+        assert_eq!(result.matches, vec![(0, 6), (18, 24)]);
+        assert_eq!(result.highlighted_snippet("[", "]"), "[東京]タワ...");
+
+        assert_eq!(paths("タワー 京都"), vec!["kyoto.md"]);
+        assert_eq!(paths("タワー -京都"), vec!["tokyo.md"]);
+        assert_eq!(paths("東京 OR 京都"), vec!["kyoto.md", "tokyo.md"]);
+        assert!(paths("東京 -名所").is_empty());
+    }
+
+    #[test]
+    fn test_search_chunks_query_syntax() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES (1, 'dev/cpp.md', 'C++ Tips'), (2, 'home.md', 'Home');
+             INSERT INTO tags (note_id, tag, display) VALUES (1, 'lang/cpp', 'lang/cpp');
+             INSERT INTO chunks VALUES
+                (1, 1, '# Templates', 'C++ templates and foo-bar programming', 0),
+                (2, 2, '# Inbox', 'What? Programming notes and foo bar ideas', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+
+        let paths = |query: &str| -> Vec<String> {
+            search_chunks(&conn, query, &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.note_path)
+                .collect()
+        };
+
+        assert_eq!(paths("C++"), vec!["dev/cpp.md"]);
+        assert_eq!(paths("what?").len(), 1);
+        assert_eq!(paths("foo-bar").len(), 2);
+        assert_eq!(paths("program* -templates"), vec!["home.md"]);
+        assert_eq!(paths("templates OR ideas").len(), 2);
+        assert_eq!(paths("NEAR(foo ideas, 2)"), vec!["home.md"]);
+        assert_eq!(paths("heading:templates"), vec!["dev/cpp.md"]);
+        assert_eq!(paths("programming -heading:inbox"), vec!["dev/cpp.md"]);
+        assert_eq!(paths("programming title:tips"), vec!["dev/cpp.md"]);
+        assert_eq!(paths("programming path:HOME"), vec!["home.md"]);
+        assert_eq!(paths("programming tag:lang"), vec!["dev/cpp.md"]);
+        assert_eq!(paths("programming -tag:lang/cpp"), vec!["home.md"]);
+
+        let err = search_chunks(&conn, "\"unbalanced", &SearchOptions::default()).unwrap_err();
+        assert!(matches!(err, SearchError::Syntax { column: 1, .. }));
+    }
+
+    #[test]
+    fn test_search_chunks_note_filters() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             CREATE TABLE fields (
+                id INTEGER PRIMARY KEY, note_id INTEGER, key TEXT, value TEXT, value_type TEXT,
+                number REAL
+             );
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES
+                (1, 'work/a.md', 'A', 1704067200), (2, 'work/sub/b.md', 'B', 1717200000),
+                (3, 'home/c.md', 'C', 1717200000), (4, 'workshop.md', 'D', 1717200000);
+             INSERT INTO tags (note_id, tag, display) VALUES
+                (1, 'project/x', 'project/x'), (2, 'project', 'project'), (2, 'urgent', 'urgent'),
+                (3, 'urgent', 'urgent');
+             INSERT INTO fields (note_id, key, value, value_type, number) VALUES
+                (1, 'status', 'done', 'text', NULL), (2, 'status', 'open', 'text', NULL);
+             INSERT INTO chunks VALUES
+                (1, 1, NULL, 'meeting notes', 0), (2, 2, NULL, 'meeting agenda', 0),
+                (3, 3, NULL, 'meeting at home', 0), (4, 4, NULL, 'meeting room', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+
+        let paths = |options: SearchOptions| -> Vec<String> {
+            let mut paths: Vec<String> = search_chunks(&conn, "meeting", &options)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.note_path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let folder = SearchOptions {
+            folders: vec!["work".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(paths(folder), vec!["work/a.md", "work/sub/b.md"]);
+
+        let glob = SearchOptions {
+            paths: vec!["*/c.md".to_string(), "work*.md".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            paths(glob),
+            vec!["home/c.md", "work/a.md", "work/sub/b.md", "workshop.md"]
+        );
+
+        let any_tag = SearchOptions {
+            tags: vec!["project".to_string(), "#urgent".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            paths(any_tag),
+            vec!["home/c.md", "work/a.md", "work/sub/b.md"]
+        );
+
+        let all_tags = SearchOptions {
+            tags: vec!["project".to_string(), "urgent".to_string()],
+            all_tags: true,
+            ..Default::default()
+        };
+        assert_eq!(paths(all_tags), vec!["work/sub/b.md"]);
+
+        let recent = SearchOptions {
+            modified_after: Some("2024-03-01".to_string()),
+            modified_before: Some("2030-01-01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            paths(recent),
+            vec!["home/c.md", "work/sub/b.md", "workshop.md"]
+        );
+
+        let property = SearchOptions {
+            properties: vec![PropertyFilter::parse("status=done").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(paths(property), vec!["work/a.md"]);
+
+        // Filters apply before the limit
+        let limited = SearchOptions {
+            limit: 1,
+            folders: vec!["home".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(paths(limited), vec!["home/c.md"]);
+    }
+
+    #[test]
+    fn test_search_notes_groups_and_boosts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT, mtime INTEGER);
+             CREATE TABLE aliases (id INTEGER PRIMARY KEY, note_id INTEGER, alias TEXT);
+             CREATE TABLE links (
+                id INTEGER PRIMARY KEY, src_note_id INTEGER, dst_note_id INTEGER, dst_text TEXT
+             );
+             CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT,
+                byte_offset INTEGER
+             );
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES
+                (1, 'log.md', 'Daily Log', 1700000000), (2, 'garden.md', 'Plants', 1700000000),
+                (3, 'other.md', 'Other', 1700000000);
+             INSERT INTO aliases (note_id, alias) VALUES (2, 'Compost Guide');
+             INSERT INTO links (src_note_id, dst_note_id, dst_text) VALUES
+                (3, 2, 'garden'), (1, 2, 'garden');
+             INSERT INTO chunks VALUES
+                (1, 1, '# Monday', 'compost compost compost bin emptied', 0),
+                (2, 1, '# Tuesday', 'more compost today', 100),
+                (3, 1, '# Tuesday', 'compost again', 200),
+                (4, 2, '# Soil > Compost', 'turn the compost weekly', 0);
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+
+        let unboosted = NoteBoosts {
+            title: 0.0,
+            heading: 0.0,
+            recency: 0.0,
+            inlinks: 0.0,
+            ..Default::default()
+        };
+        let plain = search_notes(&conn, "compost", &SearchOptions::default(), &unboosted).unwrap();
+        assert_eq!(plain.len(), 2);
+        assert_eq!(plain[0].note_path, "log.md");
+        assert_eq!(plain[0].chunk_count, 3);
+        assert_eq!(plain[0].heading_paths, vec!["# Monday", "# Tuesday"]);
+        assert_eq!(plain[0].best.chunk_id, 1);
+        assert_eq!(plain[0].score, plain[0].relevance);
+
+        let boosted = search_notes(
+            &conn,
+            "compost",
+            &SearchOptions::default(),
+            &NoteBoosts::default(),
+        )
+        .unwrap();
+        assert_eq!(boosted[0].note_path, "garden.md");
+        let boosts = boosted[0].boosts;
+        assert_eq!(boosts.title, 1.0);
+        assert_eq!(boosts.heading, 0.5);
+        assert_eq!(boosts.recency, 0.2);
+        assert_eq!(boosts.inlinks, 0.5);
+        assert_eq!(boosted[1].boosts.inlinks, 0.0);
+
+        let one = SearchOptions {
+            limit: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            search_notes(&conn, "compost", &one, &NoteBoosts::default())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
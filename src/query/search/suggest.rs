@@ -0,0 +1,208 @@
+// "Did you mean" suggestions from the full-text index vocabulary
+use super::syntax::parse_search_query;
+use super::SearchError;
+use crate::query::fuzzy::{levenshtein, max_typo_distance};
+use rusqlite::{Connection, OptionalExtension};
+
+/// Suggestions listed for each misspelled word
+pub const SUGGESTIONS_PER_TERM: usize = 3;
+
+/// An indexed term close to a query word that matches nothing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermSuggestion {
+    /// The query word, lowercased
+    pub term: String,
+    /// The indexed term; a word stem with the `porter` tokenizer
+    pub suggestion: String,
+    pub distance: usize,
+    /// Chunks containing `suggestion`
+    pub documents: i64,
+    /// `ln(1 + documents) / (1 + distance)²`, higher is better
+    pub score: f64,
+}
+
+/// Suggest up to `per_term` indexed terms for each word of `query` that
+/// matches no chunk, closest and most common first. Words are compared with
+/// the terms in `fts_chunks_vocab` by edit distance, allowing more typos in
+/// longer words. Nothing is suggested with the `trigram` tokenizer, whose
+/// vocabulary holds character triples rather than words.
+pub fn suggest_terms(
+    conn: &Connection,
+    query: &str,
+    per_term: usize,
+) -> Result<Vec<TermSuggestion>, SearchError> {
+    let parsed = parse_search_query(query, false)?;
+
+    let fts_sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'fts_chunks'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if fts_sql.map_or(true, |sql| sql.contains("trigram")) {
+        return Ok(Vec::new());
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    for term in &parsed.terms {
+        for word in term.split(|c: char| !c.is_alphanumeric()) {
+            if !word.is_empty() && !words.iter().any(|w| w == word) {
+                words.push(word.to_string());
+            }
+        }
+    }
+
+    let mut known_stmt =
+        conn.prepare("SELECT 1 FROM fts_chunks WHERE fts_chunks MATCH ?1 LIMIT 1")?;
+    let mut vocab_stmt = conn
+        .prepare("SELECT term, doc FROM fts_chunks_vocab WHERE length(term) BETWEEN ?1 AND ?2")?;
+
+    let mut suggestions = Vec::new();
+    for word in words {
+        let max_distance = max_typo_distance(&word);
+        if max_distance == 0 {
+            continue;
+        }
+        let quoted = format!("\"{}\"", word.replace('"', "\"\""));
+        if known_stmt.exists([&quoted])? {
+            continue;
+        }
+
+        let length = word.chars().count();
+        let rows = vocab_stmt.query_map(
+            [
+                length.saturating_sub(max_distance) as i64,
+                (length + max_distance) as i64,
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+
+        let mut candidates = Vec::new();
+        for row in rows {
+            let (term, documents) = row?;
+            let distance = levenshtein(&word, &term);
+            if distance == 0 || distance > max_distance {
+                continue;
+            }
+            candidates.push(TermSuggestion {
+                term: word.clone(),
+                suggestion: term,
+                distance,
+                documents,
+                score: (1.0 + documents as f64).ln() / ((1 + distance) as f64).powi(2),
+            });
+        }
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.suggestion.cmp(&b.suggestion))
+        });
+        candidates.truncate(per_term);
+        suggestions.extend(candidates);
+    }
+
+    Ok(suggestions)
+}
+
+/// `query` with each misspelled word replaced by its best suggestion, or
+/// `None` without suggestions
+pub fn corrected_query(query: &str, suggestions: &[TermSuggestion]) -> Option<String> {
+    if suggestions.is_empty() {
+        return None;
+    }
+
+    let mut corrected = String::with_capacity(query.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        let lower = word.to_lowercase();
+        match suggestions.iter().find(|s| s.term == lower) {
+            Some(best) => out.push_str(&best.suggestion),
+            None => out.push_str(word),
+        }
+        word.clear();
+    };
+    for c in query.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut corrected);
+            corrected.push(c);
+        }
+    }
+    flush(&mut word, &mut corrected);
+
+    Some(corrected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db(tokenizer: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE chunks (id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT);
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, heading_path, text, content=chunks, content_rowid=id,
+                tokenize = '{tokenizer}'
+             );
+             CREATE VIRTUAL TABLE fts_chunks_vocab USING fts5vocab(fts_chunks, 'row');
+             INSERT INTO chunks VALUES
+                (1, 1, NULL, 'Productivity tips for deep focus'),
+                (2, 2, NULL, 'More productivity and product ideas'),
+                (3, 3, NULL, 'Production notes and productivity');
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');"
+        ))
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_suggest_terms_prefers_close_common_terms() {
+        let conn = setup_db("unicode61");
+
+        let suggestions = suggest_terms(&conn, "Prodcutivity \"deep focis\" -missing", 3).unwrap();
+        let pairs: Vec<(&str, &str, usize)> = suggestions
+            .iter()
+            .map(|s| (s.term.as_str(), s.suggestion.as_str(), s.distance))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("prodcutivity", "productivity", 2), ("focis", "focus", 1),]
+        );
+        assert_eq!(suggestions[0].documents, 3);
+
+        // Known words and short words get no suggestions
+        assert!(suggest_terms(&conn, "productivity ide", 3)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_suggest_terms_with_stemming_and_trigrams() {
+        let conn = setup_db("porter unicode61");
+        assert!(suggest_terms(&conn, "ideas", 3).unwrap().is_empty());
+        let suggestions = suggest_terms(&conn, "producton", 3).unwrap();
+        assert!(suggestions.iter().any(|s| s.suggestion == "product"));
+
+        let conn = setup_db("trigram");
+        assert!(suggest_terms(&conn, "prodcutivity", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_corrected_query() {
+        let suggestions = vec![TermSuggestion {
+            term: "focsu".to_string(),
+            suggestion: "focus".to_string(),
+            distance: 2,
+            documents: 1,
+            score: 0.1,
+        }];
+        assert_eq!(
+            corrected_query("deep \"Focsu\" time", &suggestions).as_deref(),
+            Some("deep \"focus\" time")
+        );
+        assert_eq!(corrected_query("deep", &[]), None);
+    }
+}
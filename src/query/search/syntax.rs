@@ -0,0 +1,611 @@
+use super::SearchError;
+
+/// Tokens allowed between `NEAR(...)` terms when no distance is given
+const DEFAULT_NEAR_DISTANCE: usize = 10;
+/// Characters in a term the `trigram` tokenizer can match
+const TRIGRAM_CHARS: usize = 3;
+const FIELDS: [(&str, Field); 4] = [
+    ("title", Field::Filter(FilterField::Title)),
+    ("heading", Field::Heading),
+    ("path", Field::Filter(FilterField::Path)),
+    ("tag", Field::Filter(FilterField::Tag)),
+];
+
+/// Note-level restrictions applied in SQL next to the FTS match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FilterField {
+    Title,
+    Path,
+    Tag,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Filter {
+    pub field: FilterField,
+    pub value: String,
+    pub negated: bool,
+}
+
+/// A word or phrase matched by scanning chunks instead of through FTS5
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ScanTerm {
+    /// ASCII-lowercased like SQLite's `lower()`
+    pub text: String,
+    /// Look in the heading path rather than the chunk text
+    pub heading: bool,
+}
+
+/// A user query translated to an escaped FTS5 expression plus note filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ParsedQuery {
+    /// Empty when every term is scanned
+    pub fts: String,
+    pub filters: Vec<Filter>,
+    /// Lowercased words and phrases the query searches for, excluded terms left out
+    pub terms: Vec<String>,
+    /// Scanned terms, of which each group needs one to match
+    pub scans: Vec<Vec<ScanTerm>>,
+    /// Scanned terms that must not match
+    pub excluded_scans: Vec<ScanTerm>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Any,
+    Heading,
+    Filter(FilterField),
+}
+
+enum Token {
+    Or {
+        start: usize,
+    },
+    Atom {
+        start: usize,
+        negated: bool,
+        field: Field,
+        /// FTS5 expression for searchable fields, raw text for filters
+        text: String,
+        /// Words and phrases as typed, for searchable fields
+        terms: Vec<String>,
+        /// The word or phrase, unless the atom is a filter or `NEAR(...)`
+        literal: Option<String>,
+    },
+}
+
+/// Parse the `search notes` query syntax:
+///
+/// - `word` and `"exact phrase"`, all of which must match
+/// - `prefix*` and `"phrase prefix"*`
+/// - `-word` and `-"phrase"` to exclude
+/// - `a OR b`, joining the terms on either side
+/// - `NEAR(a b, 5)` for terms within 5 tokens of each other (default 10)
+/// - `heading:word` to match heading paths only
+/// - `title:`, `path:` and `tag:` (nested tags included) to filter notes, also negated
+///
+/// Everything else is searched literally, so `C++` or `foo-bar` are safe.
+///
+/// With `scan_short_terms`, for `trigram` indexes, words and phrases shorter than
+/// 3 characters go to `scans` instead of the FTS5 expression, along with the rest of
+/// an `OR` group containing one. Excluded terms follow when no FTS5 term is left.
+pub(super) fn parse_search_query(
+    query: &str,
+    scan_short_terms: bool,
+) -> Result<ParsedQuery, SearchError> {
+    if query.trim().is_empty() {
+        return Err(SearchError::syntax(query, 0, "the search query is empty"));
+    }
+
+    let mut scanner = Scanner { query, pos: 0 };
+    let mut tokens = Vec::new();
+    while let Some(token) = scanner.next_token()? {
+        tokens.push(token);
+    }
+
+    let mut groups: Vec<Vec<(String, Option<ScanTerm>)>> = Vec::new();
+    let mut excluded: Vec<(String, Option<ScanTerm>)> = Vec::new();
+    let mut filters = Vec::new();
+    let mut terms: Vec<String> = Vec::new();
+    let mut pending_or: Option<usize> = None;
+    let mut after_term = false;
+
+    for token in tokens {
+        match token {
+            Token::Or { start } => {
+                if !after_term || pending_or.is_some() {
+                    return Err(SearchError::syntax(
+                        query,
+                        start,
+                        "OR must join two search terms, as in `cats OR dogs`",
+                    ));
+                }
+                pending_or = Some(start);
+                after_term = false;
+            }
+            Token::Atom {
+                start,
+                negated,
+                field,
+                text,
+                terms: atom_terms,
+                literal,
+            } => {
+                if pending_or.is_some() && (negated || matches!(field, Field::Filter(_))) {
+                    return Err(SearchError::syntax(
+                        query,
+                        start,
+                        "OR can only join search terms, not excluded terms or `title:`, `path:` and `tag:` filters",
+                    ));
+                }
+                let scan = literal.map(|literal| ScanTerm {
+                    text: literal.to_ascii_lowercase(),
+                    heading: field == Field::Heading,
+                });
+                match field {
+                    Field::Filter(field) => filters.push(Filter {
+                        field,
+                        value: text,
+                        negated,
+                    }),
+                    _ if negated => excluded.push((text, scan)),
+                    _ => {
+                        for term in atom_terms {
+                            let term = term.to_lowercase();
+                            if !terms.contains(&term) {
+                                terms.push(term);
+                            }
+                        }
+                        match (pending_or.take(), groups.last_mut()) {
+                            (Some(_), Some(group)) => group.push((text, scan)),
+                            _ => groups.push(vec![(text, scan)]),
+                        }
+                    }
+                }
+                after_term = !negated && !matches!(field, Field::Filter(_));
+            }
+        }
+    }
+
+    if let Some(start) = pending_or {
+        return Err(SearchError::syntax(
+            query,
+            start,
+            "OR must join two search terms, as in `cats OR dogs`",
+        ));
+    }
+    if groups.is_empty() {
+        let message = if !excluded.is_empty() {
+            "excluded terms need at least one term to search for"
+        } else if !filters.is_empty() {
+            "`title:`, `path:` and `tag:` filters need at least one term to search for"
+        } else {
+            "the query has no searchable terms"
+        };
+        return Err(SearchError::syntax(query, 0, message));
+    }
+
+    let is_short = |(_, scan): &(String, Option<ScanTerm>)| {
+        scan.as_ref()
+            .is_some_and(|scan| scan.text.chars().count() < TRIGRAM_CHARS)
+    };
+    let (mut scanned, mut groups): (Vec<_>, Vec<_>) = groups.into_iter().partition(|group| {
+        scan_short_terms && group.iter().any(is_short) && group.iter().all(|(_, s)| s.is_some())
+    });
+    if groups.is_empty() && excluded.iter().any(|(_, scan)| scan.is_none()) {
+        // An excluded NEAR(...) needs a term FTS5 can match
+        groups = std::mem::take(&mut scanned);
+    }
+    let (excluded_scanned, excluded): (Vec<_>, Vec<_>) = excluded
+        .into_iter()
+        .partition(|term| scan_short_terms && (groups.is_empty() || is_short(term)));
+
+    let mut fts = groups
+        .into_iter()
+        .map(|group| {
+            let mut texts: Vec<String> = group.into_iter().map(|(text, _)| text).collect();
+            if texts.len() == 1 {
+                texts.pop().unwrap_or_default()
+            } else {
+                format!("({})", texts.join(" OR "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    if !excluded.is_empty() {
+        fts = format!("({fts})");
+        for (term, _) in excluded {
+            fts.push_str(" NOT ");
+            fts.push_str(&term);
+        }
+    }
+
+    Ok(ParsedQuery {
+        fts,
+        filters,
+        terms,
+        scans: scanned
+            .into_iter()
+            .map(|group| group.into_iter().filter_map(|(_, scan)| scan).collect())
+            .collect(),
+        excluded_scans: excluded_scanned
+            .into_iter()
+            .filter_map(|(_, scan)| scan)
+            .collect(),
+    })
+}
+
+/// FTS5 string literal for `text`
+fn quote(text: &str, prefix: bool) -> String {
+    let star = if prefix { "*" } else { "" };
+    format!("\"{}\"{star}", text.replace('"', "\"\""))
+}
+
+struct Scanner<'a> {
+    query: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn rest(&self) -> &'a str {
+        &self.query[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.query.len() - trimmed.len();
+    }
+
+    fn at_boundary(&self) -> bool {
+        self.peek().map_or(true, char::is_whitespace)
+    }
+
+    fn error(&self, position: usize, message: &str) -> SearchError {
+        SearchError::syntax(self.query, position, message)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, SearchError> {
+        loop {
+            self.skip_whitespace();
+            let Some(first) = self.peek() else {
+                return Ok(None);
+            };
+            let start = self.pos;
+
+            let mut negated = false;
+            if first == '-' {
+                self.pos += 1;
+                if self.at_boundary() {
+                    // A lone `-` is punctuation, not an exclusion
+                    continue;
+                }
+                negated = true;
+            }
+
+            if !negated && self.rest().starts_with("OR") {
+                let after = &self.rest()[2..];
+                if after.chars().next().map_or(true, char::is_whitespace) {
+                    self.pos += 2;
+                    return Ok(Some(Token::Or { start }));
+                }
+            }
+
+            let field = self.field(start)?;
+            let atom = match field {
+                Field::Filter(_) => Some((self.filter_value()?, Vec::new(), None)),
+                _ if self.rest().starts_with("NEAR(") => {
+                    let (text, terms) = self.near()?;
+                    Some((text, terms, None))
+                }
+                _ if self.peek() == Some('"') => {
+                    let (phrase, prefix) = self.phrase()?;
+                    Some((quote(&phrase, prefix), vec![phrase.clone()], Some(phrase)))
+                }
+                _ => self
+                    .word(start)?
+                    .map(|(word, prefix)| (quote(&word, prefix), vec![word.clone()], Some(word))),
+            };
+            let Some((text, terms, literal)) = atom else {
+                // Punctuation such as `?` or `++` has nothing to match
+                continue;
+            };
+
+            let text = match field {
+                Field::Heading => format!("heading_path : {text}"),
+                _ => text,
+            };
+            return Ok(Some(Token::Atom {
+                start,
+                negated,
+                field,
+                text,
+                terms,
+                literal,
+            }));
+        }
+    }
+
+    /// A `title:`-style prefix, consumed if present
+    fn field(&mut self, start: usize) -> Result<Field, SearchError> {
+        let rest = self.rest();
+        let Some((name, _)) = rest.split_once(':') else {
+            return Ok(Field::Any);
+        };
+        let Some(&(_, field)) = FIELDS.iter().find(|(f, _)| f.eq_ignore_ascii_case(name)) else {
+            return Ok(Field::Any);
+        };
+
+        self.pos += name.len() + 1;
+        if self.at_boundary() {
+            return Err(self.error(
+                start,
+                &format!("`{name}:` needs a value, as in `{name}:word` or `{name}:\"two words\"`"),
+            ));
+        }
+        Ok(field)
+    }
+
+    fn filter_value(&mut self) -> Result<String, SearchError> {
+        if self.peek() == Some('"') {
+            return Ok(self.phrase()?.0);
+        }
+        let len = self
+            .rest()
+            .find(char::is_whitespace)
+            .unwrap_or(self.rest().len());
+        let value = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(value)
+    }
+
+    /// `"..."` with an optional trailing `*`
+    fn phrase(&mut self) -> Result<(String, bool), SearchError> {
+        let start = self.pos;
+        let Some(end) = self.rest()[1..].find('"') else {
+            return Err(self.error(start, "unterminated quote; close the phrase with `\"`"));
+        };
+        let phrase = self.rest()[1..=end].to_string();
+        self.pos += end + 2;
+        if phrase.trim().is_empty() {
+            return Err(self.error(start, "empty phrase"));
+        }
+
+        let prefix = self.peek() == Some('*');
+        if prefix {
+            self.pos += 1;
+        }
+        Ok((phrase, prefix))
+    }
+
+    /// A bare word up to the next whitespace; `None` for punctuation-only words
+    fn word(&mut self, start: usize) -> Result<Option<(String, bool)>, SearchError> {
+        let len = self
+            .rest()
+            .find(char::is_whitespace)
+            .unwrap_or(self.rest().len());
+        let word = &self.rest()[..len];
+        self.pos += len;
+
+        let stem = word.trim_end_matches('*');
+        let prefix = stem.len() < word.len();
+        if prefix && stem.is_empty() {
+            return Err(self.error(start, "`*` must follow a term, as in `prog*`"));
+        }
+        if !stem.chars().any(char::is_alphanumeric) {
+            return Ok(None);
+        }
+        Ok(Some((stem.to_string(), prefix)))
+    }
+
+    /// `NEAR(term "phrase" ..., distance)`, returning the expression and its terms
+    fn near(&mut self) -> Result<(String, Vec<String>), SearchError> {
+        let start = self.pos;
+        self.pos += "NEAR(".len();
+        let mut terms = Vec::new();
+        let mut words = Vec::new();
+        let mut distance = DEFAULT_NEAR_DISTANCE;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    return Err(self.error(start, "NEAR( is missing its closing `)`"));
+                }
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(',') => {
+                    let number_start = self.pos;
+                    let Some(close) = self.rest().find(')') else {
+                        return Err(self.error(start, "NEAR( is missing its closing `)`"));
+                    };
+                    distance = self.rest()[1..close].trim().parse().map_err(|_| {
+                        self.error(
+                            number_start,
+                            "NEAR distance must be a number, as in `NEAR(deep work, 5)`",
+                        )
+                    })?;
+                    self.pos += close + 1;
+                    break;
+                }
+                Some('"') => {
+                    let (phrase, prefix) = self.phrase()?;
+                    terms.push(quote(&phrase, prefix));
+                    words.push(phrase);
+                }
+                Some(_) => {
+                    let len = self
+                        .rest()
+                        .find(|c: char| c.is_whitespace() || c == ',' || c == ')')
+                        .unwrap_or(self.rest().len());
+                    let word = &self.rest()[..len];
+                    self.pos += len;
+                    let stem = word.trim_end_matches('*');
+                    if stem.chars().any(char::is_alphanumeric) {
+                        terms.push(quote(stem, stem.len() < word.len()));
+                        words.push(stem.to_string());
+                    }
+                }
+            }
+        }
+
+        if terms.len() < 2 {
+            return Err(self.error(
+                start,
+                "NEAR needs at least two terms, as in `NEAR(deep work, 5)`",
+            ));
+        }
+        Ok((format!("NEAR({}, {distance})", terms.join(" ")), words))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fts(query: &str) -> String {
+        parse_search_query(query, false).unwrap().fts
+    }
+
+    fn error(query: &str) -> String {
+        parse_search_query(query, false).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_terms_are_escaped() {
+        assert_eq!(fts("deep work"), "\"deep\" AND \"work\"");
+        assert_eq!(
+            fts("C++ foo-bar what?"),
+            "\"C++\" AND \"foo-bar\" AND \"what?\""
+        );
+        assert_eq!(fts("say 5\"4 ++ ?"), "\"say\" AND \"5\"\"4\"");
+        assert_eq!(fts("NEAR AND not"), "\"NEAR\" AND \"AND\" AND \"not\"");
+    }
+
+    #[test]
+    fn test_phrases_prefixes_and_exclusions() {
+        assert_eq!(fts("\"deep work\" prog*"), "\"deep work\" AND \"prog\"*");
+        assert_eq!(fts("\"deep wo\"*"), "\"deep wo\"*");
+        assert_eq!(
+            fts("focus -email -\"open office\""),
+            "(\"focus\") NOT \"email\" NOT \"open office\""
+        );
+        assert_eq!(fts("pre - post"), "\"pre\" AND \"post\"");
+    }
+
+    #[test]
+    fn test_or_and_near() {
+        assert_eq!(
+            fts("focus cats OR dogs OR birds"),
+            "\"focus\" AND (\"cats\" OR \"dogs\" OR \"birds\")"
+        );
+        assert_eq!(fts("NEAR(deep work)"), "NEAR(\"deep\" \"work\", 10)");
+        assert_eq!(
+            fts("NEAR(deep \"flow state\" foc*, 3)"),
+            "NEAR(\"deep\" \"flow state\" \"foc\"*, 3)"
+        );
+    }
+
+    #[test]
+    fn test_terms_exclude_negations_and_filters() {
+        let parsed = parse_search_query(
+            "Deep \"Flow State\" -draft NEAR(foc* deep) title:x heading:Work",
+            false,
+        )
+        .unwrap();
+        assert_eq!(parsed.terms, vec!["deep", "flow state", "foc", "work"]);
+    }
+
+    #[test]
+    fn test_fields() {
+        let parsed = parse_search_query(
+            "heading:metrics title:\"Deep Work\" -tag:draft path:projects/ x:y",
+            false,
+        )
+        .unwrap();
+        assert_eq!(parsed.fts, "heading_path : \"metrics\" AND \"x:y\"");
+        assert_eq!(
+            parsed.filters,
+            vec![
+                Filter {
+                    field: FilterField::Title,
+                    value: "Deep Work".to_string(),
+                    negated: false
+                },
+                Filter {
+                    field: FilterField::Tag,
+                    value: "draft".to_string(),
+                    negated: true
+                },
+                Filter {
+                    field: FilterField::Path,
+                    value: "projects/".to_string(),
+                    negated: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_short_terms_are_scanned() {
+        let scan = |text: &str, heading: bool| ScanTerm {
+            text: text.to_string(),
+            heading,
+        };
+
+        let parsed = parse_search_query("東京 tower Go OR golang -ad heading:AI", true).unwrap();
+        assert_eq!(parsed.fts, "\"tower\"");
+        assert_eq!(
+            parsed.scans,
+            vec![
+                vec![scan("東京", false)],
+                vec![scan("go", false), scan("golang", false)],
+                vec![scan("ai", true)],
+            ]
+        );
+        assert_eq!(parsed.excluded_scans, vec![scan("ad", false)]);
+        assert_eq!(parsed.terms, vec!["東京", "tower", "go", "golang", "ai"]);
+
+        // Without an FTS5 term, excluded terms are scanned too
+        let parsed = parse_search_query("東京 -station", true).unwrap();
+        assert_eq!(parsed.fts, "");
+        assert_eq!(parsed.excluded_scans, vec![scan("station", false)]);
+
+        // ...unless they cannot be
+        let parsed = parse_search_query("東京 -NEAR(a b)", true).unwrap();
+        assert_eq!(parsed.fts, "(\"東京\") NOT NEAR(\"a\" \"b\", 10)");
+        assert!(parsed.scans.is_empty());
+
+        let parsed = parse_search_query("東京 NEAR(a b) OR go", true).unwrap();
+        assert_eq!(parsed.fts, "(NEAR(\"a\" \"b\", 10) OR \"go\")");
+        assert_eq!(parsed.scans, vec![vec![scan("東京", false)]]);
+        assert_eq!(fts("東京"), "\"東京\"");
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        let message = error("deep \"work");
+        assert!(
+            message.contains("column 6: unterminated quote"),
+            "{message}"
+        );
+        assert!(message.ends_with("\n       ^"), "{message}");
+
+        assert!(error("").contains("empty"));
+        assert!(error("OR cats").contains("OR must join two search terms"));
+        assert!(error("cats OR").contains("column 6"));
+        assert!(error("cats OR -dogs").contains("OR can only join search terms"));
+        assert!(error("-cats").contains("excluded terms need at least one term"));
+        assert!(error("tag:x").contains("filters need at least one term"));
+        assert!(error("title: cats").contains("`title:` needs a value"));
+        assert!(error("* cats").contains("`*` must follow a term"));
+        assert!(error("NEAR(a b").contains("missing its closing"));
+        assert!(error("NEAR(a b, x)").contains("NEAR distance must be a number"));
+        assert!(error("NEAR(a)").contains("at least two terms"));
+        assert!(error("??").contains("no searchable terms"));
+    }
+}
@@ -0,0 +1,172 @@
+// Nearest chunks by embedding similarity
+use super::SearchOptions;
+use rusqlite::{Connection, OptionalExtension, Result};
+
+/// A chunk close in meaning to the query
+#[derive(Debug, Clone)]
+pub struct SemanticResult {
+    pub chunk_id: i64,
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub heading_path: Option<String>,
+    pub chunk_text: String,
+    /// Cosine similarity to the query, from -1 to 1
+    pub score: f64,
+}
+
+fn decode_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// The `options.limit` chunks of notes passing the note filters in `options`
+/// whose `model` embeddings have the highest cosine similarity to `query`,
+/// best first. Stored vectors are unit length, so only the query is
+/// normalized; vectors of another dimension are ignored.
+pub fn semantic_search(
+    conn: &Connection,
+    model: &str,
+    query: &[f32],
+    options: &SearchOptions,
+) -> Result<Vec<SemanticResult>> {
+    let norm = query.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 || options.limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut params = vec![model.to_string(), query.len().to_string()];
+    let mut conditions = vec![
+        "e.model = ?1".to_string(),
+        "e.dimensions = CAST(?2 AS INTEGER)".to_string(),
+    ];
+    conditions.extend(options.note_conditions("n", &mut params));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT e.chunk_id, e.vector FROM embeddings e
+         JOIN chunks c ON c.id = e.chunk_id
+         JOIN notes n ON n.id = c.note_id
+         WHERE {}",
+        conditions.join(" AND ")
+    ))?;
+    let mut scored: Vec<(i64, f64)> = Vec::new();
+    let mut rows = stmt.query(rusqlite::params_from_iter(params.iter()))?;
+    while let Some(row) = rows.next()? {
+        let vector = decode_vector(&row.get::<_, Vec<u8>>(1)?);
+        let dot: f32 = vector.iter().zip(query).map(|(a, b)| a * b).sum();
+        scored.push((row.get(0)?, f64::from(dot / norm)));
+    }
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.truncate(options.limit);
+
+    let mut details = conn.prepare(
+        "SELECT c.note_id, n.path, n.title, c.heading_path, c.text
+         FROM chunks c JOIN notes n ON n.id = c.note_id
+         WHERE c.id = ?1",
+    )?;
+    let mut results = Vec::with_capacity(scored.len());
+    for (chunk_id, score) in scored {
+        let found = details
+            .query_row([chunk_id], |row| {
+                Ok(SemanticResult {
+                    chunk_id,
+                    note_id: row.get(0)?,
+                    note_path: row.get(1)?,
+                    note_title: row.get(2)?,
+                    heading_path: row.get(3)?,
+                    chunk_text: row.get(4)?,
+                    score,
+                })
+            })
+            .optional()?;
+        results.extend(found);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE chunks (id INTEGER PRIMARY KEY, note_id INTEGER, heading_path TEXT, text TEXT);
+             CREATE TABLE embeddings (chunk_id INTEGER PRIMARY KEY, model TEXT, hash TEXT, dimensions INTEGER, vector BLOB);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             INSERT INTO notes VALUES (1, 'a.md', 'A'), (2, 'b.md', 'B');
+             INSERT INTO tags (note_id, tag, display) VALUES (2, 'work', 'work');
+             INSERT INTO chunks VALUES (1, 1, NULL, 'one'), (2, 2, 'B > Part', 'two'), (3, 2, NULL, 'three');",
+        )
+        .unwrap();
+        let vectors: [(i64, &str, Vec<f32>); 4] = [
+            (1, "m", vec![1.0, 0.0]),
+            (2, "m", vec![0.6, 0.8]),
+            (3, "m", vec![0.0, 1.0, 0.0]),
+            (9, "m", vec![1.0, 0.0]),
+        ];
+        for (chunk_id, model, vector) in vectors {
+            let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+            conn.execute(
+                "INSERT INTO embeddings VALUES (?1, ?2, 'h', ?3, ?4)",
+                rusqlite::params![chunk_id, model, vector.len() as i64, blob],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn limit(limit: usize) -> SearchOptions {
+        SearchOptions {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_semantic_search_ranks_by_cosine() {
+        let conn = setup_db();
+
+        let results = semantic_search(&conn, "m", &[0.0, 2.0], &limit(10)).unwrap();
+        // Chunk 3 has another dimension and chunk 9 no longer exists
+        let ids: Vec<i64> = results.iter().map(|r| r.chunk_id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert!((results[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(results[0].heading_path.as_deref(), Some("B > Part"));
+        assert_eq!(results[1].score, 0.0);
+
+        assert_eq!(
+            semantic_search(&conn, "m", &[1.0, 0.0], &limit(1)).unwrap()[0].note_path,
+            "a.md"
+        );
+        assert!(semantic_search(&conn, "other", &[1.0, 0.0], &limit(10))
+            .unwrap()
+            .is_empty());
+        assert!(semantic_search(&conn, "m", &[0.0, 0.0], &limit(10))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_semantic_search_applies_note_filters() {
+        let conn = setup_db();
+
+        let tagged = SearchOptions {
+            tags: vec!["work".to_string()],
+            ..limit(10)
+        };
+        let results = semantic_search(&conn, "m", &[1.0, 0.0], &tagged).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note_path, "b.md");
+
+        let path = SearchOptions {
+            paths: vec!["a*".to_string()],
+            ..limit(10)
+        };
+        let results = semantic_search(&conn, "m", &[0.0, 1.0], &path).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, 1);
+    }
+}
@@ -0,0 +1,459 @@
+use super::tags::preferred_displays;
+use rusqlite::{Connection, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Share of a tag's notes that must also carry another tag for the two to
+/// count as almost always used together
+const COMPANION_CONFIDENCE: f64 = 0.8;
+/// Most similar tagged notes consulted when suggesting tags from content
+const SIMILAR_NOTES: usize = 5;
+/// Most frequent terms of a note used to look for similar notes
+const CONTENT_TERMS: usize = 12;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "because", "been", "before", "being", "between", "could", "does",
+    "each", "from", "have", "here", "into", "just", "like", "make", "many", "more", "most", "much",
+    "only", "other", "over", "same", "should", "some", "such", "than", "that", "their", "them",
+    "then", "there", "these", "they", "this", "those", "through", "very", "what", "when", "where",
+    "which", "while", "will", "with", "would", "your",
+];
+
+#[derive(Debug, Clone)]
+pub struct TagAnalysisOptions {
+    /// Minimum number of notes a pair of tags must share to be reported
+    pub min_count: usize,
+    /// Notes with at most this many tags get suggestions
+    pub max_note_tags: usize,
+    /// Suggested tags per note
+    pub suggestions_per_note: usize,
+    /// Maximum entries in each section
+    pub limit: usize,
+}
+
+impl Default for TagAnalysisOptions {
+    fn default() -> Self {
+        Self {
+            min_count: 2,
+            max_note_tags: 1,
+            suggestions_per_note: 3,
+            limit: 20,
+        }
+    }
+}
+
+/// Two tags used on the same notes
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagPair {
+    pub tag_a: String,
+    pub tag_b: String,
+    /// Notes carrying both tags
+    pub together: usize,
+    pub count_a: usize,
+    pub count_b: usize,
+    /// Pointwise mutual information in bits: how much more often the tags
+    /// appear together than they would by chance
+    pub pmi: f64,
+}
+
+/// `tag` is almost always accompanied by `implies`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagImplication {
+    pub tag: String,
+    pub implies: String,
+    pub together: usize,
+    pub tag_count: usize,
+    /// Share of `tag`'s notes that also carry `implies`
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedTag {
+    pub tag: String,
+    /// Mean of `link_score` and `content_score`
+    pub score: f64,
+    /// Share of linked notes (either direction) carrying the tag
+    pub link_score: f64,
+    /// Rank-weighted share of the most similar tagged notes carrying the tag
+    pub content_score: f64,
+}
+
+/// Tag suggestions for an untagged or lightly tagged note
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteTagSuggestions {
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub current_tags: Vec<String>,
+    pub suggestions: Vec<SuggestedTag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagAnalysis {
+    pub total_notes: usize,
+    pub tagged_notes: usize,
+    pub tag_count: usize,
+    /// Pairs sharing at least `min_count` notes, highest PMI first
+    pub pairs: Vec<TagPair>,
+    pub companions: Vec<TagImplication>,
+    pub suggestions: Vec<NoteTagSuggestions>,
+}
+
+/// Analyze how tags are used together and suggest tags for notes with few
+/// of them, from the tags of linked notes and of notes with similar content
+pub fn analyze_tags(conn: &Connection, options: &TagAnalysisOptions) -> Result<TagAnalysis> {
+    let displays = preferred_displays(conn)?;
+    let display = |key: &str| {
+        displays
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    };
+
+    let mut note_tags: HashMap<i64, BTreeSet<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT note_id, tag FROM tags")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (note_id, tag) = row?;
+        note_tags.entry(note_id).or_default().insert(tag);
+    }
+
+    let total_notes: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
+    let total_notes = total_notes as usize;
+
+    let mut tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut pair_counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for tags in note_tags.values() {
+        for (idx, tag) in tags.iter().enumerate() {
+            *tag_counts.entry(tag).or_default() += 1;
+            for other in tags.iter().skip(idx + 1) {
+                *pair_counts.entry((tag, other)).or_default() += 1;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut companions = Vec::new();
+    for (&(a, b), &together) in &pair_counts {
+        if together < options.min_count.max(1) {
+            continue;
+        }
+        let (count_a, count_b) = (tag_counts[a], tag_counts[b]);
+        let pmi = ((together * total_notes) as f64 / (count_a * count_b) as f64).log2();
+        if pmi > 0.0 {
+            pairs.push(TagPair {
+                tag_a: display(a),
+                tag_b: display(b),
+                together,
+                count_a,
+                count_b,
+                pmi,
+            });
+        }
+
+        for (tag, implies, tag_count) in [(a, b, count_a), (b, a, count_b)] {
+            let confidence = together as f64 / tag_count as f64;
+            if confidence >= COMPANION_CONFIDENCE {
+                companions.push(TagImplication {
+                    tag: display(tag),
+                    implies: display(implies),
+                    together,
+                    tag_count,
+                    confidence,
+                });
+            }
+        }
+    }
+
+    pairs.sort_by(|x, y| {
+        y.pmi
+            .total_cmp(&x.pmi)
+            .then(y.together.cmp(&x.together))
+            .then_with(|| (&x.tag_a, &x.tag_b).cmp(&(&y.tag_a, &y.tag_b)))
+    });
+    pairs.truncate(options.limit);
+    companions.sort_by(|x, y| {
+        y.confidence
+            .total_cmp(&x.confidence)
+            .then(y.together.cmp(&x.together))
+            .then_with(|| (&x.tag, &x.implies).cmp(&(&y.tag, &y.implies)))
+    });
+    companions.truncate(options.limit);
+
+    let suggestions = suggest_tags(conn, &note_tags, options)?
+        .into_iter()
+        .map(|mut note| {
+            note.current_tags = note.current_tags.iter().map(|t| display(t)).collect();
+            for suggestion in &mut note.suggestions {
+                suggestion.tag = display(&suggestion.tag);
+            }
+            note
+        })
+        .collect();
+
+    Ok(TagAnalysis {
+        total_notes,
+        tagged_notes: note_tags.len(),
+        tag_count: tag_counts.len(),
+        pairs,
+        companions,
+        suggestions,
+    })
+}
+
+/// Suggestions keyed by tag key for notes with at most `max_note_tags` tags
+fn suggest_tags(
+    conn: &Connection,
+    note_tags: &HashMap<i64, BTreeSet<String>>,
+    options: &TagAnalysisOptions,
+) -> Result<Vec<NoteTagSuggestions>> {
+    let mut neighbors: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT src_note_id, dst_note_id FROM links
+         WHERE dst_note_id IS NOT NULL AND dst_note_id != src_note_id",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?;
+    for row in rows {
+        let (src, dst) = row?;
+        neighbors.entry(src).or_default().insert(dst);
+        neighbors.entry(dst).or_default().insert(src);
+    }
+
+    let mut stmt = conn.prepare("SELECT id, path, title FROM notes ORDER BY path")?;
+    let notes = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let no_tags = BTreeSet::new();
+    let mut results = Vec::new();
+    for (note_id, note_path, note_title) in notes {
+        let current = note_tags.get(&note_id).unwrap_or(&no_tags);
+        if current.len() > options.max_note_tags {
+            continue;
+        }
+
+        let mut link_scores: HashMap<&str, f64> = HashMap::new();
+        if let Some(linked) = neighbors.get(&note_id) {
+            for neighbor in linked {
+                for tag in note_tags.get(neighbor).unwrap_or(&no_tags) {
+                    *link_scores.entry(tag).or_default() += 1.0 / linked.len() as f64;
+                }
+            }
+        }
+
+        let mut content_scores: HashMap<&str, f64> = HashMap::new();
+        let similar: Vec<i64> = similar_notes(conn, note_id)?
+            .into_iter()
+            .filter(|id| note_tags.contains_key(id))
+            .take(SIMILAR_NOTES)
+            .collect();
+        let total_weight: f64 = (1..=similar.len()).map(|rank| 1.0 / rank as f64).sum();
+        for (idx, similar_id) in similar.iter().enumerate() {
+            for tag in &note_tags[similar_id] {
+                *content_scores.entry(tag).or_default() += 1.0 / (idx + 1) as f64 / total_weight;
+            }
+        }
+
+        let candidates: BTreeSet<&str> = link_scores
+            .keys()
+            .chain(content_scores.keys())
+            .copied()
+            .filter(|tag| !current.contains(*tag))
+            .collect();
+        let mut suggestions: Vec<SuggestedTag> = candidates
+            .into_iter()
+            .map(|tag| {
+                let link_score = link_scores.get(tag).copied().unwrap_or(0.0);
+                let content_score = content_scores.get(tag).copied().unwrap_or(0.0);
+                SuggestedTag {
+                    tag: tag.to_string(),
+                    score: (link_score + content_score) / 2.0,
+                    link_score,
+                    content_score,
+                }
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.tag.cmp(&b.tag)));
+        suggestions.truncate(options.suggestions_per_note);
+
+        if !suggestions.is_empty() {
+            results.push(NoteTagSuggestions {
+                note_id,
+                note_path,
+                note_title,
+                current_tags: current.iter().cloned().collect(),
+                suggestions,
+            });
+        }
+        if results.len() >= options.limit {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Other notes ranked by BM25 against the note's most frequent terms
+fn similar_notes(conn: &Connection, note_id: i64) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT text FROM chunks WHERE note_id = ?1")?;
+    let texts = stmt
+        .query_map([note_id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    let terms = content_terms(&texts.join("\n"));
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let fts_query = terms
+        .iter()
+        .map(|term| format!("\"{term}\""))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    // bm25() cannot be aggregated, so keep the best chunk of each note here
+    let mut stmt = conn.prepare(
+        "SELECT c.note_id
+         FROM fts_chunks
+         JOIN chunks c ON fts_chunks.rowid = c.id
+         WHERE fts_chunks MATCH ?1 AND c.note_id != ?2
+         ORDER BY rank, c.note_id
+         LIMIT 200",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![fts_query, note_id], |row| {
+        row.get::<_, i64>(0)
+    })?;
+
+    let mut ranked = Vec::new();
+    for row in rows {
+        let id = row?;
+        if !ranked.contains(&id) {
+            ranked.push(id);
+        }
+    }
+    Ok(ranked)
+}
+
+/// Most frequent words of at least four letters, skipping stopwords and numbers
+fn content_terms(text: &str) -> Vec<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if word.chars().count() >= 4
+            && !word.chars().all(|c| c.is_numeric())
+            && !STOPWORDS.contains(&word.as_str())
+        {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut terms: Vec<(String, usize)> = counts.into_iter().collect();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    terms
+        .into_iter()
+        .take(CONTENT_TERMS)
+        .map(|(term, _)| term)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             CREATE TABLE links (
+                id INTEGER PRIMARY KEY, src_note_id INTEGER, dst_text TEXT, dst_note_id INTEGER
+             );
+             CREATE TABLE chunks (id INTEGER PRIMARY KEY, note_id INTEGER, text TEXT);
+             CREATE VIRTUAL TABLE fts_chunks USING fts5(
+                note_id UNINDEXED, text, content=chunks, content_rowid=id
+             );
+             INSERT INTO notes VALUES
+                (1, 'a.md', 'A'), (2, 'b.md', 'B'), (3, 'c.md', 'C'),
+                (4, 'd.md', 'D'), (5, 'e.md', 'E');
+             INSERT INTO tags (note_id, tag, display) VALUES
+                (1, 'rust', 'Rust'), (1, 'programming', 'programming'),
+                (2, 'rust', 'Rust'), (2, 'programming', 'programming'),
+                (3, 'programming', 'programming'), (3, 'python', 'python'),
+                (4, 'cooking', 'cooking');
+             INSERT INTO links (src_note_id, dst_text, dst_note_id) VALUES
+                (5, 'A', 1), (2, 'E', 5);
+             INSERT INTO chunks VALUES
+                (1, 1, 'Ownership and borrowing in rust compilers'),
+                (2, 2, 'Lifetimes and borrowing rules'),
+                (3, 3, 'Python scripting'),
+                (4, 4, 'Pasta recipes and sauces'),
+                (5, 5, 'Notes on borrowing and ownership in compilers');
+             INSERT INTO fts_chunks(fts_chunks) VALUES ('rebuild');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_analyze_tags_pairs_and_companions() {
+        let conn = setup_db();
+        let analysis = analyze_tags(&conn, &TagAnalysisOptions::default()).unwrap();
+
+        assert_eq!(
+            (
+                analysis.total_notes,
+                analysis.tagged_notes,
+                analysis.tag_count
+            ),
+            (5, 4, 4)
+        );
+        assert_eq!(analysis.pairs.len(), 1);
+        let pair = &analysis.pairs[0];
+        assert_eq!(
+            (pair.tag_a.as_str(), pair.tag_b.as_str()),
+            ("programming", "Rust")
+        );
+        assert_eq!((pair.together, pair.count_a, pair.count_b), (2, 3, 2));
+        assert!((pair.pmi - (10.0f64 / 6.0).log2()).abs() < 1e-9);
+
+        let companions: Vec<(&str, &str)> = analysis
+            .companions
+            .iter()
+            .map(|c| (c.tag.as_str(), c.implies.as_str()))
+            .collect();
+        assert_eq!(companions, vec![("Rust", "programming")]);
+    }
+
+    #[test]
+    fn test_analyze_tags_suggestions_from_links_and_content() {
+        let conn = setup_db();
+        let analysis = analyze_tags(&conn, &TagAnalysisOptions::default()).unwrap();
+
+        let untagged = analysis
+            .suggestions
+            .iter()
+            .find(|s| s.note_path == "e.md")
+            .unwrap();
+        assert!(untagged.current_tags.is_empty());
+        let top = &untagged.suggestions[0];
+        assert!(top.tag == "Rust" || top.tag == "programming");
+        assert_eq!(top.link_score, 1.0);
+        assert!(top.content_score > 0.0);
+        assert!(!untagged.suggestions.iter().any(|s| s.tag == "cooking"));
+
+        // Notes with more tags than `max_note_tags` get no suggestions
+        assert!(!analysis.suggestions.iter().any(|s| s.note_path == "a.md"));
+    }
+
+    #[test]
+    fn test_content_terms() {
+        let terms = content_terms("The borrow checker checks borrow rules, 2024 rules with this");
+        assert_eq!(terms, vec!["borrow", "rules", "checker", "checks"]);
+    }
+}
@@ -1,4 +1,7 @@
+use super::fuzzy::levenshtein;
+use crate::parser::tag_key;
 use rusqlite::{Connection, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone)]
 pub struct TagResult {
@@ -9,7 +12,7 @@ pub struct TagResult {
 }
 
 fn get_tags_for_note(conn: &Connection, note_id: i64) -> Result<Vec<String>> {
-    let mut tag_stmt = conn.prepare("SELECT tag FROM tags WHERE note_id = ?1 ORDER BY tag")?;
+    let mut tag_stmt = conn.prepare("SELECT display FROM tags WHERE note_id = ?1 ORDER BY tag")?;
     let tags = tag_stmt.query_map([note_id], |row| row.get::<_, String>(0))?;
 
     let mut tag_list = Vec::new();
@@ -63,86 +66,350 @@ fn comma_placeholders(count: usize) -> String {
         .join(",")
 }
 
-/// List all unique tags in the vault
+/// List all unique tags in the vault, each in its most used spelling
 pub fn list_tags(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT tag FROM tags ORDER BY tag")?;
+    let mut stmt = conn.prepare(
+        "SELECT (
+            SELECT d.display FROM tags d WHERE d.tag = t.tag
+            GROUP BY d.display ORDER BY COUNT(*) DESC, d.display LIMIT 1
+         )
+         FROM tags t
+         GROUP BY t.tag
+         ORDER BY t.tag",
+    )?;
 
     let results = stmt.query_map([], |row| row.get::<_, String>(0))?;
     collect_string_column(results)
 }
 
-/// Get all notes that have a specific tag
-pub fn get_notes_by_tag(conn: &Connection, tag: &str) -> Result<Vec<TagResult>> {
-    let mut stmt = conn.prepare(
-        "SELECT DISTINCT
-            n.id,
-            n.path,
-            n.title
-         FROM notes n
-         JOIN tags t ON n.id = t.note_id
-         WHERE t.tag = ?1
-         ORDER BY n.path",
-    )?;
+/// Most used spelling of each tag key
+pub(crate) fn preferred_displays(conn: &Connection) -> Result<HashMap<String, String>> {
+    Ok(list_tags(conn)?
+        .into_iter()
+        .map(|display| (tag_key(&display), display))
+        .collect())
+}
 
-    let note_rows = stmt.query_map([tag], map_note_row)?;
-    collect_tag_results(conn, note_rows)
+/// Nested tags are written `parent/child`: matches the tag in `?{param}` and
+/// all of its descendants
+pub(crate) fn tag_condition(column: &str, param: usize) -> String {
+    format!("({column} = ?{param} OR substr({column}, 1, length(?{param}) + 1) = ?{param} || '/')")
 }
 
-/// Get all notes that have ALL of the specified tags (AND intersection)
-pub fn get_notes_by_tags_and(conn: &Connection, tags: &[&str]) -> Result<Vec<TagResult>> {
+/// Case-folded tag from user input (`#Project/` -> `project`)
+pub(crate) fn normalize_tag_query(tag: &str) -> String {
+    tag_key(tag.trim().trim_end_matches('/'))
+}
+
+/// Notes matching all (`require_all`) or any of the tags
+fn get_notes_by_tags(
+    conn: &Connection,
+    tags: &[&str],
+    exact: bool,
+    require_all: bool,
+) -> Result<Vec<TagResult>> {
     if tags.is_empty() {
         return Ok(Vec::new());
     }
 
-    let placeholders = comma_placeholders(tags.len());
+    let condition = if exact {
+        let placeholders = comma_placeholders(tags.len());
+        if require_all {
+            format!(
+                "n.id IN (
+                    SELECT note_id FROM tags WHERE tag IN ({placeholders})
+                    GROUP BY note_id HAVING COUNT(DISTINCT tag) = {}
+                )",
+                tags.len()
+            )
+        } else {
+            format!("n.id IN (SELECT note_id FROM tags WHERE tag IN ({placeholders}))")
+        }
+    } else {
+        (1..=tags.len())
+            .map(|param| {
+                format!(
+                    "n.id IN (SELECT t.note_id FROM tags t WHERE {})",
+                    tag_condition("t.tag", param)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(if require_all { " AND " } else { " OR " })
+    };
     let query = format!(
-        "SELECT DISTINCT
+        "SELECT
             n.id,
             n.path,
             n.title
          FROM notes n
-         WHERE n.id IN (
-             SELECT note_id FROM tags WHERE tag IN ({})
-             GROUP BY note_id HAVING COUNT(DISTINCT tag) = {}
-         )
-         ORDER BY n.path",
-        placeholders,
-        tags.len()
+         WHERE {condition}
+         ORDER BY n.path"
     );
 
     let mut stmt = conn.prepare(&query)?;
 
-    let params: Vec<&dyn rusqlite::ToSql> =
-        tags.iter().map(|t| t as &dyn rusqlite::ToSql).collect();
-    let rows = stmt.query_map(params.as_slice(), map_note_row)?;
+    let tags: Vec<String> = tags.iter().map(|t| normalize_tag_query(t)).collect();
+    let rows = stmt.query_map(rusqlite::params_from_iter(tags), map_note_row)?;
     collect_tag_results(conn, rows)
 }
 
+/// Get all notes that have a specific tag, or one of its nested tags
+/// (`project` matches `project/alpha`) unless `exact`. Tags match case-insensitively.
+pub fn get_notes_by_tag(conn: &Connection, tag: &str, exact: bool) -> Result<Vec<TagResult>> {
+    get_notes_by_tags(conn, &[tag], exact, false)
+}
+
+/// Get all notes that have ALL of the specified tags (AND intersection)
+pub fn get_notes_by_tags_and(
+    conn: &Connection,
+    tags: &[&str],
+    exact: bool,
+) -> Result<Vec<TagResult>> {
+    get_notes_by_tags(conn, tags, exact, true)
+}
+
 /// Get all notes that have ANY of the specified tags (OR union)
-pub fn get_notes_by_tags_or(conn: &Connection, tags: &[&str]) -> Result<Vec<TagResult>> {
-    if tags.is_empty() {
-        return Ok(Vec::new());
+pub fn get_notes_by_tags_or(
+    conn: &Connection,
+    tags: &[&str],
+    exact: bool,
+) -> Result<Vec<TagResult>> {
+    get_notes_by_tags(conn, tags, exact, false)
+}
+
+/// A level of the tag hierarchy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagNode {
+    /// Last path segment (`backend` for `project/alpha/backend`)
+    pub name: String,
+    /// Full tag path in its most used spelling
+    pub tag: String,
+    /// Notes carrying this tag or any nested tag
+    pub note_count: usize,
+    /// Notes carrying exactly this tag
+    pub direct_count: usize,
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Default)]
+struct TagLevel {
+    display: Option<String>,
+    /// Notes with the tag or a descendant
+    all: BTreeSet<i64>,
+    /// Notes with exactly the tag
+    direct: BTreeSet<i64>,
+}
+
+/// Build the tag hierarchy from `/`-separated tags. With `root`, only that
+/// tag's subtree is returned.
+pub fn tag_tree(conn: &Connection, root: Option<&str>) -> Result<Vec<TagNode>> {
+    let displays = preferred_displays(conn)?;
+    let mut stmt = conn.prepare("SELECT DISTINCT note_id, tag FROM tags")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    // Keyed by case-folded tag path
+    let mut levels: BTreeMap<String, TagLevel> = BTreeMap::new();
+    for row in rows {
+        let (note_id, key) = row?;
+        let display = displays.get(&key).cloned().unwrap_or_else(|| key.clone());
+        let key_segments: Vec<&str> = key.split('/').collect();
+        let display_segments: Vec<&str> = display.split('/').collect();
+
+        for depth in 1..=key_segments.len() {
+            let level = levels.entry(key_segments[..depth].join("/")).or_default();
+            level.all.insert(note_id);
+            if depth == key_segments.len() {
+                level.direct.insert(note_id);
+                level.display = Some(display.clone());
+            } else if level.display.is_none() && display_segments.len() == key_segments.len() {
+                level.display = Some(display_segments[..depth].join("/"));
+            }
+        }
     }
 
-    let placeholders = comma_placeholders(tags.len());
-    let query = format!(
-        "SELECT DISTINCT
-            n.id,
-            n.path,
-            n.title
-         FROM notes n
-         WHERE n.id IN (
-             SELECT note_id FROM tags WHERE tag IN ({placeholders})
-         )
-         ORDER BY n.path"
-    );
+    let nodes = build_tag_nodes(&levels, None);
+    match root.map(normalize_tag_query) {
+        Some(root) => Ok(find_tag_node(nodes, &root).into_iter().collect()),
+        None => Ok(nodes),
+    }
+}
 
-    let mut stmt = conn.prepare(&query)?;
+fn build_tag_nodes(levels: &BTreeMap<String, TagLevel>, parent: Option<&str>) -> Vec<TagNode> {
+    levels
+        .iter()
+        .filter(|(key, _)| match parent {
+            Some(parent) => key
+                .strip_prefix(parent)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|rest| !rest.contains('/')),
+            None => !key.contains('/'),
+        })
+        .map(|(key, level)| {
+            let tag = level.display.clone().unwrap_or_else(|| key.clone());
+            TagNode {
+                name: tag.rsplit('/').next().unwrap_or(&tag).to_string(),
+                tag,
+                note_count: level.all.len(),
+                direct_count: level.direct.len(),
+                children: build_tag_nodes(levels, Some(key)),
+            }
+        })
+        .collect()
+}
 
-    let params: Vec<&dyn rusqlite::ToSql> =
-        tags.iter().map(|t| t as &dyn rusqlite::ToSql).collect();
-    let rows = stmt.query_map(params.as_slice(), map_note_row)?;
-    collect_tag_results(conn, rows)
+fn find_tag_node(nodes: Vec<TagNode>, key: &str) -> Option<TagNode> {
+    nodes.into_iter().find_map(|node| {
+        let node_key = tag_key(&node.tag);
+        if node_key == key {
+            Some(node)
+        } else if key.starts_with(&format!("{node_key}/")) {
+            find_tag_node(node.children, key)
+        } else {
+            None
+        }
+    })
+}
+
+/// One spelling of a tag and the number of notes using it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagVariant {
+    pub tag: String,
+    pub note_count: usize,
+}
+
+/// Tags that are likely meant to be the same, most used spelling first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarTagGroup {
+    pub variants: Vec<TagVariant>,
+}
+
+impl SimilarTagGroup {
+    pub fn note_count(&self) -> usize {
+        self.variants.iter().map(|v| v.note_count).sum()
+    }
+}
+
+/// Words of the last tag segment with `-`/`_` separators removed and plurals
+/// reduced (`Meeting-Notes` -> `["meeting", "note"]`)
+fn tag_words(key: &str) -> Vec<String> {
+    let segment = key.rsplit('/').next().unwrap_or(key);
+    segment
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            if word.len() > 4 && word.ends_with("ies") {
+                format!("{}y", &word[..word.len() - 3])
+            } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                word[..word.len() - 1].to_string()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Whether two distinct tag keys under the same parent look like duplicates:
+/// same words up to plurals and separators, one extending the other
+/// (`meeting` / `meeting-notes`), or a one-letter typo in longer tags
+fn tags_look_alike(a: &str, b: &str) -> bool {
+    let parent = |key: &str| key.rsplit_once('/').map(|(parent, _)| parent.to_string());
+    if parent(a) != parent(b) {
+        return false;
+    }
+
+    let (words_a, words_b) = (tag_words(a), tag_words(b));
+    let (shorter, longer) = if words_a.len() <= words_b.len() {
+        (&words_a, &words_b)
+    } else {
+        (&words_b, &words_a)
+    };
+    if !shorter.is_empty() && longer.starts_with(shorter) && shorter.concat().len() >= 5 {
+        return true;
+    }
+
+    let (joined_a, joined_b) = (words_a.concat(), words_b.concat());
+    joined_a.chars().count() >= 5
+        && joined_b.chars().count() >= 5
+        && levenshtein(&joined_a, &joined_b) <= 1
+}
+
+/// Find groups of likely duplicate tags: case variants of the same tag
+/// (`Meeting` / `meeting`), plurals (`meetings`), separators and extensions
+/// (`meeting-notes`) and one-letter typos. Nested tags are only compared with
+/// their siblings. Groups are ordered by total usage.
+pub fn find_similar_tags(conn: &Connection) -> Result<Vec<SimilarTagGroup>> {
+    let mut stmt = conn.prepare(
+        "SELECT tag, display, COUNT(DISTINCT note_id)
+         FROM tags
+         GROUP BY tag, display
+         ORDER BY tag, COUNT(DISTINCT note_id) DESC, display",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+
+    let mut keys: Vec<String> = Vec::new();
+    let mut variants: Vec<Vec<TagVariant>> = Vec::new();
+    for row in rows {
+        let (key, display, count) = row?;
+        if keys.last() != Some(&key) {
+            keys.push(key);
+            variants.push(Vec::new());
+        }
+        if let Some(list) = variants.last_mut() {
+            list.push(TagVariant {
+                tag: display,
+                note_count: count as usize,
+            });
+        }
+    }
+
+    // Union-find over tag keys
+    let mut parent: Vec<usize> = (0..keys.len()).collect();
+    fn find(parent: &mut [usize], idx: usize) -> usize {
+        let mut root = idx;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[idx] = root;
+        root
+    }
+    for i in 0..keys.len() {
+        for j in i + 1..keys.len() {
+            if tags_look_alike(&keys[i], &keys[j]) {
+                let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+                parent[root_j] = root_i;
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<TagVariant>> = BTreeMap::new();
+    for (idx, list) in variants.into_iter().enumerate() {
+        let root = find(&mut parent, idx);
+        groups.entry(root).or_default().extend(list);
+    }
+
+    let mut groups: Vec<SimilarTagGroup> = groups
+        .into_values()
+        .filter(|variants| variants.len() > 1)
+        .map(|mut variants| {
+            variants.sort_by(|a, b| b.note_count.cmp(&a.note_count).then(a.tag.cmp(&b.tag)));
+            SimilarTagGroup { variants }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.note_count()
+            .cmp(&a.note_count())
+            .then_with(|| a.variants[0].tag.cmp(&b.variants[0].tag))
+    });
+
+    Ok(groups)
 }
 
 #[cfg(test)]
@@ -157,7 +424,7 @@ mod tests {
         )
         .unwrap();
         conn.execute(
-            "CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT)",
+            "CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT)",
             [],
         )
         .unwrap();
@@ -175,12 +442,21 @@ mod tests {
         .unwrap();
 
         // Insert tags
-        conn.execute("INSERT INTO tags (note_id, tag) VALUES (1, 'tag1')", [])
-            .unwrap();
-        conn.execute("INSERT INTO tags (note_id, tag) VALUES (1, 'tag2')", [])
-            .unwrap();
-        conn.execute("INSERT INTO tags (note_id, tag) VALUES (2, 'tag1')", [])
-            .unwrap();
+        conn.execute(
+            "INSERT INTO tags (note_id, tag, display) VALUES (1, 'tag1', 'tag1')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO tags (note_id, tag, display) VALUES (1, 'tag2', 'tag2')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO tags (note_id, tag, display) VALUES (2, 'tag1', 'tag1')",
+            [],
+        )
+        .unwrap();
     }
 
     #[test]
@@ -215,7 +491,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let notes = get_notes_by_tag(&conn, "tag1").unwrap();
+        let notes = get_notes_by_tag(&conn, "tag1", false).unwrap();
         assert_eq!(notes.len(), 2);
     }
 
@@ -224,7 +500,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let notes = get_notes_by_tags_and(&conn, &["tag1", "tag2"]).unwrap();
+        let notes = get_notes_by_tags_and(&conn, &["tag1", "tag2"], false).unwrap();
         assert_eq!(notes.len(), 1); // Only test1 has both tags
     }
 
@@ -233,7 +509,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let notes = get_notes_by_tags_and(&conn, &[]).unwrap();
+        let notes = get_notes_by_tags_and(&conn, &[], false).unwrap();
         assert!(notes.is_empty());
     }
 
@@ -242,7 +518,7 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let notes = get_notes_by_tags_or(&conn, &["tag1", "tag2"]).unwrap();
+        let notes = get_notes_by_tags_or(&conn, &["tag1", "tag2"], false).unwrap();
         assert_eq!(notes.len(), 2);
     }
 
@@ -251,7 +527,128 @@ mod tests {
         let conn = Connection::open_in_memory().unwrap();
         setup_test_db(&conn);
 
-        let notes = get_notes_by_tags_or(&conn, &[]).unwrap();
+        let notes = get_notes_by_tags_or(&conn, &[], false).unwrap();
         assert!(notes.is_empty());
     }
+
+    fn setup_nested_db(conn: &Connection) {
+        setup_test_db(conn);
+        conn.execute_batch(
+            "INSERT INTO notes (path, title) VALUES ('alpha.md', 'Alpha');
+             INSERT INTO notes (path, title) VALUES ('backend.md', 'Backend');
+             INSERT INTO notes (path, title) VALUES ('projector.md', 'Projector');
+             INSERT INTO tags (note_id, tag, display) VALUES (3, 'project/alpha', 'project/alpha');
+             INSERT INTO tags (note_id, tag, display) VALUES (4, 'project/alpha/backend', 'project/alpha/backend');
+             INSERT INTO tags (note_id, tag, display) VALUES (4, 'tag1', 'tag1');
+             INSERT INTO tags (note_id, tag, display) VALUES (5, 'projector', 'projector');",
+        )
+        .unwrap();
+    }
+
+    fn paths(notes: &[TagResult]) -> Vec<&str> {
+        notes.iter().map(|n| n.note_path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_get_notes_by_tag_includes_nested_tags() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_nested_db(&conn);
+
+        let notes = get_notes_by_tag(&conn, "#project", false).unwrap();
+        assert_eq!(paths(&notes), vec!["alpha.md", "backend.md"]);
+
+        let notes = get_notes_by_tag(&conn, "project/alpha", true).unwrap();
+        assert_eq!(paths(&notes), vec!["alpha.md"]);
+        assert!(get_notes_by_tag(&conn, "project", true).unwrap().is_empty());
+
+        let notes = get_notes_by_tags_and(&conn, &["project", "tag1"], false).unwrap();
+        assert_eq!(paths(&notes), vec!["backend.md"]);
+    }
+
+    #[test]
+    fn test_tag_tree_counts() {
+        let conn = Connection::open_in_memory().unwrap();
+        setup_nested_db(&conn);
+
+        let tree = tag_tree(&conn, None).unwrap();
+        let roots: Vec<(&str, usize, usize)> = tree
+            .iter()
+            .map(|n| (n.tag.as_str(), n.note_count, n.direct_count))
+            .collect();
+        assert_eq!(
+            roots,
+            vec![
+                ("project", 2, 0),
+                ("projector", 1, 1),
+                ("tag1", 3, 3),
+                ("tag2", 1, 1)
+            ]
+        );
+
+        let alpha = &tree[0].children[0];
+        assert_eq!((alpha.name.as_str(), alpha.note_count), ("alpha", 2));
+        assert_eq!(alpha.children[0].tag, "project/alpha/backend");
+
+        let subtree = tag_tree(&conn, Some("project/alpha")).unwrap();
+        assert_eq!(subtree.len(), 1);
+        assert_eq!(subtree[0].note_count, 2);
+        assert!(tag_tree(&conn, Some("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_similar_tags() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             INSERT INTO tags (note_id, tag, display) VALUES
+                (1, 'meeting', 'meeting'), (2, 'meeting', 'meeting'), (3, 'meeting', 'Meeting'),
+                (4, 'meetings', 'meetings'),
+                (5, 'meeting-notes', 'Meeting-notes'),
+                (1, 'project', 'project'), (2, 'project', 'Project'),
+                (1, 'area/health', 'area/health'), (2, 'health', 'health'),
+                (3, 'pkm', 'pkm'), (4, 'pkms', 'pkms');",
+        )
+        .unwrap();
+
+        let groups = find_similar_tags(&conn).unwrap();
+        let summary: Vec<Vec<(&str, usize)>> = groups
+            .iter()
+            .map(|g| {
+                g.variants
+                    .iter()
+                    .map(|v| (v.tag.as_str(), v.note_count))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                vec![
+                    ("meeting", 2),
+                    ("Meeting", 1),
+                    ("Meeting-notes", 1),
+                    ("meetings", 1)
+                ],
+                vec![("Project", 1), ("project", 1)],
+            ]
+        );
+        assert_eq!(groups[0].note_count(), 5);
+    }
+
+    #[test]
+    fn test_tags_keep_most_used_spelling() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tags (id INTEGER PRIMARY KEY, note_id INTEGER, tag TEXT, display TEXT);
+             INSERT INTO tags (note_id, tag, display) VALUES
+                (1, 'work/q1', 'Work/Q1'), (2, 'work/q1', 'Work/Q1'), (3, 'work/q1', 'work/q1');",
+        )
+        .unwrap();
+
+        assert_eq!(list_tags(&conn).unwrap(), vec!["Work/Q1"]);
+        let tree = tag_tree(&conn, Some("#WORK")).unwrap();
+        assert_eq!(tree[0].tag, "Work");
+        assert_eq!(tree[0].children[0].name, "Q1");
+        assert_eq!(tree[0].note_count, 3);
+    }
 }
@@ -0,0 +1,255 @@
+use rusqlite::{Connection, Result};
+use std::collections::HashMap;
+
+use super::tags::{normalize_tag_query, tag_condition};
+use super::SearchOptions;
+
+/// SQL condition for tasks that are neither done (`x`) nor cancelled (`-`)
+const OPEN_CONDITION: &str = "t.status NOT IN ('x', 'X', '-')";
+
+/// Filters for `search tasks`. Dates are `YYYY-MM-DD` strings.
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub open: bool,
+    pub overdue: bool,
+    pub due_before: Option<String>,
+    /// Tag on the task itself, matching nested tags too
+    pub tag: Option<String>,
+    /// Note filters as in `search notes`; `limit` and `snippet_tokens` are unused
+    pub notes: SearchOptions,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskResult {
+    pub id: i64,
+    pub note_id: i64,
+    pub note_path: String,
+    pub note_title: String,
+    pub status: String,
+    pub text: String,
+    pub line: i64,
+    pub heading: Option<String>,
+    pub due: Option<String>,
+    pub scheduled: Option<String>,
+    pub done: Option<String>,
+    pub priority: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl TaskResult {
+    pub fn is_open(&self) -> bool {
+        !matches!(self.status.as_str(), "x" | "X" | "-")
+    }
+}
+
+/// Search tasks across the vault. `today` decides which tasks are overdue.
+/// Results are ordered by due date (undated last), then path and line.
+pub fn search_tasks(
+    conn: &Connection,
+    filter: &TaskFilter,
+    today: &str,
+) -> Result<Vec<TaskResult>> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+
+    if filter.open || filter.overdue {
+        conditions.push(OPEN_CONDITION.to_string());
+    }
+    if filter.overdue {
+        params.push(today.to_string());
+        conditions.push(format!("t.due IS NOT NULL AND t.due < ?{}", params.len()));
+    }
+    if let Some(due_before) = &filter.due_before {
+        params.push(due_before.clone());
+        conditions.push(format!("t.due IS NOT NULL AND t.due < ?{}", params.len()));
+    }
+    if let Some(tag) = &filter.tag {
+        params.push(normalize_tag_query(tag));
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM task_tags tt WHERE tt.task_id = t.id AND {})",
+            tag_condition("tt.tag", params.len())
+        ));
+    }
+    conditions.extend(filter.notes.note_conditions("n", &mut params));
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let query = format!(
+        "SELECT t.id, n.id, n.path, n.title, t.status, t.text, t.line, t.heading,
+                t.due, t.scheduled, t.done, t.priority
+         FROM tasks t
+         JOIN notes n ON t.note_id = n.id
+         {where_clause}
+         ORDER BY t.due IS NULL, t.due, n.path, t.line
+         LIMIT {}",
+        filter.limit
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+        Ok(TaskResult {
+            id: row.get(0)?,
+            note_id: row.get(1)?,
+            note_path: row.get(2)?,
+            note_title: row.get(3)?,
+            status: row.get(4)?,
+            text: row.get(5)?,
+            line: row.get(6)?,
+            heading: row.get(7)?,
+            due: row.get(8)?,
+            scheduled: row.get(9)?,
+            done: row.get(10)?,
+            priority: row.get(11)?,
+            tags: Vec::new(),
+        })
+    })?;
+
+    let mut tasks = rows.collect::<Result<Vec<_>>>()?;
+    let mut tags = get_task_tags(conn)?;
+    for task in &mut tasks {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+    }
+
+    Ok(tasks)
+}
+
+fn get_task_tags(conn: &Connection) -> Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare("SELECT task_id, tag FROM task_tags ORDER BY id")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?;
+
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (task_id, tag) = row?;
+        tags.entry(task_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, path TEXT, title TEXT);
+             CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY, note_id INTEGER, status TEXT, text TEXT,
+                line INTEGER, byte_offset INTEGER, heading TEXT, due TEXT,
+                scheduled TEXT, done TEXT, priority TEXT
+             );
+             CREATE TABLE task_tags (id INTEGER PRIMARY KEY, task_id INTEGER, tag TEXT);
+             INSERT INTO notes VALUES (1, 'work/Plan.md', 'Plan');
+             INSERT INTO notes VALUES (2, 'Home.md', 'Home');
+             INSERT INTO notes VALUES (3, 'work_old/Notes.md', 'Notes');
+             INSERT INTO tasks VALUES
+                (1, 1, ' ', 'Write report', 3, 10, 'Todo', '2024-01-10', NULL, NULL, 'high'),
+                (2, 1, 'x', 'Send mail', 4, 30, 'Todo', '2024-01-05', NULL, '2024-01-04', NULL),
+                (3, 1, '/', 'Review', 5, 50, 'Todo', '2024-02-01', NULL, NULL, NULL),
+                (4, 2, ' ', 'Water plants', 2, 5, NULL, NULL, NULL, NULL, NULL),
+                (5, 2, '-', 'Old idea', 3, 20, NULL, '2023-12-01', NULL, NULL, NULL),
+                (6, 3, ' ', 'Archive', 1, 0, NULL, NULL, NULL, NULL, NULL);
+             INSERT INTO task_tags VALUES
+                (1, 1, 'work'), (2, 4, 'home'), (3, 3, 'work/review'), (4, 6, 'workshop');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(tasks: &[TaskResult]) -> Vec<i64> {
+        tasks.iter().map(|t| t.id).collect()
+    }
+
+    fn filter() -> TaskFilter {
+        TaskFilter {
+            limit: 50,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_tasks_all_ordered_by_due() {
+        let conn = setup_db();
+        let tasks = search_tasks(&conn, &filter(), "2024-01-20").unwrap();
+
+        assert_eq!(ids(&tasks), vec![5, 2, 1, 3, 4, 6]);
+        assert_eq!(tasks[2].tags, vec!["work"]);
+        assert!(!tasks[0].is_open());
+    }
+
+    #[test]
+    fn test_search_tasks_open_and_overdue() {
+        let conn = setup_db();
+        let open = TaskFilter {
+            open: true,
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &open, "2024-01-20").unwrap()),
+            vec![1, 3, 4, 6]
+        );
+
+        let overdue = TaskFilter {
+            overdue: true,
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &overdue, "2024-01-20").unwrap()),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_search_tasks_due_before_tag_path_and_limit() {
+        let conn = setup_db();
+
+        let due_before = TaskFilter {
+            due_before: Some("2024-01-08".to_string()),
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &due_before, "2024-01-20").unwrap()),
+            vec![5, 2]
+        );
+
+        let tag = TaskFilter {
+            tag: Some("#Work".to_string()),
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &tag, "2024-01-20").unwrap()),
+            vec![1, 3]
+        );
+
+        // `_` in a folder name is not a wildcard
+        let folder = TaskFilter {
+            notes: SearchOptions {
+                folders: vec!["work".to_string()],
+                ..Default::default()
+            },
+            limit: 2,
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &folder, "2024-01-20").unwrap()),
+            vec![2, 1]
+        );
+
+        let path = TaskFilter {
+            notes: SearchOptions {
+                paths: vec!["work_*".to_string()],
+                ..Default::default()
+            },
+            ..filter()
+        };
+        assert_eq!(
+            ids(&search_tasks(&conn, &path, "2024-01-20").unwrap()),
+            vec![6]
+        );
+    }
+}
@@ -42,6 +42,7 @@ pub fn setup_test_config() -> Result<(TempDir, TempDir, Config)> {
         search: Default::default(),
         graph: Default::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     Ok((vault_dir, db_dir, config))
@@ -64,6 +64,7 @@ fn test_config_database_path_default() {
         search: SearchConfig::default(),
         graph: GraphConfig::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     let db_path = config.database_path();
@@ -82,6 +83,7 @@ fn test_config_database_path_custom() {
         search: SearchConfig::default(),
         graph: GraphConfig::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     let db_path = config.database_path();
@@ -98,6 +100,7 @@ fn test_config_config_dir() {
         search: SearchConfig::default(),
         graph: GraphConfig::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     let config_dir = config.config_dir();
@@ -116,6 +119,7 @@ fn test_config_log_dir_default() {
         search: SearchConfig::default(),
         graph: GraphConfig::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     let log_dir = config.log_dir();
@@ -134,6 +138,7 @@ fn test_config_log_dir_custom() {
         search: SearchConfig::default(),
         graph: GraphConfig::default(),
         llm: None,
+        embeddings: Default::default(),
     };
 
     let log_dir = config.log_dir();
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use obsidian_cli_inspector::db::Database;
+use obsidian_cli_inspector::db::{Database, SCHEMA_VERSION};
 use tempfile::TempDir;
 
 #[test]
@@ -12,7 +12,7 @@ fn test_database_open_and_initialize() -> Result<()> {
 
     // Check version was set
     let version = db.get_version()?;
-    assert_eq!(version, Some(1));
+    assert_eq!(version, Some(SCHEMA_VERSION));
 
     Ok(())
 }
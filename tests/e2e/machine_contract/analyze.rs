@@ -71,3 +71,40 @@ fn machine_contract_related() {
     assert_eq!(output["params"]["limit"], 5);
     insta::assert_json_snapshot!("machine_contract_related", normalize_for_snapshot(output));
 }
+
+#[test]
+#[ignore]
+fn machine_contract_analyze_tags() {
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "analyze",
+        "tags",
+        "--max-tags",
+        "2",
+        "--suggestions",
+        "2",
+        "--limit",
+        "3",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run analyze tags command");
+    validate_schema(&output, "analyze.tags");
+
+    let result = &output["result"];
+    assert_eq!(result["total_notes"], 12);
+    assert_eq!(result["pairs"][0]["tag_a"], "learning");
+    assert_eq!(result["pairs"][0]["tag_b"], "productivity");
+    let suggestions = result["suggestions"].as_array().unwrap();
+    assert!(!suggestions.is_empty());
+    assert!(suggestions
+        .iter()
+        .all(|note| note["current_tags"].as_array().unwrap().len() <= 2));
+    insta::assert_json_snapshot!(
+        "machine_contract_analyze_tags",
+        normalize_for_snapshot(output)
+    );
+}
@@ -66,3 +66,55 @@ fn machine_contract_broken_links() {
         normalize_for_snapshot(output)
     );
 }
+
+#[test]
+#[ignore]
+fn machine_contract_footnotes() {
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "diagnose",
+        "footnotes",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run footnotes command");
+    validate_schema(&output, "diagnose.footnotes");
+
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["note_path"], "Stoicism.md");
+    assert_eq!(items[0]["label"], "letters");
+    assert_eq!(items[0]["issue"], "missing_definition");
+    insta::assert_json_snapshot!("machine_contract_footnotes", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_tags_diagnose() {
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "diagnose",
+        "tags",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run tags command");
+    validate_schema(&output, "diagnose.tags");
+
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    let variants = items[0]["variants"].as_array().unwrap();
+    assert_eq!(variants.len(), 2);
+    assert_eq!(variants[0]["tag"], "Reflections");
+    assert_eq!(variants[1]["tag"], "reflection");
+    insta::assert_json_snapshot!(
+        "machine_contract_tags_diagnose",
+        normalize_for_snapshot(output)
+    );
+}
@@ -74,10 +74,174 @@ fn machine_contract_search() {
     assert_eq!(output["params"]["limit"], 20);
     assert!(output["result"]["total"].is_number());
     assert!(output["result"]["items"].is_array());
+    assert_eq!(output["params"]["snippet_tokens"], 16);
+    let first = &output["result"]["items"][0];
+    let snippet = first["snippet"].as_str().unwrap();
+    let start = first["snippet_matches"][0]["start"].as_u64().unwrap() as usize;
+    let end = first["snippet_matches"][0]["end"].as_u64().unwrap() as usize;
+    assert!(snippet[start..end].eq_ignore_ascii_case("productivity"));
 
     insta::assert_json_snapshot!("machine_contract_search", normalize_for_snapshot(output));
 }
 
+#[test]
+#[ignore]
+fn machine_contract_search_filtered() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "notes",
+        "productivity",
+        "--tag",
+        "focus",
+        "--tag",
+        "learning",
+        "--path",
+        "D*",
+        "--prop",
+        "created>=2024-01-01",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run search command");
+    validate_schema(&output, "search.notes");
+
+    assert_eq!(output["params"]["tags"][1], "learning");
+    let items = output["result"]["items"].as_array().unwrap();
+    assert!(!items.is_empty());
+    assert!(items.iter().all(|item| item["note_path"] == "Deep Work.md"));
+
+    insta::assert_json_snapshot!(
+        "machine_contract_search_filtered",
+        normalize_for_snapshot(output)
+    );
+}
+
+#[test]
+#[ignore]
+fn machine_contract_search_suggestions() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "notes",
+        "prodcutivity OR linkd",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run search command");
+    validate_schema(&output, "search.notes");
+
+    assert_eq!(output["result"]["total"], 0);
+    let suggestions = output["result"]["suggestions"].as_array().unwrap();
+    assert_eq!(suggestions[0]["term"], "prodcutivity");
+    assert_eq!(suggestions[0]["suggestion"], "productivity");
+    assert_eq!(suggestions[0]["distance"], 2);
+    let linkd: Vec<&str> = suggestions
+        .iter()
+        .filter(|s| s["term"] == "linkd")
+        .map(|s| s["suggestion"].as_str().unwrap())
+        .collect();
+    assert_eq!(linkd, vec!["links", "link"]);
+
+    insta::assert_json_snapshot!(
+        "machine_contract_search_suggestions",
+        normalize_for_snapshot(output)
+    );
+}
+
+#[test]
+#[ignore]
+fn machine_contract_search_group_by_note() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "notes",
+        "productivity",
+        "--group-by",
+        "note",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run search command");
+    validate_schema(&output, "search.notes");
+    assert_eq!(output["params"]["group_by"], "note");
+
+    // Scores include a recency boost that depends on file times, so check
+    // the shape and ordering instead of snapshotting
+    let items = output["result"]["items"].as_array().unwrap();
+    assert!(!items.is_empty());
+    let mut paths: Vec<&str> = items
+        .iter()
+        .map(|item| item["note_path"].as_str().unwrap())
+        .collect();
+    let scores: Vec<f64> = items
+        .iter()
+        .map(|item| item["score"].as_f64().unwrap())
+        .collect();
+    assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+    for item in items {
+        assert!(item["chunk_count"].as_u64().unwrap() >= 1);
+        assert!(item["score"].as_f64() >= item["relevance"].as_f64());
+        assert!(item["snippet"].is_string());
+        assert!(item["heading_paths"].is_array());
+        for boost in ["title", "heading", "recency", "inlinks"] {
+            assert!(item["boosts"][boost].is_number());
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths.len(), items.len(), "one result per note");
+}
+
+#[test]
+#[ignore]
+fn machine_contract_grep() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "grep",
+        r"\[\[Pomodoro \w+\]\]",
+        "--context",
+        "1",
+        "--tag",
+        "productivity",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run grep command");
+    validate_schema(&output, "search.grep");
+
+    let items = output["result"]["items"].as_array().unwrap();
+    assert!(!items.is_empty());
+    for item in items {
+        let text = item["text"].as_str().unwrap();
+        let start = item["matches"][0]["start"].as_u64().unwrap() as usize;
+        assert!(text[start..].starts_with("[[Pomodoro Technique]]"));
+    }
+
+    insta::assert_json_snapshot!("machine_contract_grep", normalize_for_snapshot(output));
+}
+
 #[test]
 #[ignore]
 fn machine_contract_backlinks() {
@@ -196,3 +360,200 @@ fn machine_contract_tags_list() {
     assert_eq!(output["params"]["list"], true);
     insta::assert_json_snapshot!("machine_contract_tags_list", normalize_for_snapshot(output));
 }
+
+#[test]
+#[ignore]
+fn machine_contract_tags_tree() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "tags",
+        "projects",
+        "--tree",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run tags --tree command");
+    validate_schema(&output, "search.tags");
+
+    assert_eq!(output["params"]["tree"], true);
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["tag"], "projects");
+    assert_eq!(items[0]["note_count"], 4);
+    assert_eq!(items[0]["children"][0]["name"], "cli");
+    assert_eq!(items[0]["children"][0]["note_count"], 2);
+    insta::assert_json_snapshot!("machine_contract_tags_tree", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_mentions() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "mentions",
+        "Deep Work",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run mentions command");
+    validate_schema(&output, "search.mentions");
+
+    assert_eq!(output["params"]["note"], "Deep Work");
+    assert_eq!(output["params"]["limit"], 20);
+    assert!(output["result"]["items"].is_array());
+    insta::assert_json_snapshot!("machine_contract_mentions", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_blocks() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "blocks",
+        "^block123",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run blocks command");
+    validate_schema(&output, "search.blocks");
+
+    assert_eq!(output["params"]["id"], "^block123");
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["note_path"], "Projects.md");
+    insta::assert_json_snapshot!("machine_contract_blocks", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_tasks() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "tasks",
+        "--open",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run tasks command");
+    validate_schema(&output, "search.tasks");
+
+    assert_eq!(output["params"]["open"], true);
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["status"], "/");
+    assert_eq!(items[0]["due"], "2024-02-05");
+    assert_eq!(items[0]["tags"][0], "projects");
+    insta::assert_json_snapshot!("machine_contract_tasks", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_callouts() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "callouts",
+        "--type",
+        "warning",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run callouts command");
+    validate_schema(&output, "search.callouts");
+
+    assert_eq!(output["params"]["type"], "warning");
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["note_path"], "Software Architecture.md");
+    assert_eq!(items[0]["title"], "Concurrency");
+    assert_eq!(items[0]["fold"], "collapsed");
+    assert_eq!(items[0]["depth"], 2);
+    insta::assert_json_snapshot!("machine_contract_callouts", normalize_for_snapshot(output));
+}
+
+#[test]
+#[ignore]
+fn machine_contract_properties() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "properties",
+        "rating>=4",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run properties command");
+    validate_schema(&output, "search.properties");
+
+    assert_eq!(output["params"]["filters"][0], "rating>=4");
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["note_path"], "Book Notes.md");
+    assert_eq!(items[0]["fields"][0]["source"], "inline");
+    insta::assert_json_snapshot!(
+        "machine_contract_properties",
+        normalize_for_snapshot(output)
+    );
+}
+
+#[test]
+#[ignore]
+fn machine_contract_dql() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "query",
+        "dql",
+        "TABLE rating, length(file.outlinks) AS links FROM #books OR \"Projects\" SORT file.name",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run dql command");
+    validate_schema(&output, "query.dql");
+
+    let result = &output["result"];
+    assert_eq!(result["type"], "table");
+    assert_eq!(result["columns"][1], "links");
+    let items = result["items"].as_array().unwrap();
+    assert_eq!(items[0]["note_path"], "Book Notes.md");
+    assert_eq!(items[0]["values"]["rating"], 5);
+    insta::assert_json_snapshot!("machine_contract_dql", normalize_for_snapshot(output));
+}
@@ -1,5 +1,5 @@
 use crate::e2e_tests::helpers::{
-    get_test_config_path, run_command, run_command_json, validate_schema,
+    bootstrap_test_db, get_test_config_path, run_command, run_command_json, validate_schema,
 };
 
 // #[test]
@@ -67,3 +67,35 @@ fn contract_config_flag() {
     assert!(success, "Command with --config flag should succeed");
     assert!(!stdout.is_empty(), "Output should be present");
 }
+
+#[test]
+#[ignore]
+fn contract_ambiguous_note_error() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let (success, _stdout, stderr) = run_command(&[
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "search",
+        "backlinks",
+        "Notes",
+    ]);
+    assert!(!success, "ambiguous note argument should fail");
+
+    let error: serde_json::Value =
+        serde_json::from_str(stderr.trim()).expect("error output should be JSON");
+    assert_eq!(error["error"]["code"], 3);
+    assert_eq!(error["error"]["kind"], "ambiguous_note");
+    assert_eq!(error["error"]["identifier"], "Notes");
+
+    let paths: Vec<&str> = error["error"]["candidates"]
+        .as_array()
+        .expect("candidates should be an array")
+        .iter()
+        .filter_map(|c| c["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["Book Notes.md", "Daily Notes.md"]);
+}
@@ -75,3 +75,29 @@ fn machine_contract_describe() {
     assert_eq!(output["params"]["filename"], "Home");
     insta::assert_json_snapshot!("machine_contract_describe", normalize_for_snapshot(output));
 }
+
+#[test]
+#[ignore]
+fn machine_contract_outline() {
+    bootstrap_test_db();
+
+    let config_path = get_test_config_path().to_string_lossy().to_string();
+    let args = vec![
+        "--output",
+        "json",
+        "--config",
+        &config_path,
+        "view",
+        "outline",
+        "Projects",
+    ];
+
+    let output = run_command_json(&args).expect("Failed to run outline command");
+    validate_schema(&output, "view.outline");
+
+    assert_eq!(output["params"]["note"], "Projects");
+    let items = output["result"]["items"].as_array().unwrap();
+    assert_eq!(items[0]["level"], 1);
+    assert_eq!(items[0]["text"], "Projects");
+    insta::assert_json_snapshot!("machine_contract_outline", normalize_for_snapshot(output));
+}
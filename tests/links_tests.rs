@@ -2,6 +2,7 @@ mod common;
 
 use anyhow::Result;
 use obsidian_cli_inspector::commands::*;
+use obsidian_cli_inspector::query::AmbiguousNoteError;
 
 // CLI equivalent: cargo run -- --config test-config.toml backlinks "Home.md"
 // CLI equivalent: cargo run -- --config test-config.toml backlinks "Ideas.md"
@@ -115,3 +116,80 @@ fn test_bidirectional_links() -> Result<()> {
 
     Ok(())
 }
+
+// CLI equivalent: cargo run -- --config test-config.toml search backlinks "Index"
+#[test]
+fn test_backlinks_ambiguous_note() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::create_dir_all(vault_dir.path().join("work"))?;
+    std::fs::create_dir_all(vault_dir.path().join("life"))?;
+    std::fs::write(vault_dir.path().join("work/Index.md"), "# Work\n")?;
+    std::fs::write(vault_dir.path().join("life/Index.md"), "# Life\n")?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    // Two notes share the basename, so the resolver refuses to guess
+    let err = get_backlinks(&config, "Index", None).unwrap_err();
+    let ambiguous = err
+        .downcast_ref::<AmbiguousNoteError>()
+        .expect("expected an ambiguous note error");
+    let paths: Vec<&str> = ambiguous
+        .candidates
+        .iter()
+        .map(|c| c.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["life/Index.md", "work/Index.md"]);
+
+    // A full path is unambiguous
+    get_backlinks(&config, "work/Index", None)?;
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml diagnose broken-links
+#[test]
+fn test_broken_links_missing_heading() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Reading.md"),
+        "# Reading\n\nSee [[Deep Work#Deep Work]] and [[Deep Work#Shallow Work]].",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let broken = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::diagnose_broken_links)?;
+    let missing: Vec<_> = broken
+        .iter()
+        .filter(|l| l.status == "missing_heading")
+        .collect();
+
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].target, "Deep Work#Shallow Work");
+    assert_eq!(missing[0].candidates, vec!["Deep Work"]);
+
+    diagnose_broken_links_cmd(&config, None)?;
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml view outline "Projects"
+#[test]
+fn test_view_outline() -> Result<()> {
+    let (_vault_dir, _db_dir, config) = common::setup_test_config()?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    show_outline(&config, "Projects", None)?;
+    show_outline(&config, "NonExistent", None)?;
+
+    Ok(())
+}
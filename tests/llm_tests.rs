@@ -0,0 +1,590 @@
+mod common;
+
+use anyhow::Result;
+use obsidian_cli_inspector::commands::*;
+use obsidian_cli_inspector::config::{Config, EmbeddingProvider, LlmConfig};
+use obsidian_cli_inspector::db::Database;
+use obsidian_cli_inspector::machine_contract::ResultDataBuilder;
+use obsidian_cli_inspector::query::SearchOptions;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// Local HTTP server answering every request with `respond(path, body)`
+struct StubServer {
+    url: String,
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl StubServer {
+    fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&str, &Value) -> (u16, Value) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+                let (status, response) = respond(&path, &body);
+                recorded.lock().unwrap().push((path, body));
+                let response = response.to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                );
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn llm_config(&self) -> LlmConfig {
+        LlmConfig {
+            api_url: self.url.clone(),
+            model: "stub-chat".to_string(),
+            embedding_model: Some("stub-embed".to_string()),
+            api_key_env: None,
+            timeout_seconds: 5,
+        }
+    }
+}
+
+/// Words the stub embedding counts; each is one dimension
+const STUB_VOCABULARY: [&str; 6] = ["focus", "learning", "idea", "project", "home", "work"];
+
+/// `/embeddings` answered with keyword counts, so similar wording means similar vectors
+fn stub_embeddings(path: &str, body: &Value) -> (u16, Value) {
+    if !path.ends_with("/embeddings") {
+        return (404, serde_json::json!({"error": "not found"}));
+    }
+    let data: Vec<Value> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let text = input.as_str().unwrap().to_lowercase();
+            let embedding: Vec<f32> = STUB_VOCABULARY
+                .iter()
+                .map(|word| text.matches(word).count() as f32)
+                .collect();
+            serde_json::json!({"index": index, "embedding": embedding})
+        })
+        .collect();
+    (200, serde_json::json!({"data": data}))
+}
+
+fn embedded_chunks(config: &Config) -> Result<(i64, i64)> {
+    let db = Database::open(config.database_path())?;
+    Ok(db.conn().execute_query(|conn| {
+        conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM chunks),
+                    (SELECT COUNT(*) FROM embeddings WHERE model = 'stub-embed')",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    })?)
+}
+
+#[test]
+fn test_embed_only_changed_chunks() -> Result<()> {
+    let server = StubServer::start(stub_embeddings);
+    let (vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.llm = Some(server.llm_config());
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    embed_vault(&config, false, 2, None)?;
+    let (chunks, embedded) = embedded_chunks(&config)?;
+    assert_eq!(chunks, embedded);
+    let requests = server.requests();
+    assert_eq!(requests.len(), (chunks as usize).div_ceil(2));
+    assert!(requests
+        .iter()
+        .all(|(path, body)| { path == "/v1/embeddings" && body["model"] == "stub-embed" }));
+
+    // Nothing changed: no request
+    embed_vault(&config, false, 2, None)?;
+    assert_eq!(server.requests().len(), requests.len());
+
+    // A reindexed note keeps the vectors of its unchanged chunks
+    std::fs::write(
+        vault_dir.path().join("Deep Work.md"),
+        "# Deep Work\n\nFocus and productivity strategies.\n\n## Rules\n\nWork deeply on one project.",
+    )?;
+    index_vault(&config, false, false, false, None)?;
+    embed_vault(&config, false, 2, None)?;
+    let new_requests = &server.requests()[requests.len()..];
+    let inputs: Vec<&str> = new_requests
+        .iter()
+        .flat_map(|(_, body)| body["input"].as_array().unwrap())
+        .map(|input| input.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        inputs,
+        vec!["Deep Work\n# Deep Work > ## Rules\n\n## Rules\n\nWork deeply on one project."]
+    );
+    let (chunks, embedded) = embedded_chunks(&config)?;
+    assert_eq!(chunks, embedded);
+
+    // Semantic search ranks by meaning, with the stored vectors
+    let options = SearchOptions {
+        limit: 3,
+        ..Default::default()
+    };
+    semantic_search_vault(&config, "staying focused", &options, None)?;
+    let db = Database::open(config.database_path())?;
+    let (model, results) = obsidian_cli_inspector::commands::query_semantic::semantic_results(
+        &config,
+        &db,
+        "how do I focus",
+        &options,
+    )?;
+    assert_eq!(model, "stub-embed");
+    assert_eq!(results[0].note_path, "Deep Work.md");
+    assert!(results[0].score > results[1].score);
+
+    Ok(())
+}
+
+#[test]
+fn test_embed_requires_llm_config_and_reports_http_errors() -> Result<()> {
+    let (_vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let err = embed_vault(&config, false, 32, None).unwrap_err();
+    assert!(err.to_string().contains("[llm]"));
+
+    let server = StubServer::start(|_, _| (500, serde_json::json!({"error": "model not loaded"})));
+    config.llm = Some(server.llm_config());
+    let err = embed_vault(&config, false, 32, None).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("HTTP 500"), "{message}");
+    assert!(message.contains("model not loaded"), "{message}");
+
+    let db = Database::open(config.database_path())?;
+    let err = obsidian_cli_inspector::commands::query_semantic::semantic_results(
+        &config,
+        &db,
+        "focus",
+        &SearchOptions::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("index embed"));
+
+    Ok(())
+}
+
+#[test]
+fn test_hybrid_search_explains_contributions() -> Result<()> {
+    let server = StubServer::start(stub_embeddings);
+    let (_vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.llm = Some(server.llm_config());
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+    embed_vault(&config, false, 32, None)?;
+
+    let options = SearchOptions {
+        limit: 5,
+        ..Default::default()
+    };
+    hybrid_search_vault(
+        &config,
+        "learning",
+        &options,
+        &config.search.hybrid_weights(),
+        None,
+    )?;
+
+    let result = ResultDataBuilder::build_query_result_data(
+        &config,
+        "search.hybrid",
+        &serde_json::json!({"query": "learning", "limit": 5, "tags": ["learning"]}),
+    )?;
+    assert_eq!(result["model"], "stub-embed");
+    assert_eq!(result["weights"]["rrf_k"], 60.0);
+    let items = result["items"].as_array().unwrap();
+    assert!(!items.is_empty());
+    assert!(items.iter().all(|item| {
+        let path = item["note_path"].as_str().unwrap();
+        path == "Home.md" || path == "Learning Strategies.md"
+    }));
+    let top = &items[0];
+    assert_eq!(top["note_path"], "Learning Strategies.md");
+    let keyword = &top["explanation"]["keyword"];
+    let semantic = &top["explanation"]["semantic"];
+    assert_eq!(keyword["rank"], 1);
+    assert_eq!(semantic["rank"], 1);
+    assert!(semantic["similarity"].as_f64().unwrap() > 0.0);
+    let total =
+        keyword["contribution"].as_f64().unwrap() + semantic["contribution"].as_f64().unwrap();
+    assert!((top["score"].as_f64().unwrap() - total).abs() < 1e-12);
+
+    // A zero weight leaves the ranking out
+    let result = ResultDataBuilder::build_query_result_data(
+        &config,
+        "search.hybrid",
+        &serde_json::json!({"query": "learning", "semantic_weight": 0.0}),
+    )?;
+    let items = result["items"].as_array().unwrap();
+    assert!(!items.is_empty());
+    assert!(items
+        .iter()
+        .all(|item| item["explanation"]["semantic"].is_null()));
+
+    Ok(())
+}
+
+#[test]
+fn test_local_embeddings_work_offline() -> Result<()> {
+    let (_vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.embeddings.provider = EmbeddingProvider::Local;
+    config.embeddings.dimensions = 128;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    embed_vault(&config, false, 32, None)?;
+    embed_vault(&config, false, 32, None)?;
+    let db = Database::open(config.database_path())?;
+    let (chunks, embedded): (i64, i64) = db.conn().execute_query(|conn| {
+        conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM chunks),
+                    (SELECT COUNT(*) FROM embeddings WHERE model = 'local-tfidf-128' AND dimensions = 128)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    })?;
+    assert_eq!(chunks, embedded);
+
+    let options = SearchOptions {
+        limit: 3,
+        ..Default::default()
+    };
+    let (model, results) = obsidian_cli_inspector::commands::query_semantic::semantic_results(
+        &config,
+        &db,
+        "effective learning techniques",
+        &options,
+    )?;
+    assert_eq!(model, "local-tfidf-128");
+    assert_eq!(results[0].note_path, "Learning Strategies.md");
+
+    let result = ResultDataBuilder::build_query_result_data(
+        &config,
+        "search.hybrid",
+        &serde_json::json!({"query": "productivity", "limit": 3}),
+    )?;
+    assert_eq!(result["model"], "local-tfidf-128");
+    assert!(!result["items"].as_array().unwrap().is_empty());
+
+    Ok(())
+}
+
+/// `/chat/completions` answered with a fixed reply citing the first excerpt;
+/// `/embeddings` as in `stub_embeddings`
+fn stub_chat(path: &str, body: &Value) -> (u16, Value) {
+    if path.ends_with("/chat/completions") {
+        let reply = "Work on one project at a time [1].";
+        return (
+            200,
+            serde_json::json!({"choices": [{"message": {"role": "assistant", "content": reply}}]}),
+        );
+    }
+    stub_embeddings(path, body)
+}
+
+#[test]
+fn test_ask_cites_retrieved_chunks() -> Result<()> {
+    let server = StubServer::start(stub_chat);
+    let (_vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.llm = Some(server.llm_config());
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let options = SearchOptions {
+        limit: 3,
+        ..Default::default()
+    };
+    ask_vault(&config, "How do I focus?", &options, true, None)?;
+
+    // Without embeddings the context comes from full-text search
+    let (path, body) = server.requests().pop().unwrap();
+    assert_eq!(path, "/v1/chat/completions");
+    assert_eq!(body["model"], "stub-chat");
+    let prompt = body["messages"][1]["content"].as_str().unwrap();
+    assert!(
+        prompt.contains("[1] Deep Work#Deep Work (Deep Work.md)"),
+        "{prompt}"
+    );
+    assert!(prompt.ends_with("Question: How do I focus?"));
+
+    let result = ResultDataBuilder::build_query_result_data(
+        &config,
+        "ask",
+        &serde_json::json!({"question": "How do I focus?", "show_context": true}),
+    )?;
+    assert_eq!(result["retrieval"], "fts");
+    assert_eq!(result["answer"], "Work on one project at a time [1].");
+    assert_eq!(result["model"], "stub-chat");
+    let items = result["items"].as_array().unwrap();
+    assert_eq!(items[0]["link"], "Deep Work#Deep Work");
+    assert_eq!(items[0]["cited"], true);
+    assert!(items[0]["text"].as_str().unwrap().contains("Focus"));
+    assert!(items[1..].iter().all(|item| item["cited"] == false));
+
+    // With embeddings the context comes from hybrid search
+    embed_vault(&config, false, 32, None)?;
+    let result = ResultDataBuilder::build_query_result_data(
+        &config,
+        "ask",
+        &serde_json::json!({"question": "How do I focus?", "tags": ["learning"]}),
+    )?;
+    assert_eq!(result["retrieval"], "hybrid");
+    let items = result["items"].as_array().unwrap();
+    assert!(items.iter().all(|item| item.get("text").is_none()));
+    assert!(items.iter().all(|item| {
+        let path = item["note_path"].as_str().unwrap();
+        path == "Home.md" || path == "Learning Strategies.md"
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn test_ask_honors_timeout_and_requires_llm() -> Result<()> {
+    let (_vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let err = ask_vault(&config, "focus", &SearchOptions::default(), false, None).unwrap_err();
+    assert!(err.to_string().contains("[llm]"));
+
+    let server = StubServer::start(|path, body| {
+        std::thread::sleep(std::time::Duration::from_millis(2500));
+        stub_chat(path, body)
+    });
+    config.llm = Some(LlmConfig {
+        timeout_seconds: 1,
+        ..server.llm_config()
+    });
+    let started = std::time::Instant::now();
+    let err = ask_vault(&config, "focus", &SearchOptions::default(), false, None).unwrap_err();
+    assert!(started.elapsed() < std::time::Duration::from_millis(2500));
+    let message = format!("{err:#}");
+    assert!(message.contains("chat/completions"), "{message}");
+
+    Ok(())
+}
+
+/// `/chat/completions` answered with a summary, or with suggestions when the
+/// system prompt asks for JSON
+fn stub_analyze(path: &str, body: &Value) -> (u16, Value) {
+    let system = body["messages"][0]["content"].as_str().unwrap_or_default();
+    let reply = if system.contains("JSON") {
+        "```json\n{\"title\": \"Study Techniques\", \"tags\": [\"learning\", \"Productivity\", \"spaced repetition\"]}\n```"
+    } else {
+        "A short summary."
+    };
+    if path.ends_with("/chat/completions") {
+        return (
+            200,
+            serde_json::json!({"choices": [{"message": {"role": "assistant", "content": reply}}]}),
+        );
+    }
+    (404, serde_json::json!({"error": "not found"}))
+}
+
+#[test]
+fn test_analyze_summarize_caches_by_content() -> Result<()> {
+    let server = StubServer::start(stub_analyze);
+    let (vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.llm = Some(server.llm_config());
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+    let home = std::fs::read_to_string(vault_dir.path().join("Home.md"))?;
+
+    summarize_vault(
+        &config,
+        Some("Home"),
+        &SearchOptions::default(),
+        false,
+        None,
+    )?;
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let prompt = requests[0].1["messages"][1]["content"].as_str().unwrap();
+    assert!(prompt.starts_with("Title: Home\n\n"), "{prompt}");
+    assert!(prompt.contains("Welcome to my vault."));
+
+    // An unchanged note is answered from the cache
+    let summarize = |params: Value| {
+        ResultDataBuilder::build_query_result_data(&config, "analyze.summarize", &params)
+    };
+    let result = summarize(serde_json::json!({"note": "Home"}))?;
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(result["model"], "stub-chat");
+    assert_eq!(result["items"][0]["note_path"], "Home.md");
+    assert_eq!(result["items"][0]["summary"], "A short summary.");
+    assert_eq!(result["items"][0]["cached"], true);
+
+    // --refresh asks again
+    let result = summarize(serde_json::json!({"note": "Home", "refresh": true}))?;
+    assert_eq!(server.requests().len(), 2);
+    assert_eq!(result["items"][0]["cached"], false);
+
+    // Batch mode only requests the notes not cached yet
+    let result = summarize(serde_json::json!({"tags": ["learning"]}))?;
+    assert_eq!(server.requests().len(), 3);
+    let items = result["items"].as_array().unwrap();
+    let notes: Vec<(&str, bool)> = items
+        .iter()
+        .map(|item| {
+            (
+                item["note_path"].as_str().unwrap(),
+                item["cached"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        notes,
+        vec![("Home.md", true), ("Learning Strategies.md", false)]
+    );
+
+    // A changed note is summarized again
+    std::fs::write(
+        vault_dir.path().join("Deep Work.md"),
+        "# Deep Work\n\nFocus and productivity strategies.\n\n## Rules\n\nWork deeply.",
+    )?;
+    index_vault(&config, false, false, false, None)?;
+    summarize_vault(
+        &config,
+        Some("Deep Work"),
+        &SearchOptions::default(),
+        false,
+        None,
+    )?;
+    summarize_vault(
+        &config,
+        Some("Deep Work"),
+        &SearchOptions::default(),
+        false,
+        None,
+    )?;
+    assert_eq!(server.requests().len(), 4);
+
+    // A batch needs a filter, and a missing note is not an error
+    let err = summarize_vault(&config, None, &SearchOptions::default(), false, None).unwrap_err();
+    assert!(err.to_string().contains("--tag"));
+    let result = summarize(serde_json::json!({"note": "Missing"}))?;
+    assert_eq!(result["total"], 0);
+
+    // Nothing is written to the vault
+    assert_eq!(
+        std::fs::read_to_string(vault_dir.path().join("Home.md"))?,
+        home
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_analyze_suggest_tags() -> Result<()> {
+    let server = StubServer::start(stub_analyze);
+    let (vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+    let note = std::fs::read_to_string(vault_dir.path().join("Learning Strategies.md"))?;
+
+    let err = suggest_tags_vault(&config, "Learning Strategies", false, None).unwrap_err();
+    assert!(err.to_string().contains("[llm]"));
+
+    config.llm = Some(server.llm_config());
+    suggest_tags_vault(&config, "Learning Strategies", false, None)?;
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let prompt = requests[0].1["messages"][1]["content"].as_str().unwrap();
+    assert!(prompt.starts_with("Existing vault tags: "), "{prompt}");
+    assert!(prompt.contains("productivity"));
+
+    let params = serde_json::json!({"note": "Learning Strategies"});
+    let result =
+        ResultDataBuilder::build_query_result_data(&config, "analyze.suggest-tags", &params)?;
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(result["cached"], true);
+    assert_eq!(result["note_path"], "Learning Strategies.md");
+    assert_eq!(result["title"], "Study Techniques");
+    // The note already has #learning
+    assert_eq!(
+        result["items"],
+        serde_json::json!([
+            {"tag": "Productivity", "existing": true},
+            {"tag": "spaced-repetition", "existing": false}
+        ])
+    );
+
+    let params = serde_json::json!({"note": "Missing"});
+    let result =
+        ResultDataBuilder::build_query_result_data(&config, "analyze.suggest-tags", &params)?;
+    assert_eq!(result["total"], 0);
+    assert!(result["note_path"].is_null());
+
+    assert_eq!(
+        std::fs::read_to_string(vault_dir.path().join("Learning Strategies.md"))?,
+        note
+    );
+
+    Ok(())
+}
@@ -188,3 +188,21 @@ fn test_extract_markdown_links_nested_brackets() {
     // Should handle this gracefully - exact behavior may vary
     assert!(links.len() <= 1); // At most one link extracted
 }
+
+#[test]
+fn test_build_markdown_link_decodes_percent_escapes() {
+    let link = build_markdown_link("Deep Work", "Deep%20Work.md#Key%20Principles", false).unwrap();
+    assert_eq!(link.text, "Deep Work");
+    assert_eq!(link.heading_ref, Some("Key Principles".to_string()));
+
+    let link = build_markdown_link("Odd", "100%zz.md", false).unwrap();
+    assert_eq!(link.text, "100%zz");
+}
+
+#[test]
+fn test_extract_markdown_links_skips_footnotes() {
+    let content = "A claim[^1](p. 3) and [^2]: see [source](Source.md)";
+    let links = extract_markdown_links(content);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].text, "Source");
+}
@@ -2,6 +2,14 @@ mod common;
 
 use anyhow::Result;
 use obsidian_cli_inspector::commands::*;
+use obsidian_cli_inspector::query::{PropertyFilter, SearchOptions};
+
+fn search_options(limit: usize) -> SearchOptions {
+    SearchOptions {
+        limit,
+        ..Default::default()
+    }
+}
 
 #[test]
 fn test_search_vault() -> Result<()> {
@@ -12,8 +20,8 @@ fn test_search_vault() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test search
-    search_vault(&config, "productivity", 10, None)?;
-    search_vault(&config, "learning", 10, None)?;
+    search_vault(&config, "productivity", &search_options(10), None)?;
+    search_vault(&config, "learning", &search_options(10), None)?;
 
     Ok(())
 }
@@ -27,7 +35,7 @@ fn test_search_empty_query() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test empty query (should handle gracefully)
-    search_vault(&config, "", 10, None)?;
+    search_vault(&config, "", &search_options(10), None)?;
 
     Ok(())
 }
@@ -41,7 +49,7 @@ fn test_search_no_results() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test query with no matches
-    search_vault(&config, "xyznonexistentterm", 10, None)?;
+    search_vault(&config, "xyznonexistentterm", &search_options(10), None)?;
 
     Ok(())
 }
@@ -55,9 +63,9 @@ fn test_search_with_limit() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test search with different limits
-    search_vault(&config, "learning", 1, None)?;
-    search_vault(&config, "learning", 5, None)?;
-    search_vault(&config, "learning", 100, None)?;
+    search_vault(&config, "learning", &search_options(1), None)?;
+    search_vault(&config, "learning", &search_options(5), None)?;
+    search_vault(&config, "learning", &search_options(100), None)?;
 
     Ok(())
 }
@@ -71,9 +79,637 @@ fn test_search_various_terms() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test various search terms
-    search_vault(&config, "vault", 10, None)?;
-    search_vault(&config, "strategies", 10, None)?;
-    search_vault(&config, "work", 10, None)?;
+    search_vault(&config, "vault", &search_options(10), None)?;
+    search_vault(&config, "strategies", &search_options(10), None)?;
+    search_vault(&config, "work", &search_options(10), None)?;
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml search mentions "Projects"
+#[test]
+fn test_search_mentions() -> Result<()> {
+    let (_vault_dir, _db_dir, config) = common::setup_test_config()?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    // Home.md links to Projects, so only plain-text references count
+    list_unlinked_mentions(&config, "Projects", 10, None)?;
+    list_unlinked_mentions(&config, "NonExistent", 10, None)?;
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml search blocks "^focus-rule"
+#[test]
+fn test_search_blocks() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Rules.md"),
+        "# Rules\n\nWork in long, focused blocks. ^focus-rule\n\nSee [[Rules#^missing]].",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    find_blocks(&config, "^focus-rule", None, None)?;
+    find_blocks(&config, "focus-rule", Some("Rules"), None)?;
+    find_blocks(&config, "nope", None, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let broken = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::diagnose_broken_links)?;
+    let missing: Vec<_> = broken
+        .iter()
+        .filter(|l| l.status == "missing_block")
+        .collect();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].target, "Rules#^missing");
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml search tasks --open --tag work
+#[test]
+fn test_search_tasks() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Todo.md"),
+        "# Todo\n\n- [ ] Plan sprint #work 📅 2000-01-01\n- [x] Ship release #work ✅ 2000-01-02\n- [/] Tidy desk",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let filter = obsidian_cli_inspector::query::TaskFilter {
+        open: true,
+        tag: Some("work".to_string()),
+        limit: 10,
+        ..Default::default()
+    };
+    list_tasks(&config, &filter, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let overdue = obsidian_cli_inspector::query::TaskFilter {
+        overdue: true,
+        limit: 10,
+        ..Default::default()
+    };
+    let tasks = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::search_tasks(conn, &overdue, "2000-06-01")
+    })?;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].text, "Plan sprint #work");
+    assert_eq!(tasks[0].heading.as_deref(), Some("Todo"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_properties() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Review.md"),
+        "---\nrating: 3\nstatus: draft\n---\n# Review\n\n- Reviewed:: 2024-05-01\nScore [rating:: 4.5]",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let filters = vec![PropertyFilter::parse("rating>4").map_err(anyhow::Error::msg)?];
+    search_properties(&config, &filters, 10, None)?;
+    search_properties(&config, &[], 10, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let matches = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::find_notes_by_properties(conn, &filters, 10)
+    })?;
+    assert_eq!(matches.len(), 1);
+
+    let review = &matches[0];
+    let values: Vec<(&str, &str)> = review
+        .fields
+        .iter()
+        .map(|f| (f.value.as_str(), f.source.as_str()))
+        .collect();
+    assert_eq!(values, vec![("3", "frontmatter"), ("4.5", "inline")]);
+
+    let filters = vec![
+        PropertyFilter::parse("status=draft").map_err(anyhow::Error::msg)?,
+        PropertyFilter::parse("reviewed>=2024-01-01").map_err(anyhow::Error::msg)?,
+    ];
+    let matches = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::find_notes_by_properties(conn, &filters, 10)
+    })?;
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].note_title, "Review");
+
+    Ok(())
+}
+
+#[test]
+fn test_query_dql() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::create_dir_all(vault_dir.path().join("Books"))?;
+    std::fs::write(
+        vault_dir.path().join("Books/Deep Work.md"),
+        "---\nrating: 5\n---\n# Deep Work\n\n#books\n\nStatus:: done",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Books/Atomic Habits.md"),
+        "# Atomic Habits\n\n#books [rating:: 3] [status:: reading]",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    run_dql_query(
+        &config,
+        "TABLE rating, status FROM #books SORT rating DESC",
+        None,
+    )?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let result = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::run_dql(
+            conn,
+            r#"TABLE WITHOUT ID file.name AS "Book", rating FROM "Books" WHERE rating >= 3 AND file.size > 0 SORT rating DESC"#,
+        ))
+    })??;
+
+    assert_eq!(result.columns, vec!["Book", "rating"]);
+    let names: Vec<String> = result
+        .rows
+        .iter()
+        .map(|r| r.values[0].to_string())
+        .collect();
+    assert_eq!(names, vec!["Deep Work", "Atomic Habits"]);
+
+    let err = run_dql_query(&config, "LIST WHERE (rating", None).unwrap_err();
+    assert!(err.to_string().contains("expected ')'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_callouts() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("ADR.md"),
+        "# ADR\n\n> [!decision] Use SQLite\n> Simple to ship\n> > [!caution]- Locking\n> > One writer only\n\n> [!todo]\n> Benchmark sqlite",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    list_callouts(&config, Some("warning"), None, 10, None)?;
+    list_callouts(&config, None, Some("sqlite"), 10, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let warnings = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::search_callouts(conn, Some("warning"), None, 10)
+    })?;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, "caution");
+    assert_eq!(warnings[0].line, 5);
+    assert_eq!(warnings[0].fold.as_deref(), Some("collapsed"));
+
+    let decisions = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::search_callouts(conn, Some("decision"), None, 10)
+    })?;
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(warnings[0].parent_id, Some(decisions[0].id));
+
+    let sqlite = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::search_callouts(conn, None, Some("SQLite"), 10)
+    })?;
+    assert_eq!(sqlite.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_diagnose_footnotes() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Sources.md"),
+        "# Sources\n\nClaim[^1](p. 3) and another[^2].\n\n[^1]: [Origin](Origin.md)\n[^3]: Unused\n[^3]: Twice",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    diagnose_footnotes_cmd(&config, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let issues = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::diagnose_footnotes)?;
+    let summary: Vec<(&str, &str)> = issues
+        .iter()
+        .map(|i| (i.label.as_str(), i.issue.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("2", "missing_definition"),
+            ("3", "unused_definition"),
+            ("3", "duplicate_label")
+        ]
+    );
+
+    // The footnote reference is not a link; the link inside the definition is
+    let unresolved = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::get_unresolved_links)?;
+    let aliases: Vec<Option<&str>> = unresolved
+        .iter()
+        .filter(|l| l.note_path == "Sources.md")
+        .map(|l| l.alias.as_deref())
+        .collect();
+    assert_eq!(aliases, vec![Some("Origin")]);
+
+    Ok(())
+}
+
+#[test]
+fn test_search_snippets_and_match_offsets() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    let filler = "word ".repeat(60);
+    std::fs::write(
+        vault_dir.path().join("Long.md"),
+        format!("# Long\n\n{filler}zephyr {filler}"),
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    search_vault(&config, "zephyr", &search_options(10), None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let options = SearchOptions {
+        limit: 10,
+        snippet_tokens: 8,
+        ..Default::default()
+    };
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_chunks(
+            conn, "zephyr", &options,
+        ))
+    })??;
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert!(result.snippet.len() < 80);
+    assert!(result.snippet.starts_with("...") && result.snippet.ends_with("..."));
+    let (start, end) = result.snippet_matches[0];
+    assert_eq!(&result.snippet[start..end], "zephyr");
+    let (start, end) = result.matches[0];
+    assert_eq!(&result.chunk_text[start..end], "zephyr");
+    assert!(result.highlighted_snippet("<", ">").contains("<zephyr>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_query_syntax() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Cpp.md"),
+        "# Cpp\n\n## Templates\n\nNotes on C++ templates and foo-bar idioms. #lang/cpp",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    for query in [
+        "C++",
+        "foo-bar",
+        "what?",
+        "templ* -python",
+        "heading:templates tag:lang",
+    ] {
+        search_vault(&config, query, &search_options(10), None)?;
+    }
+
+    let err = search_vault(&config, "\"unbalanced", &search_options(10), None).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("unterminated quote"), "{message}");
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_chunks(
+            conn,
+            "heading:templates tag:lang",
+            &search_options(10),
+        ))
+    })??;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].note_path, "Cpp.md");
+
+    Ok(())
+}
+
+#[test]
+fn test_search_filters_apply_before_limit() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::create_dir_all(vault_dir.path().join("work/meetings"))?;
+    for idx in 0..5 {
+        std::fs::write(
+            vault_dir.path().join(format!("Standup {idx}.md")),
+            "# Standup\n\nQuarterly roadmap roadmap roadmap review.",
+        )?;
+    }
+    std::fs::write(
+        vault_dir.path().join("work/meetings/Planning.md"),
+        "---\nstatus: done\n---\n# Planning\n\nQuarterly roadmap review. #team/platform",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let options = SearchOptions {
+        limit: 1,
+        folders: vec!["work".to_string()],
+        tags: vec!["team".to_string()],
+        modified_after: Some("2000-01-01".to_string()),
+        properties: vec![PropertyFilter::parse("status=done").unwrap()],
+        ..Default::default()
+    };
+    search_vault(&config, "roadmap", &options, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_chunks(
+            conn, "roadmap", &options,
+        ))
+    })??;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].note_path, "work/meetings/Planning.md");
+
+    let future = SearchOptions {
+        modified_after: Some("2999-01-01".to_string()),
+        ..Default::default()
+    };
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_chunks(
+            conn, "roadmap", &future,
+        ))
+    })??;
+    assert!(results.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_search_group_by_note_boosts_title_matches() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Journal.md"),
+        "# Journal\n\n## Monday\n\nSourdough sourdough sourdough starter.\n\n## Tuesday\n\nFed the sourdough again.",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Baking.md"),
+        "---\naliases: [Sourdough Guide]\n---\n# Baking\n\nA sourdough loaf needs time.",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    search_vault_by_note(&config, "sourdough", &search_options(10), None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let mut boosts = config.search.note_boosts();
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_notes(
+            conn,
+            "sourdough",
+            &search_options(10),
+            &boosts,
+        ))
+    })??;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].note_path, "Baking.md");
+    assert!(results[0].boosts.title > 0.0);
+    let journal = &results[1];
+    assert_eq!(journal.note_path, "Journal.md");
+    assert_eq!(journal.chunk_count, 2);
+    assert_eq!(journal.heading_paths.len(), 2);
+
+    // Without boosts the note with more matches ranks first
+    boosts.title = 0.0;
+    boosts.heading = 0.0;
+    boosts.recency = 0.0;
+    boosts.inlinks = 0.0;
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_notes(
+            conn,
+            "sourdough",
+            &search_options(10),
+            &boosts,
+        ))
+    })??;
+    assert_eq!(results[0].note_path, "Journal.md");
+
+    Ok(())
+}
+
+/// Config indexing the sample vault into a temporary database
+fn sample_vault_config(db_dir: &tempfile::TempDir) -> obsidian_cli_inspector::config::Config {
+    obsidian_cli_inspector::config::Config {
+        vault_path: std::path::PathBuf::from("tests/test-vault"),
+        database_path: Some(db_dir.path().join("test.db")),
+        log_path: Some(db_dir.path().join("logs")),
+        exclude: Default::default(),
+        search: Default::default(),
+        graph: Default::default(),
+        llm: None,
+        embeddings: Default::default(),
+    }
+}
+
+fn matching_paths(
+    config: &obsidian_cli_inspector::config::Config,
+    query: &str,
+) -> Result<Vec<String>> {
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let results = db.conn().execute_query(|conn| {
+        Ok(obsidian_cli_inspector::query::search_chunks(
+            conn,
+            query,
+            &search_options(50),
+        ))
+    })??;
+    let mut paths: Vec<String> = results.into_iter().map(|r| r.note_path).collect();
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+#[test]
+fn test_search_tokenizer_change_rebuilds_index() -> Result<()> {
+    use obsidian_cli_inspector::config::FtsTokenizer;
+
+    let db_dir = tempfile::TempDir::new()?;
+    let mut config = sample_vault_config(&db_dir);
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+    assert!(matching_paths(&config, "think")?.is_empty());
+    assert!(matching_paths(&config, "ttelkast")?.is_empty());
+
+    // Stemming: `think` finds `thinking`, without reindexing any note
+    config.search.tokenizer = FtsTokenizer::Porter;
+    index_vault(&config, false, false, false, None)?;
+    assert_eq!(matching_paths(&config, "think")?, vec!["Ideas.md"]);
+
+    // Trigrams: substrings match, and the stemmed index is gone
+    config.search.tokenizer = FtsTokenizer::Trigram;
+    index_vault(&config, false, false, false, None)?;
+    assert!(matching_paths(&config, "ttelkast")?.contains(&"Zettelkasten Method.md".to_string()));
+    assert!(matching_paths(&config, "think")?.contains(&"Ideas.md".to_string()));
+    assert!(matching_paths(&config, "thinks")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_search_trigram_tokenizer_finds_cjk_text() -> Result<()> {
+    use obsidian_cli_inspector::config::FtsTokenizer;
+
+    let (vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Reading.md"),
+        "# 読書メモ\n\n今日は深い仕事について読みました。\n\n我们今天学习中文。",
+    )?;
+    config.search.tokenizer = FtsTokenizer::Trigram;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    assert_eq!(matching_paths(&config, "深い仕事")?, vec!["Reading.md"]);
+    assert_eq!(matching_paths(&config, "学习中")?, vec!["Reading.md"]);
+    assert_eq!(
+        matching_paths(&config, "heading:読書メ")?,
+        vec!["Reading.md"]
+    );
+    // Terms shorter than a trigram are scanned for
+    assert_eq!(matching_paths(&config, "中文")?, vec!["Reading.md"]);
+    assert_eq!(matching_paths(&config, "heading:読書")?, vec!["Reading.md"]);
+    assert!(matching_paths(&config, "中文 -今日")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_search_suggests_indexed_terms() -> Result<()> {
+    use obsidian_cli_inspector::config::FtsTokenizer;
+    use obsidian_cli_inspector::query::{corrected_query, suggest_terms};
+
+    let db_dir = tempfile::TempDir::new()?;
+    let mut config = sample_vault_config(&db_dir);
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+    search_vault(&config, "prodcutivity", &search_options(10), None)?;
+
+    let suggest = |config: &obsidian_cli_inspector::config::Config, query: &str| {
+        let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+        let suggestions = db
+            .conn()
+            .execute_query(|conn| Ok(suggest_terms(conn, query, 3)))??;
+        anyhow::Ok(suggestions)
+    };
+
+    let suggestions = suggest(&config, "deep prodcutivity")?;
+    assert_eq!(suggestions[0].suggestion, "productivity");
+    assert!(suggestions.iter().all(|s| s.term == "prodcutivity"));
+    assert_eq!(
+        corrected_query("deep prodcutivity", &suggestions).as_deref(),
+        Some("deep productivity")
+    );
+    assert!(suggest(&config, "deep productivity")?.is_empty());
+
+    // Stemmed terms are suggested once the index uses them
+    config.search.tokenizer = FtsTokenizer::Porter;
+    index_vault(&config, false, false, false, None)?;
+    assert!(suggest(&config, "thinking")?.is_empty());
+    assert!(suggest(&config, "thinkng")?
+        .iter()
+        .any(|s| s.suggestion == "think"));
+
+    Ok(())
+}
+
+#[test]
+fn test_search_grep_respects_exclusions_and_filters() -> Result<()> {
+    use obsidian_cli_inspector::query::{grep_notes, GrepOptions};
+
+    let (vault_dir, _db_dir, mut config) = common::setup_test_config()?;
+    config.exclude.patterns = vec![".trash/".to_string()];
+    std::fs::create_dir_all(vault_dir.path().join("work"))?;
+    std::fs::create_dir_all(vault_dir.path().join(".trash"))?;
+    std::fs::write(
+        vault_dir.path().join("work/Plan.md"),
+        "# Plan\n\n- TODO(ana) ship https://internal.corp/roadmap\n- done\n",
+    )?;
+    std::fs::write(
+        vault_dir.path().join(".trash/Old.md"),
+        "TODO(bo) https://internal.corp/old",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let options = GrepOptions {
+        context: 1,
+        ..Default::default()
+    };
+    grep_vault(&config, r"TODO\(\w+\)", &options, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let results = db.conn().execute_query(|conn| {
+        Ok(grep_notes(
+            conn,
+            &config.vault_path,
+            r"https?://internal\.corp/\S+",
+            &options,
+        ))
+    })??;
+    assert_eq!(results.len(), 1);
+    let found = &results[0];
+    assert_eq!(found.note_path, "work/Plan.md");
+    assert_eq!(found.note_title, "Plan");
+    assert_eq!((found.line, found.column), (3, 18));
+    assert_eq!(found.before[0].text, "");
+    assert_eq!(found.after[0].text, "- done");
+
+    let elsewhere = GrepOptions {
+        folders: vec!["notes".to_string()],
+        ..Default::default()
+    };
+    let results = db
+        .conn()
+        .execute_query(|conn| Ok(grep_notes(conn, &config.vault_path, "TODO", &elsewhere)))??;
+    assert!(results.is_empty());
+
+    assert!(grep_vault(&config, "TODO(", &GrepOptions::default(), None).is_err());
 
     Ok(())
 }
@@ -14,8 +14,14 @@ fn test_tags_by_single_tag() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test finding notes by tag
-    list_notes_by_tag(&config, &Some("learning".to_string()), false, None)?;
-    list_notes_by_tag(&config, &Some("productivity".to_string()), false, None)?;
+    list_notes_by_tag(&config, &Some("learning".to_string()), false, false, None)?;
+    list_notes_by_tag(
+        &config,
+        &Some("productivity".to_string()),
+        false,
+        false,
+        None,
+    )?;
 
     Ok(())
 }
@@ -30,7 +36,7 @@ fn test_tags_list_all() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test listing all tags
-    list_notes_by_tag(&config, &None, true, None)?;
+    list_notes_by_tag(&config, &None, true, false, None)?;
 
     Ok(())
 }
@@ -45,7 +51,13 @@ fn test_tags_nonexistent_tag() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test querying non-existent tag
-    list_notes_by_tag(&config, &Some("nonexistenttag".to_string()), false, None)?;
+    list_notes_by_tag(
+        &config,
+        &Some("nonexistenttag".to_string()),
+        false,
+        false,
+        None,
+    )?;
 
     Ok(())
 }
@@ -62,8 +74,14 @@ fn test_multiple_tags_per_note() -> Result<()> {
 
     // Home.md has tags: [learning, productivity]
     // Should appear in both tag queries
-    list_notes_by_tag(&config, &Some("learning".to_string()), false, None)?;
-    list_notes_by_tag(&config, &Some("productivity".to_string()), false, None)?;
+    list_notes_by_tag(&config, &Some("learning".to_string()), false, false, None)?;
+    list_notes_by_tag(
+        &config,
+        &Some("productivity".to_string()),
+        false,
+        false,
+        None,
+    )?;
 
     Ok(())
 }
@@ -79,8 +97,8 @@ fn test_tags_various_categories() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test various tag categories
-    list_notes_by_tag(&config, &Some("work".to_string()), false, None)?;
-    list_notes_by_tag(&config, &Some("creativity".to_string()), false, None)?;
+    list_notes_by_tag(&config, &Some("work".to_string()), false, false, None)?;
+    list_notes_by_tag(&config, &Some("creativity".to_string()), false, false, None)?;
 
     Ok(())
 }
@@ -96,8 +114,8 @@ fn test_tags_case_sensitive() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test if tags are case-sensitive
-    list_notes_by_tag(&config, &Some("Learning".to_string()), false, None)?;
-    list_notes_by_tag(&config, &Some("LEARNING".to_string()), false, None)?;
+    list_notes_by_tag(&config, &Some("Learning".to_string()), false, false, None)?;
+    list_notes_by_tag(&config, &Some("LEARNING".to_string()), false, false, None)?;
 
     Ok(())
 }
@@ -112,7 +130,142 @@ fn test_tags_no_args() -> Result<()> {
     index_vault(&config, false, false, false, None)?;
 
     // Test listing all tags with None and false (same as running `tags` without --all)
-    list_notes_by_tag(&config, &None, false, None)?;
+    list_notes_by_tag(&config, &None, false, false, None)?;
+
+    Ok(())
+}
+
+// CLI equivalent: cargo run -- --config test-config.toml search tags project --tree
+#[test]
+fn test_tags_nested_hierarchy() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Alpha.md"),
+        "# Alpha\n\n#project/alpha #project/alpha/backend",
+    )?;
+    std::fs::write(vault_dir.path().join("Beta.md"), "# Beta\n\n#project/beta")?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    list_notes_by_tag(&config, &Some("project".to_string()), false, false, None)?;
+    list_notes_by_tag(&config, &Some("project".to_string()), false, true, None)?;
+    show_tag_tree(&config, None, None)?;
+    show_tag_tree(&config, Some("project"), None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let nested = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::get_notes_by_tag(conn, "project", false)
+    })?;
+    assert_eq!(nested.len(), 2);
+    let exact = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::get_notes_by_tag(conn, "project", true)
+    })?;
+    assert!(exact.is_empty());
+
+    let tree = db
+        .conn()
+        .execute_query(|conn| obsidian_cli_inspector::query::tag_tree(conn, Some("project")))?;
+    assert_eq!(tree[0].note_count, 2);
+    let children: Vec<(&str, usize)> = tree[0]
+        .children
+        .iter()
+        .map(|c| (c.name.as_str(), c.note_count))
+        .collect();
+    assert_eq!(children, vec![("alpha", 1), ("beta", 1)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_diagnose_tags_finds_variants() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Standup.md"),
+        "# Standup\n\n#meeting #Project",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Retro.md"),
+        "# Retro\n\n#meetings #project",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Sync.md"),
+        "# Sync\n\n#Meeting-notes #2024",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    diagnose_tags_cmd(&config, None)?;
+    list_notes_by_tag(&config, &Some("PROJECT".to_string()), false, false, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let groups = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::find_similar_tags)?;
+    let meeting = groups
+        .iter()
+        .find(|g| g.variants.iter().any(|v| v.tag == "meeting"))
+        .expect("meeting variants should be grouped");
+    let mut spellings: Vec<&str> = meeting.variants.iter().map(|v| v.tag.as_str()).collect();
+    spellings.sort();
+    assert_eq!(spellings, vec!["Meeting-notes", "meeting", "meetings"]);
+
+    let project = db.conn().execute_query(|conn| {
+        obsidian_cli_inspector::query::get_notes_by_tag(conn, "PROJECT", true)
+    })?;
+    assert_eq!(project.len(), 2);
+
+    let tags = db
+        .conn()
+        .execute_query(obsidian_cli_inspector::query::list_tags)?;
+    assert!(!tags.iter().any(|t| t == "2024"));
+
+    Ok(())
+}
+
+#[test]
+fn test_analyze_tags_suggests_for_untagged_notes() -> Result<()> {
+    let (vault_dir, _db_dir, config) = common::setup_test_config()?;
+    std::fs::write(
+        vault_dir.path().join("Borrowing.md"),
+        "# Borrowing\n\nOwnership and borrowing in the compiler. #rust #programming",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Lifetimes.md"),
+        "# Lifetimes\n\nLifetimes extend borrowing rules. #rust #programming",
+    )?;
+    std::fs::write(
+        vault_dir.path().join("Scratch.md"),
+        "# Scratch\n\nMore on ownership and borrowing, see [[Borrowing]].",
+    )?;
+
+    // Setup
+    initialize_database(&config, false, None)?;
+    index_vault(&config, false, false, false, None)?;
+
+    let options = obsidian_cli_inspector::query::TagAnalysisOptions::default();
+    analyze_tags_cmd(&config, &options, None)?;
+
+    let db = obsidian_cli_inspector::db::Database::open(config.database_path())?;
+    let analysis = db
+        .conn()
+        .execute_query(|conn| obsidian_cli_inspector::query::analyze_tags(conn, &options))?;
+    assert!(analysis
+        .companions
+        .iter()
+        .any(|c| c.tag == "rust" && c.implies == "programming"));
+
+    let scratch = analysis
+        .suggestions
+        .iter()
+        .find(|note| note.note_path == "Scratch.md")
+        .expect("untagged note should get suggestions");
+    let tags: Vec<&str> = scratch.suggestions.iter().map(|s| s.tag.as_str()).collect();
+    assert!(tags.contains(&"rust"));
+    assert!(tags.contains(&"programming"));
 
     Ok(())
 }